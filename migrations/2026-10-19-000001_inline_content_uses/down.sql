@@ -0,0 +1,3 @@
+DROP TABLE inline_voice_awards;
+DROP TABLE inline_gif_uses;
+DROP TABLE inline_voice_uses;
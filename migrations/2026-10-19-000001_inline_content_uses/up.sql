@@ -0,0 +1,26 @@
+-- Usage of approved inline content, bumped from chosen inline results and
+-- bucketed by calendar month: the lifetime popularity the browsers sort by is
+-- a sum over the buckets, and the monthly "top grunt" is a single bucket.
+CREATE TABLE inline_voice_uses (
+    voice_id SMALLINT NOT NULL REFERENCES inline_voices (id) ON DELETE CASCADE,
+    month DATE NOT NULL,
+    uses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (voice_id, month)
+);
+
+CREATE TABLE inline_gif_uses (
+    gif_id SMALLINT NOT NULL REFERENCES inline_gifs (id) ON DELETE CASCADE,
+    month DATE NOT NULL,
+    uses INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (gif_id, month)
+);
+
+-- One row per announced month. The primary key is what keeps a restart (or
+-- two replicas) from rewarding the same month twice.
+CREATE TABLE inline_voice_awards (
+    month DATE NOT NULL PRIMARY KEY,
+    voice_id SMALLINT NOT NULL REFERENCES inline_voices (id),
+    uid INTEGER NOT NULL REFERENCES users (id),
+    uses INTEGER NOT NULL,
+    awarded_at TIMESTAMP NOT NULL
+);
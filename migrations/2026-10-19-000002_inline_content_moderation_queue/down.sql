@@ -0,0 +1,13 @@
+-- Pending and rejected rows have no meaning to the old code, which treated
+-- every row as approved-or-hidden by `status` alone.
+DELETE FROM inline_voices WHERE status <> 1;
+DELETE FROM inline_gifs WHERE status <> 1;
+
+ALTER TABLE inline_gifs
+    DROP COLUMN submitted_at,
+    DROP COLUMN review_message_id;
+
+ALTER TABLE inline_voices
+    DROP COLUMN submitted_at,
+    DROP COLUMN review_message_id,
+    DROP COLUMN file_id;
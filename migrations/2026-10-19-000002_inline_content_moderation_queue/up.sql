@@ -0,0 +1,15 @@
+-- Submissions are stored the moment they arrive as pending (status 0) and
+-- later flipped to approved (1) or rejected (2), instead of only being
+-- inserted on approval. That makes the review queue queryable and lets a
+-- lost review card be re-sent.
+--
+-- `file_id` is what a voice card is re-sent from; rows approved before this
+-- migration never need re-sending, so they keep an empty one.
+ALTER TABLE inline_voices
+    ADD COLUMN file_id VARCHAR(128) NOT NULL DEFAULT '',
+    ADD COLUMN review_message_id INTEGER,
+    ADD COLUMN submitted_at TIMESTAMP;
+
+ALTER TABLE inline_gifs
+    ADD COLUMN review_message_id INTEGER,
+    ADD COLUMN submitted_at TIMESTAMP;
@@ -0,0 +1,14 @@
+-- Bot staff. `CREATOR_ID` from the environment is always treated as an owner
+-- and needs no row here; everyone else is granted a role by an owner.
+-- Roles are ordered: 1 moderator < 2 admin < 3 owner, so a permission check
+-- is a single comparison.
+--
+-- `granted_by` is informational and deliberately not a foreign key: a second
+-- reference to `users` would make the `staff -> users` join ambiguous to
+-- diesel.
+CREATE TABLE staff (
+    uid INTEGER NOT NULL PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    role SMALLINT NOT NULL CHECK (role BETWEEN 1 AND 3),
+    granted_by INTEGER,
+    granted_at TIMESTAMP NOT NULL
+);
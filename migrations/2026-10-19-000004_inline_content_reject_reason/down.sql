@@ -0,0 +1,2 @@
+ALTER TABLE inline_gifs DROP COLUMN reject_reason;
+ALTER TABLE inline_voices DROP COLUMN reject_reason;
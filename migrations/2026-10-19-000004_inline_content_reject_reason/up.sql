@@ -0,0 +1,4 @@
+-- Why a submission was rejected, as `enums::RejectReason`. NULL for approved
+-- and pending rows, and for rejections made before reasons existed.
+ALTER TABLE inline_voices ADD COLUMN reject_reason SMALLINT;
+ALTER TABLE inline_gifs ADD COLUMN reject_reason SMALLINT;
@@ -0,0 +1 @@
+ALTER TABLE inline_gifs DROP COLUMN caption;
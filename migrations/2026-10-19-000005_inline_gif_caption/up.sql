@@ -0,0 +1,3 @@
+-- Optional caption under a GIF sent from the browser, set by `/content`.
+-- Same width as `inline_voices.caption`; empty means none.
+ALTER TABLE inline_gifs ADD COLUMN caption VARCHAR(64) NOT NULL DEFAULT '';
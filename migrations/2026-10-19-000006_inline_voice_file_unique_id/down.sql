@@ -0,0 +1,5 @@
+DROP INDEX inline_voices_file_unique_id_idx;
+
+ALTER TABLE inline_voices
+    DROP COLUMN fingerprint,
+    DROP COLUMN file_unique_id;
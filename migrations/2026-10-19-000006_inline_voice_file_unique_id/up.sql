@@ -0,0 +1,16 @@
+-- Voices get the same duplicate check GIFs already have. `file_unique_id`
+-- only comes from the Bot API, so existing rows start NULL and are filled
+-- in by the backfill the bot runs on startup. Rows approved before
+-- `file_id` was stored are looked up through the channel post in `url`.
+--
+-- `fingerprint` is a 64-bit hash of the loudness contour, compared by
+-- Hamming distance to flag re-recorded near-duplicates on the review card.
+-- NULL when the audio couldn't be downloaded or decoded.
+--
+-- Not unique: older duplicates already exist and are left to moderators.
+ALTER TABLE inline_voices
+    ADD COLUMN file_unique_id VARCHAR(64),
+    ADD COLUMN fingerprint BIGINT;
+
+CREATE INDEX inline_voices_file_unique_id_idx
+    ON inline_voices (file_unique_id);
@@ -0,0 +1,30 @@
+-- `/repost` jobs. The audience is snapshotted into `broadcast_targets` when
+-- the job is created, and every delivery is recorded as it happens, so a
+-- restart resumes where it stopped instead of re-sending to everyone.
+--
+-- status: 0 running, 1 paused, 2 cancelled, 3 done.
+CREATE TABLE broadcasts (
+    id SERIAL PRIMARY KEY,
+    from_chat_id BIGINT NOT NULL,
+    message_id INTEGER NOT NULL,
+    created_by INTEGER NOT NULL REFERENCES users (id),
+    report_chat_id BIGINT NOT NULL,
+    status SMALLINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL,
+    finished_at TIMESTAMP
+);
+
+-- kind: 0 chat, 1 user — decides what a "bot was kicked/blocked" error
+-- deactivates. status: 0 pending, 1 sent, 2 failed.
+CREATE TABLE broadcast_targets (
+    broadcast_id INTEGER NOT NULL REFERENCES broadcasts (id) ON DELETE CASCADE,
+    chat_id BIGINT NOT NULL,
+    kind SMALLINT NOT NULL,
+    status SMALLINT NOT NULL DEFAULT 0,
+    sent_at TIMESTAMP,
+    PRIMARY KEY (broadcast_id, chat_id)
+);
+
+-- The worker's "next pending batch" and the progress counts.
+CREATE INDEX broadcast_targets_broadcast_id_status_idx
+    ON broadcast_targets (broadcast_id, status);
@@ -0,0 +1 @@
+DROP TABLE duel_log;
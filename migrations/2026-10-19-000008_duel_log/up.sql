@@ -0,0 +1,14 @@
+-- One row per finished hand-pig duel. `inline_users.win`/`rout` only keep
+-- lifetime totals, which can't answer "how many duels this week".
+--
+-- A draw credits both sides, so winner/looser is just the order the duel
+-- resolved them in.
+CREATE TABLE duel_log (
+    id SERIAL PRIMARY KEY,
+    winner_uid INTEGER NOT NULL REFERENCES users (id),
+    looser_uid INTEGER NOT NULL REFERENCES users (id),
+    damage INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX duel_log_created_at_idx ON duel_log (created_at);
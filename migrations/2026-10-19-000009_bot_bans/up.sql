@@ -0,0 +1,20 @@
+-- Staff bans from the bot. Kept apart from `users.banned`, which tracks
+-- whether the user blocked the bot and is cleared as soon as they unblock.
+CREATE TABLE bot_bans (
+    uid INTEGER PRIMARY KEY REFERENCES users (id),
+    banned_at TIMESTAMP NOT NULL
+);
+
+-- Every staff action against a user or their pigs, with the note given.
+--
+-- action: see `ModerationAction`.
+CREATE TABLE moderation_log (
+    id SERIAL PRIMARY KEY,
+    staff_uid INTEGER NOT NULL REFERENCES users (id),
+    target_uid INTEGER NOT NULL REFERENCES users (id),
+    action SMALLINT NOT NULL,
+    note VARCHAR(256) NOT NULL,
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX moderation_log_target_uid_idx ON moderation_log (target_uid);
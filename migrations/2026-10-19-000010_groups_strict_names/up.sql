@@ -0,0 +1,4 @@
+-- `!epyc names +`: check pig names in this chat against the blocklist as
+-- substrings, not just whole words. Separate from `settings`, which holds
+-- the greetings flag as a whole value rather than as bits.
+ALTER TABLE groups ADD COLUMN strict_names BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,10 @@
+-- `/settings` switches. A row only exists once the user changed one; until
+-- then `UserSettings::new` holds the defaults: every notification off, since
+-- they are opt-in, and announcements mentioning the user with a link.
+CREATE TABLE user_settings (
+    uid INTEGER PRIMARY KEY REFERENCES users (id),
+    notify_day_pig BOOLEAN NOT NULL DEFAULT FALSE,
+    notify_duels BOOLEAN NOT NULL DEFAULT FALSE,
+    notify_achievements BOOLEAN NOT NULL DEFAULT FALSE,
+    mention_me BOOLEAN NOT NULL DEFAULT TRUE
+);
@@ -0,0 +1,2 @@
+-- Irreversible by design: the old captions were never shown to anyone.
+SELECT 1;
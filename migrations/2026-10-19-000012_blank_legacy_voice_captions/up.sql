@@ -0,0 +1,6 @@
+-- `inline_voices.caption` came over from MySQL, where nothing read it and
+-- the browser titled every voice "#N" from the locale. `/content` now shows
+-- a non-empty caption instead, so the leftovers would suddenly replace the
+-- localized titles. This ships together with `/content`, so every caption
+-- in the table is still a leftover.
+UPDATE inline_voices SET caption = '' WHERE caption <> '';
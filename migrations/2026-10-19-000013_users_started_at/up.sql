@@ -0,0 +1,5 @@
+-- When the user first started the bot in private; `created_at` is when the
+-- bot first saw them in any chat. Rows from before this column only have
+-- the latter, so it stands in for everyone who has started the bot.
+ALTER TABLE users ADD COLUMN started_at TIMESTAMP;
+UPDATE users SET started_at = created_at WHERE started;
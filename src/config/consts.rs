@@ -1,9 +1,12 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use ahash::{AHashSet, HashMap, HashSet};
 use teloxide::types::{ParseMode, UserId};
 use tokio::sync::{Mutex, RwLock};
 
+use crate::services::rate_limit::Limits;
+
 pub const BOT_PARSE_MODE: ParseMode = ParseMode::Html;
 pub const DEFAULT_LANG_TAG: &str = "uk";
 pub const TOP_LIMIT: i64 = 50;
@@ -25,13 +28,56 @@ pub const LOUDER_DEFAULT_RATIO: f32 = 2.0;
 pub const INLINE_NAME_SET_LIMIT: usize = 20;
 pub const INLINE_VOICE_REWARD_KG: i32 = 250;
 pub const INLINE_GIF_REWARD_KG: i32 = 250;
-/// `inline_voices.status` / `inline_gifs.status` value the browse queries
-/// filter on. Rows are only ever written once the creator has approved them,
-/// so they are inserted already approved.
+/// Paid once a month to whoever submitted the most used voice.
+pub const INLINE_TOP_VOICE_REWARD_KG: i32 = 1000;
+/// Newest uploads shown ahead of the popularity order, so they get a chance
+/// to be used at all.
+pub const INLINE_NEW_CONTENT_COUNT: usize = 5;
+/// `inline_voices.status` / `inline_gifs.status`. A submission is stored as
+/// pending when it arrives and flipped exactly once by the moderator; only
+/// approved rows are browsable. `/content` toggles approved and hidden.
+pub const INLINE_CONTENT_PENDING: i16 = 0;
 pub const INLINE_CONTENT_APPROVED: i16 = 1;
+pub const INLINE_CONTENT_REJECTED: i16 = 2;
+pub const INLINE_CONTENT_HIDDEN: i16 = 3;
+/// `inline_voices.caption` / `inline_gifs.caption` are VARCHAR(64).
+pub const INLINE_CAPTION_LIMIT: usize = 64;
+/// `moderation_log.note` is VARCHAR(256).
+pub const MODERATION_NOTE_LIMIT: usize = 256;
+/// A voice at most this many fingerprint bits away from another is flagged
+/// on its review card as a likely re-recording.
+pub const VOICE_FINGERPRINT_MAX_DISTANCE: u32 = 6;
+/// Per kind in `/mysubmissions`, newest first.
+pub const MY_SUBMISSIONS_LIMIT: usize = 20;
+/// The `/settings` flag picker: a grid of this many rows of flags per page.
+pub const SETTINGS_FLAGS_PER_ROW: usize = 6;
+pub const SETTINGS_FLAG_ROWS: usize = 4;
+/// `broadcasts.status`. Only running jobs are picked up by the worker;
+/// cancelled and done are final.
+pub const BROADCAST_RUNNING: i16 = 0;
+pub const BROADCAST_PAUSED: i16 = 1;
+pub const BROADCAST_CANCELLED: i16 = 2;
+pub const BROADCAST_DONE: i16 = 3;
+/// `broadcast_targets.kind`.
+pub const BROADCAST_TARGET_CHAT: i16 = 0;
+pub const BROADCAST_TARGET_USER: i16 = 1;
+/// `broadcast_targets.status`. Claimed is taken by a worker and being sent;
+/// it still counts as pending in the progress.
+pub const BROADCAST_TARGET_PENDING: i16 = 0;
+pub const BROADCAST_TARGET_SENT: i16 = 1;
+pub const BROADCAST_TARGET_FAILED: i16 = 2;
+pub const BROADCAST_TARGET_CLAIMED: i16 = 3;
 pub const HAND_PIG_ADDITION_ON_SUPPORTED: i32 = 500;
 pub const HAND_PIG_ADDITION_ON_SUBSCRIBED: i32 = 100;
 pub const CHARTS_PIXELS_WIDTH: u32 = 1280;
+/// Command buckets: ten per person a minute, thirty per chat, with the
+/// whole minute's worth allowed as a burst. See `MyCommands::cost`.
+pub const RATE_LIMIT_USER: Limits =
+    Limits { capacity: 10, refill: Duration::from_secs(6) };
+pub const RATE_LIMIT_CHAT: Limits =
+    Limits { capacity: 30, refill: Duration::from_secs(2) };
+/// A throttled user or chat hears about it at most once per window.
+pub const RATE_LIMIT_WARN_WINDOW: Duration = Duration::from_secs(60);
 
 pub struct ResetVoteState {
     pub initiator_id: UserId,
@@ -1,16 +1,33 @@
+//! Settings from `config.toml` and the environment.
+//!
+//! Every setting has an environment variable, which wins over the file, and
+//! a key in the file: the variable's name in lowercase, or a key of the
+//! `[db_pool]` table for the `DB_POOL_*` ones. `BOT_CONFIG_FILE` picks
+//! another file; without it a missing `config.toml` is fine. A key in the
+//! file that no setting reads, e.g. a misspelled one, is a problem too.
+//! Everything is checked before the first error is reported, so one run
+//! lists every problem.
+
+use ahash::AHashSet;
 use std::{
     env,
+    fmt::{self, Display},
+    fs, io,
     str::FromStr,
     sync::{LazyLock, OnceLock},
+    time::Duration,
 };
 use teloxide::types::Me;
 use url::Url;
 
 use crate::types::MyBot;
 
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
 pub struct Config {
-    #[allow(unused)]
     pub teloxide_token: String,
+    // A local Bot API server instead of api.telegram.org
+    pub api_url: Option<Url>,
     pub webhook_url: Url,
     pub webhook_port: u16,
     pub database_url: Url,
@@ -23,41 +40,307 @@ pub struct Config {
     // Channel with contents check, especially grunts
     pub content_check_channel_id: i64,
     pub content_check_channel_name: String,
-    // Bot creator for manage
+    // Bot creator, always an owner; other staff live in the `staff` table
     pub creator_id: u64,
     pub prometheus_token: String,
     pub gif_content_channel_id: i64,
     pub chat_link: String,
     pub log_group_id: i64,
+    pub db_pool: DbPoolConfig,
+}
+
+/// Optional, see [`DbPoolConfig::default`].
+pub struct DbPoolConfig {
+    pub max_size: usize,
+    /// For a free connection when all are taken.
+    pub wait_timeout: Duration,
+    /// For a new connection to be established.
+    pub create_timeout: Duration,
+    /// For the health check of a connection being handed out again.
+    pub recycle_timeout: Duration,
+}
+
+impl Default for DbPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_size: 16,
+            wait_timeout: Duration::from_secs(10),
+            create_timeout: Duration::from_secs(5),
+            recycle_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Every problem found in one pass, one per line.
+#[derive(Debug)]
+pub struct ConfigError {
+    pub problems: Vec<String>,
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration ({}):", self.problems.len())?;
+
+        for problem in &self.problems {
+            write!(f, "\n  - {problem}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Config {
+    /// From the process environment and the config file.
+    pub fn load() -> Result<Self, ConfigError> {
+        let explicit = env::var("BOT_CONFIG_FILE").ok();
+        let path = explicit.as_deref().unwrap_or(DEFAULT_CONFIG_FILE);
+
+        let file = match fs::read_to_string(path) {
+            Ok(text) => Some(text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                if explicit.is_some() {
+                    let problem = format!("config file {path}: not found");
+                    return Err(ConfigError { problems: vec![problem] });
+                }
+                None
+            },
+            Err(err) => {
+                let problem = format!("config file {path}: {err}");
+                return Err(ConfigError { problems: vec![problem] });
+            },
+        };
+
+        Self::load_with(file.as_deref(), |name| env::var(name).ok())
+    }
+
+    /// `file` is the TOML text, `env` looks up one variable.
+    pub fn load_with(
+        file: Option<&str>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let table = match file.map(toml::from_str::<toml::Table>) {
+            Some(Ok(table)) => table,
+            Some(Err(err)) => {
+                let problem = format!("config file: {}", err.message());
+                return Err(ConfigError { problems: vec![problem] });
+            },
+            None => toml::Table::new(),
+        };
+
+        let mut l = Loader {
+            table,
+            env: &env,
+            read: AHashSet::new(),
+            problems: vec![],
+        };
+
+        let teloxide_token = l.required("TELOXIDE_TOKEN");
+        let api_url = l.maybe("TELOXIDE_API_URL");
+        let webhook_url = l.required("WEBHOOK_URL");
+        let webhook_port = l.required("WEBHOOK_PORT");
+        let database_url = l.required("DATABASE_URL");
+        let photostock_url = l.required("PHOTOSTOCK_URL");
+        let channel_id = l.required("CHANNEL_ID");
+        let channel_name = l.required("CHANNEL_NAME");
+        let content_check_channel_id = l.required("CONTENT_CHECK_CHANNEL_ID");
+        let content_check_channel_name =
+            l.required("CONTENT_CHECK_CHANNEL_NAME");
+        let creator_id = l.required("CREATOR_ID");
+        let prometheus_token = l.required("PROMETHEUS_TOKEN");
+        let gif_content_channel_id = l.required("GIF_CONTENT_CHANNEL_ID");
+        let chat_link = l.required("CHAT_LINK");
+        let log_group_id = l.required("LOG_GROUP_ID");
+
+        let default = DbPoolConfig::default();
+        let db_pool = DbPoolConfig {
+            max_size: l.optional("DB_POOL_MAX_SIZE", default.max_size),
+            wait_timeout: l
+                .seconds("DB_POOL_WAIT_TIMEOUT", default.wait_timeout),
+            create_timeout: l
+                .seconds("DB_POOL_CREATE_TIMEOUT", default.create_timeout),
+            recycle_timeout: l
+                .seconds("DB_POOL_RECYCLE_TIMEOUT", default.recycle_timeout),
+        };
+
+        if db_pool.max_size == 0 {
+            l.problem("DB_POOL_MAX_SIZE", "must be at least 1");
+        }
+
+        l.unknown_keys();
+
+        if !l.problems.is_empty() {
+            return Err(ConfigError { problems: l.problems });
+        }
+
+        // A required setting that is missing or invalid is a problem, so
+        // each is there by now.
+        let checked = "required setting without a problem";
+
+        Ok(Self {
+            teloxide_token: teloxide_token.expect(checked),
+            api_url,
+            webhook_url: webhook_url.expect(checked),
+            webhook_port: webhook_port.expect(checked),
+            database_url: database_url.expect(checked),
+            photostock_url: photostock_url.expect(checked),
+            channel_id: channel_id.expect(checked),
+            channel_name: channel_name.expect(checked),
+            content_check_channel_id: content_check_channel_id.expect(checked),
+            content_check_channel_name: content_check_channel_name
+                .expect(checked),
+            creator_id: creator_id.expect(checked),
+            prometheus_token: prometheus_token.expect(checked),
+            gif_content_channel_id: gif_content_channel_id.expect(checked),
+            chat_link: chat_link.expect(checked),
+            log_group_id: log_group_id.expect(checked),
+            db_pool,
+        })
+    }
+}
+
+struct Loader<'a> {
+    table: toml::Table,
+    env: &'a dyn Fn(&str) -> Option<String>,
+    /// Every setting asked for, by name, whether or not it was set.
+    read: AHashSet<String>,
+    problems: Vec<String>,
+}
+
+impl Loader<'_> {
+    /// `CHANNEL_ID` is `channel_id`, `DB_POOL_MAX_SIZE` is `max_size` in
+    /// `[db_pool]`.
+    fn file_value(&self, name: &str) -> Option<Result<String, String>> {
+        let key = name.to_lowercase();
+
+        let value = match key.strip_prefix("db_pool_") {
+            Some(key) => self.table.get("db_pool")?.as_table()?.get(key)?,
+            None => self.table.get(&key)?,
+        };
+
+        Some(match value {
+            toml::Value::String(value) => Ok(value.clone()),
+            toml::Value::Integer(value) => Ok(value.to_string()),
+            toml::Value::Float(value) => Ok(value.to_string()),
+            toml::Value::Boolean(value) => Ok(value.to_string()),
+            _ => Err(format!("{key} in the config file is not a plain value")),
+        })
+    }
+
+    fn raw(&mut self, name: &str) -> Option<String> {
+        self.read.insert(name.to_owned());
+
+        if let Some(value) = (self.env)(name) {
+            return Some(value);
+        }
+
+        match self.file_value(name)? {
+            Ok(value) => Some(value),
+            Err(problem) => {
+                self.problems.push(problem);
+                None
+            },
+        }
+    }
+
+    fn parse<T: FromStr>(&mut self, name: &str, raw: &str) -> Option<T>
+    where
+        T::Err: Display,
+    {
+        match raw.parse() {
+            Ok(value) => Some(value),
+            Err(err) => {
+                // Not the value itself, which may well be a secret.
+                self.problem(name, &format!("is not valid: {err}"));
+                None
+            },
+        }
+    }
+
+    fn required<T: FromStr>(&mut self, name: &str) -> Option<T>
+    where
+        T::Err: Display,
+    {
+        let Some(raw) = self.raw(name) else {
+            self.problem(name, "is not defined");
+            return None;
+        };
+
+        self.parse(name, &raw)
+    }
+
+    /// For a setting without a default: `None` when it is not set, and for
+    /// an invalid value, which is still reported.
+    fn maybe<T: FromStr>(&mut self, name: &str) -> Option<T>
+    where
+        T::Err: Display,
+    {
+        let raw = self.raw(name)?;
+
+        self.parse(name, &raw)
+    }
+
+    /// The default also stands in for an invalid value, which is still
+    /// reported.
+    fn optional<T: FromStr>(&mut self, name: &str, default: T) -> T
+    where
+        T::Err: Display,
+    {
+        self.raw(name).and_then(|raw| self.parse(name, &raw)).unwrap_or(default)
+    }
+
+    fn seconds(&mut self, name: &str, default: Duration) -> Duration {
+        Duration::from_secs(self.optional(name, default.as_secs()))
+    }
+
+    /// Keys of the file no setting has read, so call it after all of them.
+    fn unknown_keys(&mut self) {
+        let mut unknown = vec![];
+
+        for (key, value) in &self.table {
+            match (key.as_str(), value.as_table()) {
+                ("db_pool", Some(pool)) => unknown.extend(
+                    pool.keys()
+                        .filter(|key| !self.was_read(&format!("db_pool_{key}")))
+                        .map(|key| format!("db_pool.{key}")),
+                ),
+                ("db_pool", None) => unknown.push(key.clone()),
+                _ if !self.was_read(key) => unknown.push(key.clone()),
+                _ => {},
+            }
+        }
+
+        for key in unknown {
+            self.problems
+                .push(format!("{key} in the config file is not a setting"));
+        }
+    }
+
+    fn was_read(&self, key: &str) -> bool {
+        self.read.contains(&key.to_uppercase())
+    }
+
+    fn problem(&mut self, name: &str, what: &str) {
+        self.problems.push(format!("{name} {what}"));
+    }
 }
 
-fn _from_env<T: FromStr>(name: &str) -> T
-where
-    <T as FromStr>::Err: std::fmt::Debug,
-{
-    env::var(name)
-        .unwrap_or_else(|_| panic!("{} is not defined!", name))
-        .parse::<T>()
-        .unwrap_or_else(|_| panic!("{} is not valid!", name))
+static LOADED: OnceLock<Config> = OnceLock::new();
+
+/// Keeps the config `setup::setup_config` just checked, so [`BOT_CONFIG`]
+/// doesn't read the file a second time: a change in between would panic a
+/// startup that already passed the check. `Err` hands it back if
+/// [`BOT_CONFIG`] was read before.
+pub fn init_config(config: Config) -> Result<(), Config> {
+    LOADED.set(config)
 }
 
 /// Holds no `Me`/`Bot`: those only exist after a live `get_me()`, and folding
 /// them in made reading e.g. `database_url` require Telegram auth.
-pub static BOT_CONFIG: LazyLock<Config> = LazyLock::new(|| Config {
-    teloxide_token: _from_env("TELOXIDE_TOKEN"),
-    webhook_url: _from_env("WEBHOOK_URL"),
-    webhook_port: _from_env("WEBHOOK_PORT"),
-    database_url: _from_env("DATABASE_URL"),
-    photostock_url: _from_env("PHOTOSTOCK_URL"),
-    channel_id: _from_env("CHANNEL_ID"),
-    channel_name: _from_env("CHANNEL_NAME"),
-    content_check_channel_id: _from_env("CONTENT_CHECK_CHANNEL_ID"),
-    content_check_channel_name: _from_env("CONTENT_CHECK_CHANNEL_NAME"),
-    creator_id: _from_env("CREATOR_ID"),
-    prometheus_token: _from_env("PROMETHEUS_TOKEN"),
-    gif_content_channel_id: _from_env("GIF_CONTENT_CHANNEL_ID"),
-    chat_link: _from_env("CHAT_LINK"),
-    log_group_id: _from_env("LOG_GROUP_ID"),
+///
+/// Panics with the whole report on a bad config; the bot checks it before
+/// [`init_config`], so that only happens to tests, which load it here.
+pub static BOT_CONFIG: LazyLock<&'static Config> = LazyLock::new(|| {
+    LOADED.get_or_init(|| Config::load().unwrap_or_else(|err| panic!("{err}")))
 });
 
 pub static BOT_ME: OnceLock<Me> = OnceLock::new();
@@ -71,3 +354,155 @@ pub fn bot_me() -> &'static Me {
 pub fn bot_static() -> &'static MyBot {
     BOT_STATIC.get().expect("BOT_STATIC is not set!")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+        teloxide_token = "0000000000:FILE"
+        webhook_url = "https://example.invalid/"
+        webhook_port = 8443
+        database_url = "postgres://test@localhost/test"
+        photostock_url = "https://example.invalid/photos/"
+        channel_id = -1001000000000
+        channel_name = "test_channel"
+        content_check_channel_id = -1001000000001
+        content_check_channel_name = "test_content"
+        creator_id = 1
+        prometheus_token = "test_token"
+        gif_content_channel_id = -1001000000002
+        chat_link = "test_chat"
+        log_group_id = -1001000000003
+
+        [db_pool]
+        max_size = 4
+    "#;
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    fn problems(result: Result<Config, ConfigError>) -> Vec<String> {
+        result.err().expect("config should be invalid").problems
+    }
+
+    #[test]
+    fn a_complete_file_is_enough() {
+        let config = Config::load_with(Some(FILE), no_env).unwrap();
+
+        assert_eq!(config.teloxide_token, "0000000000:FILE");
+        assert_eq!(config.webhook_port, 8443);
+        assert_eq!(config.channel_id, -1_001_000_000_000);
+        assert_eq!(config.db_pool.max_size, 4);
+    }
+
+    #[test]
+    fn optional_settings_fall_back_to_their_defaults() {
+        let config = Config::load_with(Some(FILE), no_env).unwrap();
+        let default = DbPoolConfig::default();
+
+        assert_eq!(config.db_pool.wait_timeout, default.wait_timeout);
+        assert_eq!(config.db_pool.recycle_timeout, default.recycle_timeout);
+        assert!(config.api_url.is_none());
+    }
+
+    #[test]
+    fn a_local_bot_api_server_can_be_set() {
+        let env = |name: &str| {
+            (name == "TELOXIDE_API_URL")
+                .then(|| "http://localhost:8081/".into())
+        };
+
+        let config = Config::load_with(Some(FILE), env).unwrap();
+
+        assert_eq!(
+            config.api_url.map(|url| url.to_string()).as_deref(),
+            Some("http://localhost:8081/")
+        );
+
+        let file = format!("teloxide_api_url = \"not a url\"\n{FILE}");
+        let problems = problems(Config::load_with(Some(&file), no_env));
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert!(problems[0].starts_with("TELOXIDE_API_URL is not valid"));
+    }
+
+    #[test]
+    fn the_environment_wins_over_the_file() {
+        let env = |name: &str| match name {
+            "WEBHOOK_PORT" => Some("9000".to_owned()),
+            "DB_POOL_WAIT_TIMEOUT" => Some("3".to_owned()),
+            _ => None,
+        };
+
+        let config = Config::load_with(Some(FILE), env).unwrap();
+
+        assert_eq!(config.webhook_port, 9000);
+        assert_eq!(config.db_pool.wait_timeout, Duration::from_secs(3));
+    }
+
+    #[test]
+    fn every_problem_is_reported_at_once() {
+        let env = |name: &str| match name {
+            "WEBHOOK_PORT" => Some("http".to_owned()),
+            "CREATOR_ID" => Some("-1".to_owned()),
+            "DB_POOL_MAX_SIZE" => Some("0".to_owned()),
+            _ => None,
+        };
+        let file = FILE.replace("chat_link = \"test_chat\"", "");
+
+        let problems = problems(Config::load_with(Some(&file), env));
+        let named = |name: &str| problems.iter().any(|p| p.starts_with(name));
+
+        assert_eq!(problems.len(), 4, "{problems:?}");
+        assert!(named("WEBHOOK_PORT is not valid"));
+        assert!(named("CREATOR_ID is not valid"));
+        assert!(named("CHAT_LINK is not defined"));
+        assert!(named("DB_POOL_MAX_SIZE must be at least 1"));
+    }
+
+    #[test]
+    fn without_a_file_every_required_setting_is_missing() {
+        let problems = problems(Config::load_with(None, no_env));
+
+        assert_eq!(problems.len(), 14);
+        assert!(problems.iter().all(|p| p.ends_with("is not defined")));
+    }
+
+    #[test]
+    fn a_key_no_setting_reads_is_reported() {
+        let file = format!("webhook_prot = 8443\n{FILE}max_sise = 8\n");
+
+        let problems = problems(Config::load_with(Some(&file), no_env));
+
+        assert_eq!(problems.len(), 2, "{problems:?}");
+        for key in ["webhook_prot", "db_pool.max_sise"] {
+            let problem = format!("{key} in the config file is not a setting");
+            assert!(problems.contains(&problem), "{problems:?}");
+        }
+    }
+
+    #[test]
+    fn a_broken_file_is_reported_as_such() {
+        let problems = problems(Config::load_with(Some("port = "), no_env));
+
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("config file:"), "{problems:?}");
+    }
+
+    #[test]
+    fn the_report_lists_one_problem_per_line() {
+        let err = ConfigError {
+            problems: vec![
+                "A is not defined".into(),
+                "B is not defined".into(),
+            ],
+        };
+
+        assert_eq!(
+            err.to_string(),
+            "Invalid configuration (2):\n  - A is not defined\n  - B is not \
+             defined"
+        );
+    }
+}
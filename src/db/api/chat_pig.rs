@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use ahash::AHashMap;
 use chrono::{Duration, NaiveDate, NaiveDateTime};
-use diesel::prelude::*;
+use diesel::{dsl::case_when, prelude::*};
 use diesel_async::RunQueryDsl;
 
 use crate::{
@@ -10,6 +10,7 @@ use crate::{
         ACTIVE_GROUP_MIN_PIGS, CHAT_PIG_START_MASS, TOP_LIMIT,
         TOP_LIMIT_WITH_CHARTS,
     },
+    db::api::TimedConn,
     db::models::{Game, GrowLog, GrowLogAdd, User},
     types::{DbPool, MyResult},
 };
@@ -24,6 +25,10 @@ impl ChatPig {
         Self { pool }
     }
 
+    async fn conn(&self, method: &'static str) -> MyResult<TimedConn> {
+        TimedConn::get(&self.pool, "chat_pig", method).await
+    }
+
     pub async fn get_chat_pig(
         &self,
         id_user: i64,
@@ -39,7 +44,7 @@ impl ChatPig {
             .filter(users::user_id.eq(&id_user))
             .filter(groups::chat_id.eq(&id_chat))
             .select(Game::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_chat_pig").await?)
             .await
             .optional()?;
 
@@ -59,7 +64,7 @@ impl ChatPig {
             .order(mass.desc())
             .limit(1)
             .select(Game::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_biggest_chat_pig").await?)
             .await
             .optional()?;
 
@@ -92,7 +97,7 @@ impl ChatPig {
                         .filter(groups::chat_id.eq(id_chat)),
                 ),
             )
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("set_chat_pig_name").await?)
             .await?;
 
         Ok(())
@@ -109,12 +114,34 @@ impl ChatPig {
         diesel::update(game)
             .set((mass.eq(other_mass), date.eq(cur_date)))
             .filter(id.eq(id_game))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("set_chat_pig_mass_n_date").await?)
             .await?;
 
         Ok(())
     }
 
+    /// Shifts the mass by `delta` and returns the result. Floors at 1, as
+    /// `/grow` never takes a pig below that.
+    pub async fn adjust_chat_pig_mass(
+        &self,
+        id_game: i32,
+        delta: i32,
+    ) -> MyResult<i32> {
+        use crate::db::schema::game::dsl::*;
+
+        let shifted =
+            case_when((mass + delta).gt(0), mass + delta).otherwise(1);
+
+        let result = diesel::update(game)
+            .set(mass.eq(shifted))
+            .filter(id.eq(id_game))
+            .returning(mass)
+            .get_result(&mut self.conn("adjust_chat_pig_mass").await?)
+            .await?;
+
+        Ok(result)
+    }
+
     pub async fn create_chat_pig(
         &self,
         id_user: i32,
@@ -134,7 +161,7 @@ impl ChatPig {
                 mass.eq(start_mass),
             ))
             .returning(Game::as_returning())
-            .get_result(&mut self.pool.get().await?)
+            .get_result(&mut self.conn("create_chat_pig").await?)
             .await?;
 
         Ok(result)
@@ -161,7 +188,7 @@ impl ChatPig {
             .offset(top_limit * offset_multiplier)
             .select(Game::as_select())
             .inner_join(groups::table)
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_top_chat_pigs").await?)
             .await?;
 
         Ok(results)
@@ -189,7 +216,7 @@ impl ChatPig {
             .filter(uid.eq(id_uid))
             .filter(pigs_in_group.ge(ACTIVE_GROUP_MIN_PIGS))
             .count()
-            .get_result(&mut self.pool.get().await?)
+            .get_result(&mut self.conn("count_active_chats_by_uid").await?)
             .await?;
 
         Ok(result)
@@ -208,7 +235,7 @@ impl ChatPig {
             .filter(mass.gt(min))
             .count()
             .inner_join(groups::table)
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("count_chat_pig").await?)
             .await?;
 
         Ok(results)
@@ -224,7 +251,7 @@ impl ChatPig {
             .filter(game_id.eq(id_game))
             .order(created_at.asc())
             .select(GrowLog::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_grow_log_by_game").await?)
             .await?;
 
         Ok(results)
@@ -246,7 +273,7 @@ impl ChatPig {
             .filter(created_at.ge(start_date).and(created_at.le(today)))
             .order(created_at.asc())
             .select(GrowLog::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_grow_log_by_game_14days").await?)
             .await?;
 
         Ok(results)
@@ -260,7 +287,7 @@ impl ChatPig {
 
         diesel::insert_into(grow_log)
             .values(about_grow)
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("add_grow_log_by_game").await?)
             .await?;
 
         Ok(())
@@ -277,7 +304,7 @@ impl ChatPig {
             .inner_join(users::table)
             .filter(group_id.eq(group_id_val))
             .select((Game::as_select(), User::as_select()))
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_game_users_by_group").await?)
             .await?;
 
         Ok(results)
@@ -294,7 +321,7 @@ impl ChatPig {
             .inner_join(users::table)
             .filter(group_id.eq(group_id_val))
             .select(users::user_id)
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_pig_user_ids_by_group").await?)
             .await?;
 
         Ok(results)
@@ -306,12 +333,26 @@ impl ChatPig {
         let result = game
             .filter(group_id.eq(group_id_val))
             .count()
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("count_active_pigs").await?)
             .await?;
 
         Ok(result)
     }
 
+    /// [`Self::count_active_pigs`] for every group at once, keyed by
+    /// `groups.id`; groups without pigs are absent.
+    pub async fn count_pigs_per_group(&self) -> MyResult<AHashMap<i32, i64>> {
+        use crate::db::schema::game::dsl::*;
+
+        let results = game
+            .group_by(group_id)
+            .select((group_id, diesel::dsl::count_star()))
+            .load::<(i32, i64)>(&mut self.conn("count_pigs_per_group").await?)
+            .await?;
+
+        Ok(results.into_iter().collect())
+    }
+
     pub async fn soft_reset_pigs(
         &self,
         group_id_val: i32,
@@ -320,7 +361,7 @@ impl ChatPig {
         use crate::db::schema::achievements_users::dsl::*;
         use crate::db::schema::game;
 
-        let conn = &mut self.pool.get().await?;
+        let conn = &mut self.conn("soft_reset_pigs").await?;
 
         diesel::update(game::table)
             .set((game::mass.eq(CHAT_PIG_START_MASS), game::date.eq(today)))
@@ -351,7 +392,7 @@ impl ChatPig {
         use crate::db::schema::groups;
         use crate::db::schema::grow_log::dsl::*;
 
-        let pool = &mut self.pool.get().await?;
+        let pool = &mut self.conn("get_top10_by_14days_growth").await?;
         let start_date = today - Duration::days(13);
 
         let top_users = game
@@ -388,6 +429,72 @@ impl ChatPig {
 
         Ok(result)
     }
+
+    pub async fn count_chat_pigs(&self) -> MyResult<i64> {
+        use crate::db::schema::game::dsl::*;
+
+        let result = game
+            .count()
+            .get_result(&mut self.conn("count_chat_pigs").await?)
+            .await?;
+
+        Ok(result)
+    }
+
+    pub async fn sum_chat_pig_mass(&self) -> MyResult<i64> {
+        use crate::db::schema::game::dsl::*;
+        use diesel::dsl::sum;
+
+        let result: Option<i64> = game
+            .select(sum(mass))
+            .get_result(&mut self.conn("sum_chat_pig_mass").await?)
+            .await?;
+
+        Ok(result.unwrap_or(0))
+    }
+
+    /// Every `/grow` since `since`, across all chats.
+    pub async fn count_feeds_since(
+        &self,
+        since: NaiveDateTime,
+    ) -> MyResult<i64> {
+        use crate::db::schema::grow_log::dsl::*;
+
+        let result = grow_log
+            .filter(created_at.ge(since))
+            .count()
+            .get_result(&mut self.conn("count_feeds_since").await?)
+            .await?;
+
+        Ok(result)
+    }
+
+    /// Chats ranked by the mass their pigs put on since `since`, as
+    /// `(title, gained)`. Losses count against the gain.
+    pub async fn get_top_growing_chats(
+        &self,
+        since: NaiveDateTime,
+        limit: i64,
+    ) -> MyResult<Vec<(String, i64)>> {
+        use crate::db::schema::game;
+        use crate::db::schema::groups;
+        use crate::db::schema::grow_log::dsl::*;
+        use diesel::dsl::sum;
+
+        let results = grow_log
+            .inner_join(game::table.inner_join(groups::table))
+            .filter(created_at.ge(since))
+            .group_by((groups::id, groups::title))
+            .select((groups::title, sum(weight_change)))
+            .order_by(sum(weight_change).desc())
+            .limit(limit)
+            .load::<(String, Option<i64>)>(
+                &mut self.conn("get_top_growing_chats").await?,
+            )
+            .await?;
+
+        Ok(results.into_iter().map(|(t, g)| (t, g.unwrap_or(0))).collect())
+    }
 }
 
 
@@ -8,6 +8,7 @@ use diesel::{
 use diesel_async::RunQueryDsl;
 
 use crate::{
+    db::api::TimedConn,
     db::models::{
         HryakDay, InlineGroup, InlineUser, InlineUsersGroup, NewInlineUser,
         UpdateInlineUser, User,
@@ -26,6 +27,10 @@ impl HandPig {
         Self { pool }
     }
 
+    async fn conn(&self, method: &'static str) -> MyResult<TimedConn> {
+        TimedConn::get(&self.pool, "hand_pig", method).await
+    }
+
     pub async fn update_hrundel_duel(
         &self,
         id_user: i64,
@@ -45,7 +50,7 @@ impl HandPig {
                     ),
                 )
                 .set((weight.eq(weight + offset), win.eq(win + 1)))
-                .execute(&mut self.pool.get().await?)
+                .execute(&mut self.conn("update_hrundel_duel").await?)
                 .await?;
         } else {
             diesel::update(inline_users)
@@ -61,7 +66,7 @@ impl HandPig {
                         .otherwise(1)),
                     rout.eq(rout + 1),
                 ))
-                .execute(&mut self.pool.get().await?)
+                .execute(&mut self.conn("update_hrundel_duel").await?)
                 .await?;
         }
         Ok(())
@@ -85,7 +90,7 @@ impl HandPig {
                 ),
             )
             .set((date.eq(cur_date), weight.eq(size)))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("update_hrundel_date_and_size").await?)
             .await?;
         Ok(())
     }
@@ -107,7 +112,7 @@ impl HandPig {
                 ),
             )
             .set(name.eq(new_name))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("update_hrundel_name").await?)
             .await?;
 
         Ok(())
@@ -130,7 +135,7 @@ impl HandPig {
                 ),
             )
             .set(flag.eq(new_flag))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("update_hrundel_flag").await?)
             .await?;
 
         Ok(())
@@ -145,7 +150,7 @@ impl HandPig {
                 weight.eq(data.weight),
                 gifted.eq(data.gifted),
             ))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("update_hrundel").await?)
             .await?;
 
         Ok(())
@@ -160,7 +165,7 @@ impl HandPig {
         let result = diesel::insert_into(inline_users)
             .values(&hrundel)
             .returning(InlineUser::as_returning())
-            .get_result(&mut self.pool.get().await?)
+            .get_result(&mut self.conn("add_hrundel").await?)
             .await?;
 
         Ok(result)
@@ -177,7 +182,7 @@ impl HandPig {
             .filter(users::user_id.eq(id_user))
             .inner_join(users::table)
             .select((InlineUser::as_select(), User::as_select()))
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_hrundel").await?)
             .await
             .optional()?;
 
@@ -212,7 +217,7 @@ impl HandPig {
                 InlineUser::as_select(),
                 User::as_select(),
             ))
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_hryak_day_in_chat").await?)
             .await
             .optional()?;
 
@@ -231,7 +236,7 @@ impl HandPig {
         let results = inline_groups
             .filter(chat_instance.eq(parsed_instance))
             .select(InlineGroup::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_inline_group").await?)
             .await
             .optional()?;
 
@@ -247,7 +252,7 @@ impl HandPig {
         let results = inline_groups
             .filter(id.eq(primary_id))
             .select(InlineGroup::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_inline_group_by_id").await?)
             .await
             .optional()?;
 
@@ -272,7 +277,7 @@ impl HandPig {
                 chat_instance.eq(parsed_instance),
                 invited_at.eq(cur_datetime),
             ))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("add_inline_group").await?)
             .await?;
 
         Ok(())
@@ -303,7 +308,7 @@ impl HandPig {
                 ),
             )
             .select(InlineUsersGroup::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_group_user").await?)
             .await
             .optional()?;
 
@@ -322,7 +327,7 @@ impl HandPig {
         let result = diesel::insert_into(inline_users_groups)
             .values((iu_id.eq(id_iu), ig_id.eq(id_ig)))
             .returning(InlineUsersGroup::as_returning())
-            .get_result(&mut self.pool.get().await?)
+            .get_result(&mut self.conn("add_group_to_user").await?)
             .await?;
 
         Ok(result)
@@ -345,7 +350,7 @@ impl HandPig {
             .inner_join(inline_users::table.inner_join(users::table))
             .filter(inline_groups::chat_instance.eq(parsed_instance))
             .select((InlineUsersGroup::as_select(), User::as_select()))
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_inline_users_with_user_by_chat").await?)
             .await?;
 
         Ok(results)
@@ -362,7 +367,7 @@ impl HandPig {
             .filter(iu_id.eq(id_iu))
             .filter(ig_id.eq(id_ig))
             .select(InlineUsersGroup::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_iug_by_ids").await?)
             .await
             .optional()?;
 
@@ -395,7 +400,9 @@ impl HandPig {
             ))
             .order(count(hryak_day::id).desc())
             .limit(10)
-            .load::<(String, String, i64)>(&mut self.pool.get().await?)
+            .load::<(String, String, i64)>(
+                &mut self.conn("get_day_pig_counts_by_chat").await?,
+            )
             .await?;
 
         Ok(results)
@@ -421,7 +428,7 @@ impl HandPig {
 
         diesel::insert_into(hryak_day)
             .values((iug_id.eq(user_to_chat_id), date.eq(current_date)))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("add_hryak_day_to_chat").await?)
             .await?;
 
         Ok(())
@@ -449,7 +456,7 @@ impl HandPig {
             .order_by(inline_users::weight.desc())
             .limit(10)
             .select(InlineUser::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_top10_chat").await?)
             .await?;
 
         if results.is_empty() {
@@ -470,7 +477,7 @@ impl HandPig {
             .order_by(weight.desc())
             .limit(10)
             .select(InlineUser::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_top10_global").await?)
             .await?;
 
         if results.is_empty() {
@@ -487,7 +494,7 @@ impl HandPig {
             .order_by(win.desc())
             .limit(10)
             .select(InlineUser::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_top10_win").await?)
             .await?;
 
         if results.is_empty() {
@@ -496,4 +503,52 @@ impl HandPig {
 
         Ok(Some(results))
     }
+
+    pub async fn add_duel_log(
+        &self,
+        winner: i32,
+        looser: i32,
+        damage_val: i32,
+        at: NaiveDateTime,
+    ) -> MyResult<()> {
+        use crate::db::schema::duel_log::dsl::*;
+
+        diesel::insert_into(duel_log)
+            .values((
+                winner_uid.eq(winner),
+                looser_uid.eq(looser),
+                damage.eq(damage_val),
+                created_at.eq(at),
+            ))
+            .execute(&mut self.conn("add_duel_log").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn count_duels_since(
+        &self,
+        since: NaiveDateTime,
+    ) -> MyResult<i64> {
+        use crate::db::schema::duel_log::dsl::*;
+
+        let result = duel_log
+            .filter(created_at.ge(since))
+            .count()
+            .get_result(&mut self.conn("count_duels_since").await?)
+            .await?;
+
+        Ok(result)
+    }
+
+    pub async fn count_hand_pigs(&self) -> MyResult<i64> {
+        use crate::db::schema::inline_users::dsl::*;
+
+        let result = inline_users
+            .count()
+            .get_result(&mut self.conn("count_hand_pigs").await?)
+            .await?;
+
+        Ok(result)
+    }
 }
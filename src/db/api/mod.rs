@@ -1,3 +1,57 @@
 pub mod chat_pig;
 pub mod hand_pig;
 pub mod other;
+
+use std::ops::{Deref, DerefMut};
+use std::time::Instant;
+
+use diesel_async::pooled_connection::deadpool::Object;
+
+use crate::metrics::{DB_QUERY_DURATION, QueryLabels};
+use crate::types::{DbConn, DbPool, MyResult};
+
+/// A pooled connection that reports how long it was held once dropped.
+/// Usable wherever the bare pooled one is, transactions included.
+pub struct TimedConn {
+    conn: Object<DbConn>,
+    labels: QueryLabels,
+    started: Instant,
+}
+
+impl TimedConn {
+    pub(crate) async fn get(
+        pool: &DbPool,
+        api: &'static str,
+        method: &'static str,
+    ) -> MyResult<Self> {
+        let conn = pool.get().await?;
+
+        Ok(Self {
+            conn,
+            labels: QueryLabels { api, method },
+            started: Instant::now(),
+        })
+    }
+}
+
+impl Deref for TimedConn {
+    type Target = DbConn;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl DerefMut for TimedConn {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.conn
+    }
+}
+
+impl Drop for TimedConn {
+    fn drop(&mut self) {
+        DB_QUERY_DURATION
+            .get_or_create(&self.labels)
+            .observe(self.started.elapsed().as_secs_f64());
+    }
+}
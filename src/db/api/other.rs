@@ -1,18 +1,33 @@
 use std::sync::Arc;
 
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::{
+    AsyncConnection, RunQueryDsl, scoped_futures::ScopedFutureExt,
+};
 
 use crate::{
-    config::consts::INLINE_CONTENT_APPROVED,
+    config::consts::{
+        BROADCAST_RUNNING, BROADCAST_TARGET_CLAIMED, BROADCAST_TARGET_PENDING,
+        INLINE_CONTENT_APPROVED, INLINE_CONTENT_HIDDEN, INLINE_CONTENT_PENDING,
+        INLINE_CONTENT_REJECTED,
+    },
+    db::api::TimedConn,
     db::models::{
-        AchievementUser, AchievementUserAdd, Groups, InlineGif, InlineVoice,
-        NewGroup, NewUser, UpdateGroups, UpdateUser, User, UserStatus,
+        AchievementUser, AchievementUserAdd, Broadcast, Groups, InlineGif,
+        InlineVoice, InlineVoiceAward, ModerationLog, NewBroadcast, NewGroup,
+        NewModerationLog, NewUser, Staff, UpdateGroups, UpdateUser, User,
+        UserSettings, UserStatus,
     },
     types::{DbPool, MyError, MyResult},
 };
 
+/// Approved or hidden: past moderation, so `/content` may edit it.
+const PUBLISHED: [i16; 2] = [INLINE_CONTENT_APPROVED, INLINE_CONTENT_HIDDEN];
+/// Rows per INSERT of broadcast targets; three binds each, well under the
+/// 65535 bind limit of a Postgres statement.
+const BROADCAST_TARGETS_CHUNK: usize = 10_000;
+
 #[derive(Clone)]
 pub struct Other {
     pool: Arc<DbPool>,
@@ -23,12 +38,16 @@ impl Other {
         Self { pool }
     }
 
+    async fn conn(&self, method: &'static str) -> MyResult<TimedConn> {
+        TimedConn::get(&self.pool, "other", method).await
+    }
+
     pub async fn register_user(&self, new_user: NewUser<'_>) -> MyResult<User> {
         use crate::db::schema::users::dsl::*;
         let result = diesel::insert_into(users)
             .values(new_user)
             .returning(User::as_returning())
-            .get_result(&mut self.pool.get().await?)
+            .get_result(&mut self.conn("register_user").await?)
             .await?;
         Ok(result)
     }
@@ -39,7 +58,7 @@ impl Other {
         let result = users
             .filter(user_id.eq(id_user))
             .select(User::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_user").await?)
             .await
             .optional()?;
 
@@ -52,7 +71,7 @@ impl Other {
         let result = users
             .filter(id.eq(uid))
             .select(User::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_user_by_id").await?)
             .await
             .optional()?;
 
@@ -68,7 +87,23 @@ impl Other {
         diesel::update(users)
             .set(&status)
             .filter(user_id.eq(id_user))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("change_user_status").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Called when `started` flips to true.
+    pub async fn set_user_started_at(
+        &self,
+        id_user: i64,
+        at: NaiveDateTime,
+    ) -> MyResult<()> {
+        use crate::db::schema::users::dsl::*;
+        diesel::update(users)
+            .set(started_at.eq(at))
+            .filter(user_id.eq(id_user))
+            .execute(&mut self.conn("set_user_started_at").await?)
             .await?;
 
         Ok(())
@@ -83,12 +118,202 @@ impl Other {
         diesel::update(users)
             .set(lang.eq(status))
             .filter(user_id.eq(id_user))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("change_user_lang").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// The defaults for a user who never changed anything.
+    pub async fn get_user_settings(
+        &self,
+        user_uid: i32,
+    ) -> MyResult<UserSettings> {
+        use crate::db::schema::user_settings::dsl::*;
+
+        let result = user_settings
+            .filter(uid.eq(user_uid))
+            .select(UserSettings::as_select())
+            .first(&mut self.conn("get_user_settings").await?)
+            .await
+            .optional()?;
+
+        Ok(result.unwrap_or_else(|| UserSettings::new(user_uid)))
+    }
+
+    pub async fn set_user_settings(
+        &self,
+        settings: UserSettings,
+    ) -> MyResult<()> {
+        use crate::db::schema::user_settings::dsl::*;
+
+        diesel::insert_into(user_settings)
+            .values(&settings)
+            .on_conflict(uid)
+            .do_update()
+            .set(&settings)
+            .execute(&mut self.conn("set_user_settings").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Those of `uids` a private message can reach — started the bot and
+    /// never blocked it — with their settings. Without a settings row every
+    /// notification is off, so such users are left out as well.
+    pub async fn get_notification_recipients(
+        &self,
+        uids: &[i32],
+    ) -> MyResult<Vec<(User, UserSettings)>> {
+        use crate::db::schema::{user_settings, users};
+
+        let result = users::table
+            .inner_join(user_settings::table)
+            .filter(users::id.eq_any(uids))
+            .filter(users::started.eq(true))
+            .filter(users::banned.eq(false))
+            .select((User::as_select(), UserSettings::as_select()))
+            .load(&mut self.conn("get_notification_recipients").await?)
+            .await?;
+
+        Ok(result)
+    }
+
+    /// The raw `staff.role` of a Telegram user, if they have one.
+    pub async fn get_staff_role(&self, id_user: i64) -> MyResult<Option<i16>> {
+        use crate::db::schema::staff;
+        use crate::db::schema::users;
+
+        let result = staff::table
+            .inner_join(users::table)
+            .filter(users::user_id.eq(id_user))
+            .select(staff::role)
+            .first(&mut self.conn("get_staff_role").await?)
+            .await
+            .optional()?;
+
+        Ok(result)
+    }
+
+    /// Grants a role, replacing whatever the user had before.
+    pub async fn set_staff(&self, entry: Staff) -> MyResult<()> {
+        use crate::db::schema::staff::dsl::*;
+
+        diesel::insert_into(staff)
+            .values(&entry)
+            .on_conflict(uid)
+            .do_update()
+            .set((
+                role.eq(entry.role),
+                granted_by.eq(entry.granted_by),
+                granted_at.eq(entry.granted_at),
+            ))
+            .execute(&mut self.conn("set_staff").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_staff(&self, staff_uid: i32) -> MyResult<bool> {
+        use crate::db::schema::staff::dsl::*;
+
+        let deleted = diesel::delete(staff.find(staff_uid))
+            .execute(&mut self.conn("remove_staff").await?)
+            .await?;
+
+        Ok(deleted == 1)
+    }
+
+    /// Highest role first.
+    pub async fn get_staff(&self) -> MyResult<Vec<(Staff, User)>> {
+        use crate::db::schema::staff;
+        use crate::db::schema::users;
+
+        let results = staff::table
+            .inner_join(users::table)
+            .order_by((staff::role.desc(), staff::uid.asc()))
+            .select((Staff::as_select(), User::as_select()))
+            .load(&mut self.conn("get_staff").await?)
+            .await?;
+
+        Ok(results)
+    }
+
+    /// `false` if the user was already banned.
+    pub async fn ban_user(
+        &self,
+        ban_uid: i32,
+        at: NaiveDateTime,
+    ) -> MyResult<bool> {
+        use crate::db::schema::bot_bans::dsl::*;
+
+        let inserted = diesel::insert_into(bot_bans)
+            .values((uid.eq(ban_uid), banned_at.eq(at)))
+            .on_conflict_do_nothing()
+            .execute(&mut self.conn("ban_user").await?)
+            .await?;
+
+        Ok(inserted == 1)
+    }
+
+    /// `false` if the user wasn't banned.
+    pub async fn unban_user(&self, ban_uid: i32) -> MyResult<bool> {
+        use crate::db::schema::bot_bans::dsl::*;
+
+        let deleted = diesel::delete(bot_bans.filter(uid.eq(ban_uid)))
+            .execute(&mut self.conn("unban_user").await?)
+            .await?;
+
+        Ok(deleted == 1)
+    }
+
+    /// Telegram ids of every banned user.
+    pub async fn get_banned_user_ids(&self) -> MyResult<Vec<i64>> {
+        use crate::db::schema::bot_bans;
+        use crate::db::schema::users;
+
+        let results = bot_bans::table
+            .inner_join(users::table)
+            .select(users::user_id)
+            .load(&mut self.conn("get_banned_user_ids").await?)
+            .await?;
+
+        Ok(results)
+    }
+
+    pub async fn add_moderation_log(
+        &self,
+        entry: NewModerationLog<'_>,
+    ) -> MyResult<()> {
+        use crate::db::schema::moderation_log::dsl::*;
+
+        diesel::insert_into(moderation_log)
+            .values(&entry)
+            .execute(&mut self.conn("add_moderation_log").await?)
             .await?;
 
         Ok(())
     }
 
+    /// Newest first.
+    pub async fn get_moderation_log(
+        &self,
+        target: i32,
+        limit: i64,
+    ) -> MyResult<Vec<ModerationLog>> {
+        use crate::db::schema::moderation_log::dsl::*;
+
+        let results = moderation_log
+            .filter(target_uid.eq(target))
+            .order_by(id.desc())
+            .limit(limit)
+            .select(ModerationLog::as_select())
+            .load(&mut self.conn("get_moderation_log").await?)
+            .await?;
+
+        Ok(results)
+    }
+
     pub async fn get_inline_voice_by_id(
         &self,
         voice_id: i16,
@@ -96,10 +321,10 @@ impl Other {
         use crate::db::schema::inline_voices::dsl::*;
 
         let results = inline_voices
-            .filter(status.eq(1))
+            .filter(status.eq(INLINE_CONTENT_APPROVED))
             .filter(id.eq(voice_id))
             .select(InlineVoice::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_inline_voice_by_id").await?)
             .await
             .optional()?;
 
@@ -110,10 +335,10 @@ impl Other {
         use crate::db::schema::inline_voices::dsl::*;
 
         let results = inline_voices
-            .filter(status.eq(1))
+            .filter(status.eq(INLINE_CONTENT_APPROVED))
             .order_by(id.desc())
             .select(InlineVoice::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_inline_voices").await?)
             .await?;
 
         Ok(results)
@@ -126,10 +351,10 @@ impl Other {
         use crate::db::schema::inline_gifs::dsl::*;
 
         let results = inline_gifs
-            .filter(status.eq(1))
+            .filter(status.eq(INLINE_CONTENT_APPROVED))
             .filter(id.eq(voice_id))
             .select(InlineGif::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_inline_gif_by_id").await?)
             .await
             .optional()?;
 
@@ -140,10 +365,10 @@ impl Other {
         use crate::db::schema::inline_gifs::dsl::*;
 
         let results = inline_gifs
-            .filter(status.eq(1))
+            .filter(status.eq(INLINE_CONTENT_APPROVED))
             .order_by(id.desc())
             .select(InlineGif::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_inline_gifs").await?)
             .await?;
 
         Ok(results)
@@ -155,7 +380,7 @@ impl Other {
         let results = groups
             .filter(chat_id.eq(id_chat))
             .select(Groups::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_chat").await?)
             .await
             .optional()?;
 
@@ -168,7 +393,7 @@ impl Other {
         let result = diesel::insert_into(groups)
             .values(new_group)
             .returning(Groups::as_returning())
-            .get_result(&mut self.pool.get().await?)
+            .get_result(&mut self.conn("add_chat").await?)
             .await?;
 
         Ok(result)
@@ -184,7 +409,7 @@ impl Other {
         diesel::update(groups)
             .set(chat_info)
             .filter(chat_id.eq(id_chat))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("update_chat").await?)
             .await?;
 
         Ok(())
@@ -200,7 +425,7 @@ impl Other {
         diesel::update(groups)
             .set(settings.eq(setting))
             .filter(chat_id.eq(id_chat))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("set_chat_settings").await?)
             .await?;
 
         Ok(())
@@ -216,14 +441,347 @@ impl Other {
         diesel::update(groups)
             .set(top10_setting.eq(setting))
             .filter(chat_id.eq(id_chat))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("set_top10_setting").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_strict_names(
+        &self,
+        id_chat: i64,
+        strict: bool,
+    ) -> MyResult<()> {
+        use crate::db::schema::groups::dsl::*;
+
+        diesel::update(groups)
+            .set(strict_names.eq(strict))
+            .filter(chat_id.eq(id_chat))
+            .execute(&mut self.conn("set_strict_names").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// `url` stays empty until approval: it points at the review card, which
+    /// may still be re-sent. `caption` starts empty, so the browser falls
+    /// back to the numbered title from the locale.
+    pub async fn add_pending_voice(
+        &self,
+        iv_uid: i32,
+        new_file_id: &str,
+        new_file_unique_id: &str,
+        submitted: NaiveDateTime,
+    ) -> MyResult<InlineVoice> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        let result = diesel::insert_into(inline_voices)
+            .values((
+                url.eq(""),
+                uid.eq(iv_uid),
+                status.eq(INLINE_CONTENT_PENDING),
+                caption.eq(""),
+                file_id.eq(new_file_id),
+                file_unique_id.eq(new_file_unique_id),
+                submitted_at.eq(submitted),
+            ))
+            .returning(InlineVoice::as_returning())
+            .get_result(&mut self.conn("add_pending_voice").await?)
+            .await?;
+
+        Ok(result)
+    }
+
+    pub async fn add_pending_gif(
+        &self,
+        iv_uid: i32,
+        new_file_id: &str,
+        new_file_unique_id: &str,
+        submitted: NaiveDateTime,
+    ) -> MyResult<InlineGif> {
+        use crate::db::schema::inline_gifs::dsl::*;
+
+        let result = diesel::insert_into(inline_gifs)
+            .values((
+                file_id.eq(new_file_id),
+                file_unique_id.eq(new_file_unique_id),
+                uid.eq(iv_uid),
+                status.eq(INLINE_CONTENT_PENDING),
+                submitted_at.eq(submitted),
+            ))
+            .returning(InlineGif::as_returning())
+            .get_result(&mut self.conn("add_pending_gif").await?)
+            .await?;
+
+        Ok(result)
+    }
+
+    pub async fn set_voice_review_message(
+        &self,
+        voice_id: i16,
+        message_id: i32,
+    ) -> MyResult<()> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        diesel::update(inline_voices.find(voice_id))
+            .set(review_message_id.eq(message_id))
+            .execute(&mut self.conn("set_voice_review_message").await?)
             .await?;
 
         Ok(())
     }
 
-    /// `status` and `caption` are NOT NULL with no default, so both must be
-    /// set. Nothing reads `caption`; the browser builds it from the locale.
+    pub async fn set_gif_review_message(
+        &self,
+        gif_id: i16,
+        message_id: i32,
+    ) -> MyResult<()> {
+        use crate::db::schema::inline_gifs::dsl::*;
+
+        diesel::update(inline_gifs.find(gif_id))
+            .set(review_message_id.eq(message_id))
+            .execute(&mut self.conn("set_gif_review_message").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// `None` when the voice is not pending anymore: a re-sent card leaves
+    /// two live cards behind, and only the first decision may count.
+    pub async fn approve_voice(
+        &self,
+        voice_id: i16,
+        new_url: String,
+    ) -> MyResult<Option<InlineVoice>> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        let result = diesel::update(inline_voices.find(voice_id))
+            .filter(status.eq(INLINE_CONTENT_PENDING))
+            .set((status.eq(INLINE_CONTENT_APPROVED), url.eq(new_url)))
+            .returning(InlineVoice::as_returning())
+            .get_result(&mut self.conn("approve_voice").await?)
+            .await
+            .optional()?;
+
+        Ok(result)
+    }
+
+    /// See [`Other::approve_voice`] on the `None`.
+    pub async fn reject_voice(
+        &self,
+        voice_id: i16,
+        reason: Option<i16>,
+    ) -> MyResult<Option<InlineVoice>> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        let result = diesel::update(inline_voices.find(voice_id))
+            .filter(status.eq(INLINE_CONTENT_PENDING))
+            .set((status.eq(INLINE_CONTENT_REJECTED), reject_reason.eq(reason)))
+            .returning(InlineVoice::as_returning())
+            .get_result(&mut self.conn("reject_voice").await?)
+            .await
+            .optional()?;
+
+        Ok(result)
+    }
+
+    /// See [`Other::approve_voice`] on the `None`.
+    pub async fn approve_gif(
+        &self,
+        gif_id: i16,
+    ) -> MyResult<Option<InlineGif>> {
+        use crate::db::schema::inline_gifs::dsl::*;
+
+        let result = diesel::update(inline_gifs.find(gif_id))
+            .filter(status.eq(INLINE_CONTENT_PENDING))
+            .set(status.eq(INLINE_CONTENT_APPROVED))
+            .returning(InlineGif::as_returning())
+            .get_result(&mut self.conn("approve_gif").await?)
+            .await
+            .optional()?;
+
+        Ok(result)
+    }
+
+    /// See [`Other::approve_voice`] on the `None`.
+    pub async fn reject_gif(
+        &self,
+        gif_id: i16,
+        reason: Option<i16>,
+    ) -> MyResult<Option<InlineGif>> {
+        use crate::db::schema::inline_gifs::dsl::*;
+
+        let result = diesel::update(inline_gifs.find(gif_id))
+            .filter(status.eq(INLINE_CONTENT_PENDING))
+            .set((status.eq(INLINE_CONTENT_REJECTED), reject_reason.eq(reason)))
+            .returning(InlineGif::as_returning())
+            .get_result(&mut self.conn("reject_gif").await?)
+            .await
+            .optional()?;
+
+        Ok(result)
+    }
+
+    /// Moves a published voice between approved and hidden. `None` when the
+    /// row is not in the `from` state, so hiding twice is reported.
+    pub async fn change_voice_status(
+        &self,
+        voice_id: i16,
+        from: i16,
+        to: i16,
+    ) -> MyResult<Option<InlineVoice>> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        let result = diesel::update(inline_voices.find(voice_id))
+            .filter(status.eq(from))
+            .set(status.eq(to))
+            .returning(InlineVoice::as_returning())
+            .get_result(&mut self.conn("change_voice_status").await?)
+            .await
+            .optional()?;
+
+        Ok(result)
+    }
+
+    /// See [`Other::change_voice_status`].
+    pub async fn change_gif_status(
+        &self,
+        gif_id: i16,
+        from: i16,
+        to: i16,
+    ) -> MyResult<Option<InlineGif>> {
+        use crate::db::schema::inline_gifs::dsl::*;
+
+        let result = diesel::update(inline_gifs.find(gif_id))
+            .filter(status.eq(from))
+            .set(status.eq(to))
+            .returning(InlineGif::as_returning())
+            .get_result(&mut self.conn("change_gif_status").await?)
+            .await
+            .optional()?;
+
+        Ok(result)
+    }
+
+    /// A pending row is still the moderator's to decide, so only
+    /// [`PUBLISHED`] ones are touched.
+    pub async fn set_voice_caption(
+        &self,
+        voice_id: i16,
+        new_caption: &str,
+    ) -> MyResult<bool> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        let updated = diesel::update(inline_voices.find(voice_id))
+            .filter(status.eq_any(PUBLISHED))
+            .set(caption.eq(new_caption))
+            .execute(&mut self.conn("set_voice_caption").await?)
+            .await?;
+
+        Ok(updated == 1)
+    }
+
+    /// See [`Other::set_voice_caption`].
+    pub async fn set_gif_caption(
+        &self,
+        gif_id: i16,
+        new_caption: &str,
+    ) -> MyResult<bool> {
+        use crate::db::schema::inline_gifs::dsl::*;
+
+        let updated = diesel::update(inline_gifs.find(gif_id))
+            .filter(status.eq_any(PUBLISHED))
+            .set(caption.eq(new_caption))
+            .execute(&mut self.conn("set_gif_caption").await?)
+            .await?;
+
+        Ok(updated == 1)
+    }
+
+    /// Swaps the file under a published voice; the id, and with it every
+    /// `HruVoice(id)` result already shared, stays the same.
+    pub async fn replace_voice_file(
+        &self,
+        voice_id: i16,
+        new_file_id: &str,
+        new_file_unique_id: &str,
+        new_fingerprint: Option<i64>,
+        new_url: &str,
+    ) -> MyResult<bool> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        let updated = diesel::update(inline_voices.find(voice_id))
+            .filter(status.eq_any(PUBLISHED))
+            .set((
+                file_id.eq(new_file_id),
+                file_unique_id.eq(new_file_unique_id),
+                fingerprint.eq(new_fingerprint),
+                url.eq(new_url),
+            ))
+            .execute(&mut self.conn("replace_voice_file").await?)
+            .await?;
+
+        Ok(updated == 1)
+    }
+
+    /// See [`Other::replace_voice_file`].
+    pub async fn replace_gif_file(
+        &self,
+        gif_id: i16,
+        new_file_id: &str,
+        new_file_unique_id: &str,
+    ) -> MyResult<bool> {
+        use crate::db::schema::inline_gifs::dsl::*;
+
+        let updated = diesel::update(inline_gifs.find(gif_id))
+            .filter(status.eq_any(PUBLISHED))
+            .set((
+                file_id.eq(new_file_id),
+                file_unique_id.eq(new_file_unique_id),
+            ))
+            .execute(&mut self.conn("replace_gif_file").await?)
+            .await?;
+
+        Ok(updated == 1)
+    }
+
+    /// Oldest first, the order they should be reviewed in, with the
+    /// submitter for the card caption.
+    pub async fn get_pending_voices(
+        &self,
+    ) -> MyResult<Vec<(InlineVoice, User)>> {
+        use crate::db::schema::inline_voices;
+        use crate::db::schema::users;
+
+        let results = inline_voices::table
+            .inner_join(users::table)
+            .filter(inline_voices::status.eq(INLINE_CONTENT_PENDING))
+            .order_by(inline_voices::id.asc())
+            .select((InlineVoice::as_select(), User::as_select()))
+            .load(&mut self.conn("get_pending_voices").await?)
+            .await?;
+
+        Ok(results)
+    }
+
+    /// See [`Other::get_pending_voices`].
+    pub async fn get_pending_gifs(&self) -> MyResult<Vec<(InlineGif, User)>> {
+        use crate::db::schema::inline_gifs;
+        use crate::db::schema::users;
+
+        let results = inline_gifs::table
+            .inner_join(users::table)
+            .filter(inline_gifs::status.eq(INLINE_CONTENT_PENDING))
+            .order_by(inline_gifs::id.asc())
+            .select((InlineGif::as_select(), User::as_select()))
+            .load(&mut self.conn("get_pending_gifs").await?)
+            .await?;
+
+        Ok(results)
+    }
+
+    /// Inserts an already approved voice. Only review cards sent before the
+    /// moderation queue existed, which carry no row id, still go through here.
     pub async fn add_voice(
         &self,
         iv_uid: i32,
@@ -238,7 +796,7 @@ impl Other {
                 status.eq(INLINE_CONTENT_APPROVED),
                 caption.eq(""),
             ))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("add_voice").await?)
             .await?;
 
         Ok(())
@@ -256,13 +814,13 @@ impl Other {
             .filter(uid.eq(iv_uid))
             .order_by(id.asc())
             .select(InlineVoice::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_voices_by_user").await?)
             .await?;
 
         Ok(results)
     }
 
-    /// See [`Other::add_voice`] on `status`.
+    /// See [`Other::add_voice`].
     pub async fn add_gif(
         &self,
         iv_uid: i32,
@@ -278,7 +836,7 @@ impl Other {
                 uid.eq(iv_uid),
                 status.eq(INLINE_CONTENT_APPROVED),
             ))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("add_gif").await?)
             .await?;
 
         Ok(())
@@ -295,7 +853,7 @@ impl Other {
             .filter(uid.eq(iv_uid))
             .order_by(id.asc())
             .select(InlineGif::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_gifs_by_user").await?)
             .await?;
 
         Ok(results)
@@ -310,13 +868,248 @@ impl Other {
         let results = inline_gifs
             .filter(file_unique_id.eq(id_file_unique))
             .select(InlineGif::as_select())
-            .first(&mut self.pool.get().await?)
+            .first(&mut self.conn("get_gif_by_file_unique_id").await?)
+            .await
+            .optional()?;
+
+        Ok(results)
+    }
+
+    /// Any status: a rejected voice sent again is still the same voice.
+    pub async fn get_voice_by_file_unique_id(
+        &self,
+        id_file_unique: &str,
+    ) -> MyResult<Option<InlineVoice>> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        let results = inline_voices
+            .filter(file_unique_id.eq(id_file_unique))
+            .select(InlineVoice::as_select())
+            .first(&mut self.conn("get_voice_by_file_unique_id").await?)
             .await
             .optional()?;
 
         Ok(results)
     }
 
+    pub async fn set_voice_fingerprint(
+        &self,
+        voice_id: i16,
+        new_fingerprint: i64,
+    ) -> MyResult<()> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        diesel::update(inline_voices.find(voice_id))
+            .set(fingerprint.eq(new_fingerprint))
+            .execute(&mut self.conn("set_voice_fingerprint").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Fingerprints of every voice but `except_id` that isn't rejected, for
+    /// the near-duplicate note on a review card.
+    pub async fn get_voice_fingerprints(
+        &self,
+        except_id: i16,
+    ) -> MyResult<Vec<(i16, i64)>> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        let results = inline_voices
+            .filter(id.ne(except_id))
+            .filter(status.ne(INLINE_CONTENT_REJECTED))
+            .filter(fingerprint.is_not_null())
+            .select((id, fingerprint.assume_not_null()))
+            .order(id.asc())
+            .load(&mut self.conn("get_voice_fingerprints").await?)
+            .await?;
+
+        Ok(results)
+    }
+
+    /// Rows stored before `file_unique_id` existed, oldest first. Ones
+    /// approved before `file_id` was stored are looked up by the channel
+    /// post in `url`, so only rows with neither are left out.
+    pub async fn get_voices_without_file_unique_id(
+        &self,
+    ) -> MyResult<Vec<InlineVoice>> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        let results = inline_voices
+            .filter(file_unique_id.is_null())
+            .filter(file_id.ne("").or(url.ne("")))
+            .select(InlineVoice::as_select())
+            .order(id.asc())
+            .load(&mut self.conn("get_voices_without_file_unique_id").await?)
+            .await?;
+
+        Ok(results)
+    }
+
+    /// `new_file_id` is only new for a row recovered from its channel post;
+    /// the others get their own back.
+    pub async fn set_voice_file_meta(
+        &self,
+        voice_id: i16,
+        new_file_id: &str,
+        new_file_unique_id: &str,
+        new_fingerprint: Option<i64>,
+    ) -> MyResult<()> {
+        use crate::db::schema::inline_voices::dsl::*;
+
+        diesel::update(inline_voices.find(voice_id))
+            .set((
+                file_id.eq(new_file_id),
+                file_unique_id.eq(new_file_unique_id),
+                fingerprint.eq(new_fingerprint),
+            ))
+            .execute(&mut self.conn("set_voice_file_meta").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Bumps this month's bucket, creating it on the first use.
+    pub async fn add_voice_use(
+        &self,
+        id_voice: i16,
+        cur_month: NaiveDate,
+    ) -> MyResult<()> {
+        use crate::db::schema::inline_voice_uses::dsl::*;
+
+        diesel::insert_into(inline_voice_uses)
+            .values((voice_id.eq(id_voice), month.eq(cur_month), uses.eq(1)))
+            .on_conflict((voice_id, month))
+            .do_update()
+            .set(uses.eq(uses + 1))
+            .execute(&mut self.conn("add_voice_use").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// See [`Other::add_voice_use`].
+    pub async fn add_gif_use(
+        &self,
+        id_gif: i16,
+        cur_month: NaiveDate,
+    ) -> MyResult<()> {
+        use crate::db::schema::inline_gif_uses::dsl::*;
+
+        diesel::insert_into(inline_gif_uses)
+            .values((gif_id.eq(id_gif), month.eq(cur_month), uses.eq(1)))
+            .on_conflict((gif_id, month))
+            .do_update()
+            .set(uses.eq(uses + 1))
+            .execute(&mut self.conn("add_gif_use").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Lifetime uses per voice. Never-used voices have no row at all.
+    pub async fn get_voice_use_counts(&self) -> MyResult<Vec<(i16, i64)>> {
+        use crate::db::schema::inline_voice_uses::dsl::*;
+        use diesel::dsl::sum;
+
+        let results = inline_voice_uses
+            .group_by(voice_id)
+            .select((voice_id, sum(uses)))
+            .load::<(i16, Option<i64>)>(
+                &mut self.conn("get_voice_use_counts").await?,
+            )
+            .await?;
+
+        Ok(results.into_iter().map(|(v, n)| (v, n.unwrap_or(0))).collect())
+    }
+
+    /// See [`Other::get_voice_use_counts`].
+    pub async fn get_gif_use_counts(&self) -> MyResult<Vec<(i16, i64)>> {
+        use crate::db::schema::inline_gif_uses::dsl::*;
+        use diesel::dsl::sum;
+
+        let results = inline_gif_uses
+            .group_by(gif_id)
+            .select((gif_id, sum(uses)))
+            .load::<(i16, Option<i64>)>(
+                &mut self.conn("get_gif_use_counts").await?,
+            )
+            .await?;
+
+        Ok(results.into_iter().map(|(v, n)| (v, n.unwrap_or(0))).collect())
+    }
+
+    /// The most used approved voice of `the_month`; ties go to the older
+    /// voice, so the result is stable across calls.
+    pub async fn get_top_voice_of_month(
+        &self,
+        the_month: NaiveDate,
+    ) -> MyResult<Option<(InlineVoice, i32)>> {
+        use crate::db::schema::inline_voice_uses;
+        use crate::db::schema::inline_voices;
+
+        let result = inline_voice_uses::table
+            .inner_join(inline_voices::table)
+            .filter(inline_voice_uses::month.eq(the_month))
+            .filter(inline_voices::status.eq(INLINE_CONTENT_APPROVED))
+            .order_by((
+                inline_voice_uses::uses.desc(),
+                inline_voice_uses::voice_id.asc(),
+            ))
+            .select((InlineVoice::as_select(), inline_voice_uses::uses))
+            .first(&mut self.conn("get_top_voice_of_month").await?)
+            .await
+            .optional()?;
+
+        Ok(result)
+    }
+
+    /// Records the award and pays `reward_kg` to the author's hand pig, if
+    /// they have one, in one transaction: a failed payout leaves the month
+    /// unawarded for the next try. The kg actually paid, `0` without a hand
+    /// pig; `None` when the month was already awarded — the caller must not
+    /// announce a second time.
+    pub async fn add_voice_award(
+        &self,
+        award: InlineVoiceAward,
+        reward_kg: i32,
+    ) -> MyResult<Option<i32>> {
+        use crate::db::schema::{inline_users, inline_voice_awards};
+
+        let mut conn = self.conn("add_voice_award").await?;
+        let paid = conn
+            .transaction::<_, MyError, _>(|conn| {
+                async move {
+                    let inserted =
+                        diesel::insert_into(inline_voice_awards::table)
+                            .values(&award)
+                            .on_conflict(inline_voice_awards::month)
+                            .do_nothing()
+                            .execute(conn)
+                            .await?;
+                    if inserted == 0 {
+                        return Ok(None);
+                    }
+
+                    let updated = diesel::update(inline_users::table)
+                        .filter(inline_users::uid.eq(award.uid))
+                        .set((
+                            inline_users::weight
+                                .eq(inline_users::weight + reward_kg),
+                            inline_users::date.eq(award.awarded_at.date()),
+                        ))
+                        .execute(conn)
+                        .await?;
+
+                    Ok(Some(if updated > 0 { reward_kg } else { 0 }))
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        Ok(paid)
+    }
+
     pub async fn update_chat_id(
         &self,
         from_id: i64,
@@ -327,7 +1120,7 @@ impl Other {
         diesel::update(groups)
             .filter(chat_id.eq(from_id))
             .set(chat_id.eq(to_id))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("update_chat_id").await?)
             .await?;
 
         Ok(())
@@ -343,7 +1136,7 @@ impl Other {
         diesel::update(groups)
             .filter(chat_id.eq(id_chat))
             .set(ig_id.eq(my_ig_id))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("update_chat_ig_id").await?)
             .await?;
 
         Ok(())
@@ -359,7 +1152,7 @@ impl Other {
         diesel::update(users)
             .set(chat_info)
             .filter(user_id.eq(id_user))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("update_user").await?)
             .await?;
 
         Ok(())
@@ -374,7 +1167,7 @@ impl Other {
         let results = achievements_users
             .filter(game_id.eq(id_game))
             .select(AchievementUser::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_achievements_by_game_id").await?)
             .await?;
 
         Ok(results)
@@ -393,7 +1186,9 @@ impl Other {
             let count = achievements_users
                 .filter(game_id.eq(id_game))
                 .count()
-                .get_result::<i64>(&mut self.pool.get().await?)
+                .get_result::<i64>(
+                    &mut self.conn("count_achievements_for_notice").await?,
+                )
                 .await?;
 
             Ok::<_, MyError>(count)
@@ -404,7 +1199,9 @@ impl Other {
                 .inner_join(game::table)
                 .filter(game::uid.eq(id_uid))
                 .select(count(code).aggregate_distinct())
-                .get_result::<i64>(&mut self.pool.get().await?)
+                .get_result::<i64>(
+                    &mut self.conn("count_achievements_for_notice").await?,
+                )
                 .await?;
 
             Ok::<_, MyError>(count)
@@ -427,7 +1224,7 @@ impl Other {
 
         diesel::insert_into(achievements_users)
             .values(new_achievements)
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("add_achievements").await?)
             .await?;
 
         Ok(())
@@ -444,7 +1241,7 @@ impl Other {
             .inner_join(game::table)
             .filter(game::uid.eq(id_uid))
             .select(AchievementUser::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_achievements_by_uid").await?)
             .await?;
 
         Ok(results)
@@ -460,7 +1257,7 @@ impl Other {
         diesel::update(groups)
             .set(reset_at.eq(reset_at_val))
             .filter(id.eq(group_id_val))
-            .execute(&mut self.pool.get().await?)
+            .execute(&mut self.conn("set_group_reset_at").await?)
             .await?;
 
         Ok(())
@@ -471,7 +1268,7 @@ impl Other {
 
         let results = groups
             .select(Groups::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_chats").await?)
             .await?;
 
         Ok(results)
@@ -482,7 +1279,276 @@ impl Other {
 
         let results = users
             .select(User::as_select())
-            .load(&mut self.pool.get().await?)
+            .load(&mut self.conn("get_users").await?)
+            .await?;
+
+        Ok(results)
+    }
+
+    /// `(total, started, banned)`.
+    pub async fn count_users(&self) -> MyResult<(i64, i64, i64)> {
+        use crate::db::schema::users::dsl::*;
+
+        let conn = &mut self.conn("count_users").await?;
+
+        let total = users.count().get_result(conn).await?;
+        let started_count =
+            users.filter(started.eq(true)).count().get_result(conn).await?;
+        let banned_count =
+            users.filter(banned.eq(true)).count().get_result(conn).await?;
+
+        Ok((total, started_count, banned_count))
+    }
+
+    /// Chats the bot is still a member of.
+    pub async fn count_active_chats(&self) -> MyResult<i64> {
+        use crate::db::schema::groups::dsl::*;
+
+        let result = groups
+            .filter(active.eq(true))
+            .count()
+            .get_result(&mut self.conn("count_active_chats").await?)
+            .await?;
+
+        Ok(result)
+    }
+
+    /// `(voices, gifs)` awaiting review.
+    pub async fn count_pending_submissions(&self) -> MyResult<(i64, i64)> {
+        use crate::db::schema::inline_gifs;
+        use crate::db::schema::inline_voices;
+
+        let conn = &mut self.conn("count_pending_submissions").await?;
+
+        let voices = inline_voices::table
+            .filter(inline_voices::status.eq(INLINE_CONTENT_PENDING))
+            .count()
+            .get_result(conn)
+            .await?;
+        let gifs = inline_gifs::table
+            .filter(inline_gifs::status.eq(INLINE_CONTENT_PENDING))
+            .count()
+            .get_result(conn)
+            .await?;
+
+        Ok((voices, gifs))
+    }
+
+    /// The job and its whole audience in one transaction, so the worker never
+    /// sees a job with half its targets. `targets` are `(chat_id, kind)`.
+    pub async fn add_broadcast(
+        &self,
+        new_broadcast: NewBroadcast,
+        targets: &[(i64, i16)],
+    ) -> MyResult<Broadcast> {
+        use crate::db::schema::{broadcast_targets, broadcasts};
+
+        let mut conn = self.conn("add_broadcast").await?;
+        let result = conn
+            .transaction::<_, MyError, _>(|conn| {
+                async move {
+                    let job = diesel::insert_into(broadcasts::table)
+                        .values(&new_broadcast)
+                        .returning(Broadcast::as_returning())
+                        .get_result(conn)
+                        .await?;
+
+                    for chunk in targets.chunks(BROADCAST_TARGETS_CHUNK) {
+                        let rows: Vec<_> = chunk
+                            .iter()
+                            .map(|&(target_id, target_kind)| {
+                                (
+                                    broadcast_targets::broadcast_id.eq(job.id),
+                                    broadcast_targets::chat_id.eq(target_id),
+                                    broadcast_targets::kind.eq(target_kind),
+                                )
+                            })
+                            .collect();
+
+                        diesel::insert_into(broadcast_targets::table)
+                            .values(rows)
+                            .on_conflict_do_nothing()
+                            .execute(conn)
+                            .await?;
+                    }
+
+                    Ok(job)
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        Ok(result)
+    }
+
+    pub async fn get_broadcast(
+        &self,
+        broadcast_id: i32,
+    ) -> MyResult<Option<Broadcast>> {
+        use crate::db::schema::broadcasts::dsl::*;
+
+        let results = broadcasts
+            .find(broadcast_id)
+            .select(Broadcast::as_select())
+            .first(&mut self.conn("get_broadcast").await?)
+            .await
+            .optional()?;
+
+        Ok(results)
+    }
+
+    /// Newest first.
+    pub async fn get_broadcasts(&self, limit: i64) -> MyResult<Vec<Broadcast>> {
+        use crate::db::schema::broadcasts::dsl::*;
+
+        let results = broadcasts
+            .select(Broadcast::as_select())
+            .order(id.desc())
+            .limit(limit)
+            .load(&mut self.conn("get_broadcasts").await?)
+            .await?;
+
+        Ok(results)
+    }
+
+    /// Oldest first, so jobs run in the order they were queued.
+    pub async fn get_next_running_broadcast(
+        &self,
+    ) -> MyResult<Option<Broadcast>> {
+        use crate::db::schema::broadcasts::dsl::*;
+
+        let results = broadcasts
+            .filter(status.eq(BROADCAST_RUNNING))
+            .select(Broadcast::as_select())
+            .order(id.asc())
+            .first(&mut self.conn("get_next_running_broadcast").await?)
+            .await
+            .optional()?;
+
+        Ok(results)
+    }
+
+    /// Only moves a job that is in one of `from`; `false` otherwise, so pause
+    /// and cancel can't resurrect a finished job.
+    pub async fn change_broadcast_status(
+        &self,
+        broadcast_id: i32,
+        from: &[i16],
+        to: i16,
+        finished: Option<NaiveDateTime>,
+    ) -> MyResult<bool> {
+        use crate::db::schema::broadcasts::dsl::*;
+
+        let updated = diesel::update(broadcasts.find(broadcast_id))
+            .filter(status.eq_any(from))
+            .set((status.eq(to), finished_at.eq(finished)))
+            .execute(&mut self.conn("change_broadcast_status").await?)
+            .await?;
+
+        Ok(updated == 1)
+    }
+
+    /// Marks up to `limit` pending targets claimed at `now` and returns them
+    /// as `(chat_id, kind)`, chats before users like the old in-process
+    /// loop. `SKIP LOCKED`, so two workers never claim the same row. A claim
+    /// made before `stale_before` belongs to a worker that died mid-batch
+    /// and is taken over.
+    pub async fn claim_broadcast_targets(
+        &self,
+        id_broadcast: i32,
+        limit: i64,
+        now: NaiveDateTime,
+        stale_before: NaiveDateTime,
+    ) -> MyResult<Vec<(i64, i16)>> {
+        use crate::db::schema::broadcast_targets::dsl::*;
+
+        let mut conn = self.conn("claim_broadcast_targets").await?;
+        let results = conn
+            .transaction::<_, MyError, _>(|conn| {
+                async move {
+                    let stale = status
+                        .eq(BROADCAST_TARGET_CLAIMED)
+                        .and(sent_at.assume_not_null().lt(stale_before));
+
+                    let claimed: Vec<(i64, i16)> = broadcast_targets
+                        .filter(broadcast_id.eq(id_broadcast))
+                        .filter(status.eq(BROADCAST_TARGET_PENDING).or(stale))
+                        .select((chat_id, kind))
+                        .order((kind.asc(), chat_id.asc()))
+                        .limit(limit)
+                        .for_update()
+                        .skip_locked()
+                        .load(conn)
+                        .await?;
+
+                    let ids: Vec<i64> =
+                        claimed.iter().map(|&(id, _)| id).collect();
+                    diesel::update(broadcast_targets)
+                        .filter(broadcast_id.eq(id_broadcast))
+                        .filter(chat_id.eq_any(&ids))
+                        .set((
+                            status.eq(BROADCAST_TARGET_CLAIMED),
+                            sent_at.eq(now),
+                        ))
+                        .execute(conn)
+                        .await?;
+
+                    Ok(claimed)
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        Ok(results)
+    }
+
+    /// Whether some worker is still sending a batch of this job.
+    pub async fn has_claimed_broadcast_targets(
+        &self,
+        id_broadcast: i32,
+    ) -> MyResult<bool> {
+        use crate::db::schema::broadcast_targets::dsl::*;
+
+        let result = diesel::select(diesel::dsl::exists(
+            broadcast_targets
+                .filter(broadcast_id.eq(id_broadcast))
+                .filter(status.eq(BROADCAST_TARGET_CLAIMED)),
+        ))
+        .get_result(&mut self.conn("has_claimed_broadcast_targets").await?)
+        .await?;
+
+        Ok(result)
+    }
+
+    pub async fn set_broadcast_target_status(
+        &self,
+        id_broadcast: i32,
+        id_chat: i64,
+        new_status: i16,
+        sent: NaiveDateTime,
+    ) -> MyResult<()> {
+        use crate::db::schema::broadcast_targets::dsl::*;
+
+        diesel::update(broadcast_targets.find((id_broadcast, id_chat)))
+            .set((status.eq(new_status), sent_at.eq(sent)))
+            .execute(&mut self.conn("set_broadcast_target_status").await?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// `(kind, status, count)` for every combination present.
+    pub async fn get_broadcast_progress(
+        &self,
+        id_broadcast: i32,
+    ) -> MyResult<Vec<(i16, i16, i64)>> {
+        use crate::db::schema::broadcast_targets::dsl::*;
+
+        let results = broadcast_targets
+            .filter(broadcast_id.eq(id_broadcast))
+            .group_by((kind, status))
+            .select((kind, status, diesel::dsl::count_star()))
+            .load(&mut self.conn("get_broadcast_progress").await?)
             .await?;
 
         Ok(results)
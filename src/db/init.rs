@@ -1,9 +1,10 @@
 use std::sync::{Arc, LazyLock, OnceLock};
 
-use crate::config::env::BOT_CONFIG;
+use crate::config::env::{BOT_CONFIG, DbPoolConfig};
 use crate::db::api::{chat_pig::ChatPig, hand_pig::HandPig, other::Other};
 use crate::types::{DbConn, DbPool};
 
+use deadpool::Runtime;
 use diesel_async::pooled_connection::{
     AsyncDieselConnectionManager, deadpool::Pool,
 };
@@ -36,13 +37,21 @@ impl Database {
         static POOL: OnceLock<Arc<DbPool>> = OnceLock::new();
 
         POOL.get_or_init(|| {
-            Arc::new(Self::build_pool(BOT_CONFIG.database_url.as_str()))
+            Arc::new(Self::build_pool(
+                BOT_CONFIG.database_url.as_str(),
+                &BOT_CONFIG.db_pool,
+            ))
         })
         .clone()
     }
 
-    pub fn build_pool(database_url: &str) -> DbPool {
+    pub fn build_pool(database_url: &str, limits: &DbPoolConfig) -> DbPool {
         Pool::builder(Self::get_config(database_url))
+            .max_size(limits.max_size)
+            .wait_timeout(Some(limits.wait_timeout))
+            .create_timeout(Some(limits.create_timeout))
+            .recycle_timeout(Some(limits.recycle_timeout))
+            .runtime(Runtime::Tokio1)
             .build()
             .expect("Something wrong with Pool manager!")
     }
@@ -6,6 +6,30 @@ use chrono::NaiveDateTime;
 use diesel::prelude::*;
 
 use crate::db::schema::*;
+use crate::enums::UserSetting;
+
+#[derive(Queryable, Selectable, Debug)]
+#[diesel(table_name = broadcasts)]
+pub struct Broadcast {
+    pub id: i32,
+    pub from_chat_id: i64,
+    pub message_id: i32,
+    pub created_by: i32,
+    pub report_chat_id: i64,
+    pub status: i16,
+    pub created_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = broadcasts)]
+pub struct NewBroadcast {
+    pub from_chat_id: i64,
+    pub message_id: i32,
+    pub created_by: i32,
+    pub report_chat_id: i64,
+    pub created_at: NaiveDateTime,
+}
 
 #[derive(Queryable, Debug)]
 #[diesel(table_name = counter)]
@@ -53,6 +77,7 @@ pub struct Groups {
     pub username: Option<String>,
     pub title: String,
     pub reset_at: Option<NaiveDateTime>,
+    pub strict_names: bool,
 }
 
 /// `treat_none_as_null` so a `None` writes NULL instead of skipping the
@@ -72,6 +97,7 @@ pub struct UpdateGroups {
     pub username: Option<String>,
     pub title: String,
     pub reset_at: Option<NaiveDateTime>,
+    pub strict_names: bool,
 }
 
 // TODO: split models
@@ -88,6 +114,7 @@ impl Groups {
             username: self.username.clone(),
             title: self.title.clone(),
             reset_at: self.reset_at,
+            strict_names: self.strict_names,
         }
     }
 }
@@ -160,6 +187,12 @@ pub struct InlineVoice {
     pub uid: i32,
     pub caption: String,
     pub status: i16,
+    pub file_id: String,
+    pub review_message_id: Option<i32>,
+    pub submitted_at: Option<NaiveDateTime>,
+    pub reject_reason: Option<i16>,
+    pub file_unique_id: Option<String>,
+    pub fingerprint: Option<i64>,
 }
 
 #[derive(Queryable, Selectable, Debug)]
@@ -170,6 +203,95 @@ pub struct InlineGif {
     pub file_unique_id: String,
     pub uid: i32,
     pub status: i16,
+    pub review_message_id: Option<i32>,
+    pub submitted_at: Option<NaiveDateTime>,
+    pub reject_reason: Option<i16>,
+    pub caption: String,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug)]
+#[diesel(table_name = inline_voice_awards)]
+pub struct InlineVoiceAward {
+    pub month: NaiveDate,
+    pub voice_id: i16,
+    pub uid: i32,
+    pub uses: i32,
+    pub awarded_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Debug)]
+#[diesel(table_name = moderation_log)]
+pub struct ModerationLog {
+    pub id: i32,
+    pub staff_uid: i32,
+    pub target_uid: i32,
+    pub action: i16,
+    pub note: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = moderation_log)]
+pub struct NewModerationLog<'a> {
+    pub staff_uid: i32,
+    pub target_uid: i32,
+    pub action: i16,
+    pub note: &'a str,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug)]
+#[diesel(table_name = staff)]
+pub struct Staff {
+    pub uid: i32,
+    pub role: i16,
+    pub granted_by: Option<i32>,
+    pub granted_at: NaiveDateTime,
+}
+
+/// See the `user_settings` migration for why a user may have no row.
+#[derive(
+    Queryable, Selectable, Insertable, AsChangeset, Clone, Copy, Debug,
+)]
+#[diesel(table_name = user_settings, primary_key(uid))]
+pub struct UserSettings {
+    pub uid: i32,
+    pub notify_day_pig: bool,
+    pub notify_duels: bool,
+    pub notify_achievements: bool,
+    pub mention_me: bool,
+}
+
+impl UserSettings {
+    /// Matches the column defaults.
+    pub const fn new(uid: i32) -> Self {
+        Self {
+            uid,
+            notify_day_pig: false,
+            notify_duels: false,
+            notify_achievements: false,
+            mention_me: true,
+        }
+    }
+
+    pub const fn get(&self, setting: UserSetting) -> bool {
+        match setting {
+            UserSetting::NotifyDayPig => self.notify_day_pig,
+            UserSetting::NotifyDuels => self.notify_duels,
+            UserSetting::NotifyAchievements => self.notify_achievements,
+            UserSetting::MentionMe => self.mention_me,
+        }
+    }
+
+    pub fn toggle(&mut self, setting: UserSetting) {
+        let value = match setting {
+            UserSetting::NotifyDayPig => &mut self.notify_day_pig,
+            UserSetting::NotifyDuels => &mut self.notify_duels,
+            UserSetting::NotifyAchievements => &mut self.notify_achievements,
+            UserSetting::MentionMe => &mut self.mention_me,
+        };
+        *value = !*value;
+    }
 }
 
 #[derive(Insertable, Debug)]
@@ -185,6 +307,7 @@ pub struct NewUser<'a> {
     pub username: Option<&'a str>,
     pub first_name: &'a str,
     pub last_name: Option<&'a str>,
+    pub started_at: Option<NaiveDateTime>,
 }
 
 #[derive(Queryable, Selectable, Debug)]
@@ -201,6 +324,9 @@ pub struct User {
     pub username: Option<String>,
     pub first_name: String,
     pub last_name: Option<String>,
+    /// First `/start` in private; `created_at` is when the bot first saw
+    /// the user anywhere.
+    pub started_at: Option<NaiveDateTime>,
 }
 
 /// See [`UpdateGroups`] on `treat_none_as_null`.
@@ -9,6 +9,46 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    bot_bans (uid) {
+        uid -> Int4,
+        banned_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    broadcast_targets (broadcast_id, chat_id) {
+        broadcast_id -> Int4,
+        chat_id -> Int8,
+        kind -> Int2,
+        status -> Int2,
+        sent_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    broadcasts (id) {
+        id -> Int4,
+        from_chat_id -> Int8,
+        message_id -> Int4,
+        created_by -> Int4,
+        report_chat_id -> Int8,
+        status -> Int2,
+        created_at -> Timestamp,
+        finished_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    duel_log (id) {
+        id -> Int4,
+        winner_uid -> Int4,
+        looser_uid -> Int4,
+        damage -> Int4,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     game (id) {
         id -> Int4,
@@ -37,6 +77,7 @@ diesel::table! {
         #[max_length = 128]
         title -> Varchar,
         reset_at -> Nullable<Timestamp>,
+        strict_names -> Bool,
     }
 }
 
@@ -58,6 +99,14 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    inline_gif_uses (gif_id, month) {
+        gif_id -> Int2,
+        month -> Date,
+        uses -> Int4,
+    }
+}
+
 diesel::table! {
     inline_gifs (id) {
         id -> Int2,
@@ -67,6 +116,11 @@ diesel::table! {
         uid -> Int4,
         #[max_length = 64]
         file_unique_id -> Varchar,
+        review_message_id -> Nullable<Int4>,
+        submitted_at -> Nullable<Timestamp>,
+        reject_reason -> Nullable<Int2>,
+        #[max_length = 64]
+        caption -> Varchar,
     }
 }
 
@@ -102,6 +156,24 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    inline_voice_awards (month) {
+        month -> Date,
+        voice_id -> Int2,
+        uid -> Int4,
+        uses -> Int4,
+        awarded_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    inline_voice_uses (voice_id, month) {
+        voice_id -> Int2,
+        month -> Date,
+        uses -> Int4,
+    }
+}
+
 diesel::table! {
     inline_voices (id) {
         id -> Int2,
@@ -111,6 +183,45 @@ diesel::table! {
         caption -> Varchar,
         status -> Int2,
         uid -> Int4,
+        #[max_length = 128]
+        file_id -> Varchar,
+        review_message_id -> Nullable<Int4>,
+        submitted_at -> Nullable<Timestamp>,
+        reject_reason -> Nullable<Int2>,
+        #[max_length = 64]
+        file_unique_id -> Nullable<Varchar>,
+        fingerprint -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    moderation_log (id) {
+        id -> Int4,
+        staff_uid -> Int4,
+        target_uid -> Int4,
+        action -> Int2,
+        #[max_length = 256]
+        note -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    staff (uid) {
+        uid -> Int4,
+        role -> Int2,
+        granted_by -> Nullable<Int4>,
+        granted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    user_settings (uid) {
+        uid -> Int4,
+        notify_day_pig -> Bool,
+        notify_duels -> Bool,
+        notify_achievements -> Bool,
+        mention_me -> Bool,
     }
 }
 
@@ -131,31 +242,51 @@ diesel::table! {
         last_name -> Nullable<Varchar>,
         #[max_length = 64]
         first_name -> Varchar,
+        started_at -> Nullable<Timestamp>,
     }
 }
 
 diesel::joinable!(achievements_users -> game (game_id));
+diesel::joinable!(bot_bans -> users (uid));
+diesel::joinable!(broadcast_targets -> broadcasts (broadcast_id));
+diesel::joinable!(broadcasts -> users (created_by));
 diesel::joinable!(game -> groups (group_id));
 diesel::joinable!(game -> users (uid));
 diesel::joinable!(groups -> inline_groups (ig_id));
 diesel::joinable!(grow_log -> game (game_id));
 diesel::joinable!(hryak_day -> inline_users_groups (iug_id));
+diesel::joinable!(inline_gif_uses -> inline_gifs (gif_id));
 diesel::joinable!(inline_gifs -> users (uid));
 diesel::joinable!(inline_users -> users (uid));
 diesel::joinable!(inline_users_groups -> inline_groups (ig_id));
 diesel::joinable!(inline_users_groups -> inline_users (iu_id));
+diesel::joinable!(inline_voice_awards -> inline_voices (voice_id));
+diesel::joinable!(inline_voice_awards -> users (uid));
+diesel::joinable!(inline_voice_uses -> inline_voices (voice_id));
 diesel::joinable!(inline_voices -> users (uid));
+diesel::joinable!(staff -> users (uid));
+diesel::joinable!(user_settings -> users (uid));
 
 diesel::allow_tables_to_appear_in_same_query!(
     achievements_users,
+    bot_bans,
+    broadcast_targets,
+    broadcasts,
+    duel_log,
     game,
     groups,
     grow_log,
     hryak_day,
+    inline_gif_uses,
     inline_gifs,
     inline_groups,
     inline_users,
     inline_users_groups,
+    inline_voice_awards,
+    inline_voice_uses,
     inline_voices,
+    moderation_log,
+    staff,
+    user_settings,
     users,
 );
@@ -50,9 +50,11 @@ pub async fn maybe_get_or_insert_user(
         return Ok(Some(user));
     }
 
+    let now = get_datetime();
     let new_user = NewUser {
         user_id: from.id.0 as i64,
-        created_at: get_datetime(),
+        created_at: now,
+        started_at: started.then_some(now),
         first_name: &from.first_name,
         last_name: from.last_name.as_deref(),
         username: from.username.as_deref(),
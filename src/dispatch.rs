@@ -1,39 +1,83 @@
 use teloxide::{
     dispatching::{UpdateFilterExt, UpdateHandler},
     prelude::*,
-    types::MessageKind,
+    types::{Me, MessageKind},
 };
 
 use crate::{
-    enums::{AdminCommands, EpycCommands, MyCommands},
+    enums::{AdminCommands, EpycCommand, MyCommands},
     handlers::{
         admin, callback, command, epyc, feedback, inline, message, system,
     },
+    services::{rate_limit, staff, user_moderation},
     types::MyError,
 };
 
 /// `creator_id` is a parameter rather than a `BOT_CONFIG` read, so building
-/// the tree needs no globals.
+/// the tree needs no globals. It is the implicit owner; everyone else's role
+/// comes from the `staff` table.
+///
+/// Updates from users banned with `/ban` end in the first branch, so no
+/// handler sees them and they don't count as unhandled either.
+///
+/// A throttled command ends in its own branch too: letting it fall through
+/// would hand it to the plain text handlers. Admin commands aren't limited.
 pub fn build_handler(creator_id: u64) -> UpdateHandler<MyError> {
     dptree::entry()
+        .branch(
+            dptree::filter(|u: Update| {
+                u.from().is_some_and(|f| user_moderation::is_banned(f.id))
+            })
+            .endpoint(|| async { Ok::<_, MyError>(()) }),
+        )
         .branch(
             Update::filter_message()
                 .branch(
                     dptree::entry()
                         .filter_command::<MyCommands>()
+                        .branch(
+                            dptree::filter_map(
+                                |m: Message, cmd: MyCommands| {
+                                    rate_limit::throttle(&m, cmd.cost())
+                                },
+                            )
+                            .endpoint(system::handle_throttled),
+                        )
                         .endpoint(command::filter_commands),
                 )
                 .branch(
-                    dptree::entry()
-                        .filter_command::<EpycCommands>()
-                        .endpoint(epyc::filter_commands),
+                    // The aliases come from the locales, so this is no
+                    // `BotCommands` enum.
+                    dptree::filter_map(|m: Message, me: Me| {
+                        EpycCommand::parse(m.text()?, me.username())
+                    })
+                    .branch(
+                        dptree::filter_map(|m: Message| {
+                            rate_limit::throttle(&m, 1)
+                        })
+                        .endpoint(system::handle_throttled),
+                    )
+                    .endpoint(epyc::filter_commands),
                 )
                 .branch(
-                    dptree::filter(move |m: Message| {
-                        m.from.as_ref().is_some_and(|u| u.id.0 == creator_id)
-                    })
-                    .filter_command::<AdminCommands>()
-                    .endpoint(admin::filter_admin_commands),
+                    // Parse first: the role lookup hits the database, and
+                    // only admin commands should pay for it.
+                    dptree::entry()
+                        .filter_command::<AdminCommands>()
+                        .filter_map_async(move |m: Message| async move {
+                            let from = m.from.as_ref()?;
+                            staff::role_of(creator_id, from.id)
+                                .await
+                                .unwrap_or_else(|err| {
+                                    crate::myerr!(
+                                        "Error {:?}: staff role of [{}]",
+                                        err,
+                                        from.id
+                                    );
+                                    None
+                                })
+                        })
+                        .endpoint(admin::filter_admin_commands),
                 )
                 .branch(
                     Message::filter_new_chat_members()
@@ -2,12 +2,16 @@
 
 use std::str::FromStr;
 
-use strum::{Display, EnumString, IntoStaticStr};
+use num_derive::FromPrimitive;
+use strum::{Display, EnumString, IntoStaticStr, VariantArray};
 use teloxide::macros::BotCommands;
 
+use crate::lang;
+
 // Descriptions of BotCommands — check locales /<command>_desc
-#[derive(BotCommands, Clone, Debug)]
+#[derive(BotCommands, Clone, Debug, IntoStaticStr)]
 #[command(rename_rule = "lowercase")]
+#[strum(serialize_all = "lowercase")]
 pub enum MyCommands {
     /// System
     Start,
@@ -31,18 +35,44 @@ pub enum MyCommands {
     Louder,
     Achievements,
     ResetPigs,
+    MySubmissions,
+    Settings,
 }
 
-#[derive(BotCommands, Clone, Debug)]
-#[command(rename_rule = "lowercase")]
-pub enum EpycCommands {
-    // Command center, check for multilang desc
-    #[command(rename = "епік", prefix = "!")]
-    EpycUA(String),
-    #[command(rename = "эпик", prefix = "!")]
-    EpycRU(String),
-    #[command(rename = "epyc", prefix = "!")]
-    EpycEN(String),
+impl MyCommands {
+    /// Rate limiter tokens: charts and transcoding are the expensive ones.
+    pub const fn cost(&self) -> u32 {
+        match self {
+            Self::Louder => 5,
+            Self::Top | Self::My => 3,
+            _ => 1,
+        }
+    }
+}
+
+/// `!epyc args` in any language listed under `EpycCommandAlias`.
+#[derive(Clone, Debug)]
+pub struct EpycCommand {
+    /// The alias as typed, for the replies.
+    pub name: String,
+    pub args: String,
+}
+
+impl EpycCommand {
+    /// `!<alias>[@bot] [args]`; a mention of another bot is not ours.
+    pub fn parse(text: &str, bot_username: &str) -> Option<Self> {
+        let text = text.strip_prefix('!')?;
+        let (head, args) =
+            text.split_once(char::is_whitespace).unwrap_or((text, ""));
+        let name = match head.split_once('@') {
+            Some((name, bot)) if bot.eq_ignore_ascii_case(bot_username) => name,
+            Some(_) => return None,
+            None => head,
+        };
+
+        lang::is_alias("EpycCommandAlias", name)
+            .then(|| Self { name: name.to_owned(), args: args.to_owned() })
+    }
 }
 
 // Descriptions of BotCommands — check locales /<command>_desc
@@ -51,81 +81,178 @@ pub enum EpycCommands {
 pub enum AdminCommands {
     Promote(String),
     Repost(String),
+    Queue(String),
+    Staff,
+    Grant(String),
+    Revoke(String),
+    Content(String),
+    Broadcast(String),
+    Stats,
+    Ban(String),
+    Unban(String),
+    Rename(String),
+    Mass(String),
+    Modlog(String),
+    ReloadLocales,
+}
+
+impl AdminCommands {
+    pub const fn required_role(&self) -> StaffRole {
+        match self {
+            Self::Queue(_)
+            | Self::Stats
+            | Self::Ban(_)
+            | Self::Unban(_)
+            | Self::Rename(_)
+            | Self::Modlog(_) => StaffRole::Moderator,
+            Self::Promote(_)
+            | Self::Repost(_)
+            | Self::Staff
+            | Self::Content(_)
+            | Self::Broadcast(_)
+            | Self::Mass(_)
+            | Self::ReloadLocales => StaffRole::Admin,
+            Self::Grant(_) | Self::Revoke(_) => StaffRole::Owner,
+        }
+    }
 }
 
-#[derive(EnumString)]
+/// Stored as `staff.role`. Declaration order is the permission order, so a
+/// check is `role >= StaffRole::Admin`.
+#[derive(
+    Clone, Copy, PartialEq, Eq, PartialOrd, Ord, IntoStaticStr, EnumString,
+)]
+#[cfg_attr(test, derive(Debug, strum::VariantArray))]
+#[strum(const_into_str, serialize_all = "snake_case")]
+#[repr(i16)]
+pub enum StaffRole {
+    Moderator = 1,
+    Admin = 2,
+    Owner = 3,
+}
+
+impl StaffRole {
+    pub const fn from_i16(value: i16) -> Option<Self> {
+        match value {
+            1 => Some(Self::Moderator),
+            2 => Some(Self::Admin),
+            3 => Some(Self::Owner),
+            _ => None,
+        }
+    }
+}
+
+/// Stored as `reject_reason` and shown as `RejectReason_<n>`. Append only:
+/// the numbers are in the database and in live review cards.
+#[derive(Clone, Copy, PartialEq, VariantArray, FromPrimitive)]
+#[cfg_attr(test, derive(Debug, Eq))]
+#[repr(i16)]
+pub enum RejectReason {
+    LowQuality = 1,
+    OffTopic = 2,
+    Duplicate = 3,
+    Offensive = 4,
+    Other = 5,
+}
+
+/// Stored as `moderation_log.action` and shown as `ModerationAction_<n>`.
+/// Append only.
+#[derive(Clone, Copy, PartialEq, VariantArray)]
+#[cfg_attr(test, derive(Debug, Eq))]
+#[repr(i16)]
+pub enum ModerationAction {
+    Ban = 1,
+    Unban = 2,
+    RenameChatPig = 3,
+    RenameHandPig = 4,
+    AdjustMass = 5,
+}
+
+/// Inline queries with a payload; the words live under `InlineAlias_*`.
+#[derive(Clone, Copy, VariantArray)]
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
 pub enum InlineCommands {
-    #[strum(
-        serialize = "ім'я",
-        serialize = "імя",
-        serialize = "имя",
-        serialize = "name",
-        serialize = "ad"
-    )]
     Name,
-    #[strum(
-        serialize = "хрю",
-        serialize = "hru",
-        serialize = "grunt",
-        serialize = "xort"
-    )]
     Hru,
-    #[strum(
-        serialize = "прапор",
-        serialize = "флаг",
-        serialize = "flag",
-        serialize = "bayraq"
-    )]
     Flag,
-    #[strum(serialize = "гіф", serialize = "гиф", serialize = "gif")]
     Gif,
 }
 
-#[derive(EnumString)]
+impl InlineCommands {
+    pub fn from_alias(word: &str) -> Option<Self> {
+        Self::VARIANTS
+            .iter()
+            .copied()
+            .find(|cmd| lang::is_alias(cmd.alias_key(), word))
+    }
+
+    const fn alias_key(self) -> &'static str {
+        match self {
+            Self::Name => "InlineAlias_Name",
+            Self::Hru => "InlineAlias_Hru",
+            Self::Flag => "InlineAlias_Flag",
+            Self::Gif => "InlineAlias_Gif",
+        }
+    }
+
+    /// One name per variant for the metrics, whichever alias was typed.
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Hru => "hru",
+            Self::Flag => "flag",
+            Self::Gif => "gif",
+        }
+    }
+}
+
+/// Whole inline queries; the words live under `InlineAlias_*`.
+#[derive(Clone, Copy, VariantArray)]
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
 pub enum InlineKeywords {
-    #[strum(
-        serialize = "ім'я",
-        serialize = "імя",
-        serialize = "имя",
-        serialize = "name",
-        serialize = "ad"
-    )]
     Name,
-    #[strum(
-        serialize = "хряк",
-        serialize = "свиня",
-        serialize = "свинья",
-        serialize = "pig",
-        serialize = "donuz"
-    )]
     DayPig,
-    #[strum(serialize = "ос", serialize = "oc")]
     OC,
-    #[strum(
-        serialize = "хрю",
-        serialize = "hru",
-        serialize = "grunt",
-        serialize = "xort"
-    )]
     Hru,
-    #[strum(
-        serialize = "прапор",
-        serialize = "флаг",
-        serialize = "flag",
-        serialize = "bayraq"
-    )]
     Flag,
-    #[strum(
-        serialize = "мова",
-        serialize = "язык",
-        serialize = "lang",
-        serialize = "dil"
-    )]
     Lang,
-    #[strum(serialize = "гіф", serialize = "гиф", serialize = "gif")]
     Gif,
 }
 
+impl InlineKeywords {
+    pub fn from_alias(word: &str) -> Option<Self> {
+        Self::VARIANTS
+            .iter()
+            .copied()
+            .find(|kwd| lang::is_alias(kwd.alias_key(), word))
+    }
+
+    const fn alias_key(self) -> &'static str {
+        match self {
+            Self::Name => "InlineAlias_Name",
+            Self::DayPig => "InlineAlias_DayPig",
+            Self::OC => "InlineAlias_OC",
+            Self::Hru => "InlineAlias_Hru",
+            Self::Flag => "InlineAlias_Flag",
+            Self::Lang => "InlineAlias_Lang",
+            Self::Gif => "InlineAlias_Gif",
+        }
+    }
+
+    /// One name per variant for the metrics, whichever alias was typed.
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::DayPig => "day_pig",
+            Self::OC => "oc",
+            Self::Hru => "hru",
+            Self::Flag => "flag",
+            Self::Lang => "lang",
+            Self::Gif => "gif",
+        }
+    }
+}
+
 #[derive(Clone, Copy, IntoStaticStr, EnumString)]
 #[cfg_attr(test, derive(Debug, PartialEq, Eq, strum::VariantArray))]
 #[strum(const_into_str, serialize_all = "snake_case")]
@@ -146,6 +273,74 @@ pub enum CbActions {
     SubGift,
     GifDecision,
     ResetVote,
+    ReviewBack,
+    Settings,
+    SettingsLang,
+    SettingsFlag,
+    SettingsToggle,
+}
+
+/// Where `/settings` is; the payload of [`CbActions::Settings`].
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+pub enum SettingsPage {
+    Main,
+    Lang,
+    /// Zero-based page of the flag picker.
+    Flags(usize),
+    Notifications,
+    Privacy,
+}
+
+impl SettingsPage {
+    pub fn from_payload(payload: &str) -> Option<Self> {
+        let page = match payload {
+            "" => Self::Main,
+            "lang" => Self::Lang,
+            "notify" => Self::Notifications,
+            "privacy" => Self::Privacy,
+            _ => Self::Flags(payload.strip_prefix("flags")?.parse().ok()?),
+        };
+
+        Some(page)
+    }
+
+    pub fn to_payload(self) -> String {
+        match self {
+            Self::Main => String::new(),
+            Self::Lang => "lang".to_owned(),
+            Self::Flags(page) => format!("flags{page}"),
+            Self::Notifications => "notify".to_owned(),
+            Self::Privacy => "privacy".to_owned(),
+        }
+    }
+}
+
+/// A `/settings` switch, stored in `user_settings`. The name is the payload
+/// of [`CbActions::SettingsToggle`] and, after `UserSetting_`, the locale key
+/// of its label.
+#[derive(Clone, Copy, IntoStaticStr, EnumString, VariantArray)]
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+#[strum(const_into_str, serialize_all = "snake_case")]
+pub enum UserSetting {
+    NotifyDayPig,
+    NotifyDuels,
+    NotifyAchievements,
+    MentionMe,
+}
+
+impl UserSetting {
+    pub const NOTIFICATIONS: [Self; 3] =
+        [Self::NotifyDayPig, Self::NotifyDuels, Self::NotifyAchievements];
+    pub const PRIVACY: [Self; 1] = [Self::MentionMe];
+
+    /// The page listing it, to return to after a toggle.
+    pub const fn page(self) -> SettingsPage {
+        match self {
+            Self::MentionMe => SettingsPage::Privacy,
+            _ => SettingsPage::Notifications,
+        }
+    }
 }
 
 #[derive(IntoStaticStr, EnumString, Display)]
@@ -215,6 +410,7 @@ pub enum InlineResults {
     GetMoreInfo,
     NameHryakInfo,
     RenameHryakInfo,
+    NameRejectedInfo,
     DayPigInfo,
     FlagInfo,
     FlagEmptyInfo,
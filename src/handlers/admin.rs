@@ -1,42 +1,61 @@
-use std::time::Duration;
+use std::{str::FromStr, time::Duration};
 
-use ahash::AHashSet;
+use chrono::NaiveDateTime;
 use futures::FutureExt;
-use teloxide::{ApiError, RequestError, prelude::*};
+use teloxide::{prelude::*, types::InputFile, utils::html::escape};
 
 use crate::{
-    config::{consts::HAND_PIG_ADDITION_ON_SUPPORTED, env::BOT_CONFIG},
+    config::{
+        consts::{
+            BROADCAST_CANCELLED, BROADCAST_PAUSED, BROADCAST_RUNNING,
+            BROADCAST_TARGET_CHAT, BROADCAST_TARGET_USER,
+            HAND_PIG_ADDITION_ON_SUPPORTED, INLINE_CAPTION_LIMIT,
+            INLINE_CONTENT_APPROVED, INLINE_CONTENT_HIDDEN,
+            INLINE_NAME_SET_LIMIT, MODERATION_NOTE_LIMIT,
+        },
+        env::BOT_CONFIG,
+    },
     db::{
         DB,
-        models::{Groups, UpdateGroups, User, UserStatus},
+        models::{
+            Groups, NewBroadcast, NewModerationLog, Staff, User, UserStatus,
+        },
+    },
+    enums::{AdminCommands, ModerationAction, StaffRole},
+    lang::{self, InnerLang, LocaleTag, get_tag, lng, tag_one_or},
+    services::{
+        broadcast::{self, Audience, BroadcastProgress},
+        charts, moderation, staff,
+        user_moderation::{self, UserTarget},
     },
-    enums::AdminCommands,
-    lang::{InnerLang, LocaleTag, get_tag, lng, tag_one_or},
     traits::MaybeMessageSetter,
     types::{MyBot, MyResult},
-    utils::{date::get_date, formulas::calculate_hryak_size, helpers},
+    utils::{
+        date::{get_date, get_datetime},
+        formulas::calculate_hryak_size,
+        helpers,
+    },
 };
 
-#[derive(Eq, Hash, PartialEq)]
-enum RepostTarget {
-    DM,
-    Chats,
-}
-
-struct PostStats {
-    pub chat_sended_count: u32,
-    pub chat_sended_error_count: u32,
-    pub user_sended_count: u32,
-    pub user_sended_error_count: u32,
-}
-
-const USER_SENDING_THROTTLE_TIME_MS: u64 = 333;
 const CHAT_SENDING_THROTTLE_TIME_MS: u64 = 333;
+/// Per kind, to stay well under Telegram's message length limit.
+const QUEUE_LIST_LIMIT: usize = 30;
+/// Newest first in `/broadcast`.
+const BROADCAST_LIST_LIMIT: i64 = 10;
+/// Recipients of each kind listed by `/repost --dry-run`.
+const BROADCAST_SAMPLE_SIZE: usize = 10;
+/// Chats listed (and charted) by `/stats`.
+const STATS_TOP_CHATS: i64 = 5;
+/// Newest first in `/modlog`.
+const MODERATION_LOG_LIMIT: i64 = 10;
+/// Problems listed by a failed `/reloadlocales`; the rest are only logged.
+const RELOAD_PROBLEM_LIMIT: usize = 20;
 
 pub async fn filter_admin_commands(
     bot: MyBot,
     m: Message,
     cmd: AdminCommands,
+    role: StaffRole,
 ) -> MyResult<()> {
     let Some(from) = &m.from else { return Ok(()) };
     let Some(user) = DB.other.get_user(from.id.0 as i64).await? else {
@@ -45,12 +64,55 @@ pub async fn filter_admin_commands(
 
     let ltag = tag_one_or(user.lang.as_deref(), get_tag(from));
 
+    let required = cmd.required_role();
+    if role < required {
+        let text = lng("AdminCommandRoleRequired", ltag)
+            .args(&[("role", _role_name(required, ltag))]);
+        bot.send_message(m.chat.id, text).maybe_thread_id(&m).await?;
+        return Ok(());
+    }
+
     let function = match &cmd {
         AdminCommands::Promote(arg) => {
             admin_command_promote(bot, &m, ltag, arg).boxed()
         },
         AdminCommands::Repost(arg) => {
-            admin_command_repost(bot, &m, ltag, arg).boxed()
+            admin_command_repost(bot, &m, ltag, &user, arg).boxed()
+        },
+        AdminCommands::Queue(arg) => {
+            admin_command_queue(bot, &m, ltag, arg).boxed()
+        },
+        AdminCommands::Staff => admin_command_staff(bot, &m, ltag).boxed(),
+        AdminCommands::Grant(arg) => {
+            admin_command_grant(bot, &m, ltag, &user, arg).boxed()
+        },
+        AdminCommands::Revoke(arg) => {
+            admin_command_revoke(bot, &m, ltag, arg).boxed()
+        },
+        AdminCommands::Content(arg) => {
+            admin_command_content(bot, &m, ltag, arg).boxed()
+        },
+        AdminCommands::Broadcast(arg) => {
+            admin_command_broadcast(bot, &m, ltag, arg).boxed()
+        },
+        AdminCommands::Stats => admin_command_stats(bot, &m, ltag).boxed(),
+        AdminCommands::Ban(arg) => {
+            admin_command_ban(bot, &m, ltag, &user, arg).boxed()
+        },
+        AdminCommands::Unban(arg) => {
+            admin_command_unban(bot, &m, ltag, &user, arg).boxed()
+        },
+        AdminCommands::Rename(arg) => {
+            admin_command_rename(bot, &m, ltag, &user, arg).boxed()
+        },
+        AdminCommands::Mass(arg) => {
+            admin_command_mass(bot, &m, ltag, &user, arg).boxed()
+        },
+        AdminCommands::Modlog(arg) => {
+            admin_command_modlog(bot, &m, ltag, arg).boxed()
+        },
+        AdminCommands::ReloadLocales => {
+            admin_command_reload_locales(bot, &m, ltag).boxed()
         },
     };
 
@@ -158,188 +220,1028 @@ async fn admin_command_promote(
     Ok(())
 }
 
-async fn admin_command_repost(
+async fn admin_command_queue(
     bot: MyBot,
     m: &Message,
     ltag: LocaleTag,
     arg: &str,
 ) -> MyResult<()> {
-    let m = m.clone();
-    let arg = arg.to_string();
-    tokio::spawn(async move {
-        _inner_admin_command_repost(bot, &m, ltag, &arg).await
+    let resend_all = match arg.split_whitespace().collect::<Vec<_>>()[..] {
+        [] => return _queue_list(bot, m, ltag).await,
+        ["resend"] => false,
+        ["resend", "all"] => true,
+        _ => {
+            let text = lng("AdminCommandQueueUnknownArg", ltag);
+            bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+            return Ok(());
+        },
+    };
+
+    let (voices, gifs) = tokio::try_join!(
+        DB.other.get_pending_voices(),
+        DB.other.get_pending_gifs(),
+    )?;
+
+    let throttle = Duration::from_millis(CHAT_SENDING_THROTTLE_TIME_MS);
+    let mut resent = 0;
+    let mut failed = 0;
+
+    for (voice, user) in voices {
+        if !resend_all && voice.review_message_id.is_some() {
+            continue;
+        }
+        let submitter = UserId(user.user_id as u64);
+        match moderation::send_voice_review_card(&bot, &voice, submitter).await
+        {
+            Ok(()) => resent += 1,
+            Err(err) => {
+                log::error!("Failed to re-send voice [{}]: {}", voice.id, err);
+                failed += 1;
+            },
+        }
+        tokio::time::sleep(throttle).await;
+    }
+
+    for (gif, user) in gifs {
+        if !resend_all && gif.review_message_id.is_some() {
+            continue;
+        }
+        let submitter = UserId(user.user_id as u64);
+        match moderation::send_gif_review_card(&bot, &gif, submitter).await {
+            Ok(()) => resent += 1,
+            Err(err) => {
+                log::error!("Failed to re-send gif [{}]: {}", gif.id, err);
+                failed += 1;
+            },
+        }
+        tokio::time::sleep(throttle).await;
+    }
+
+    let text = lng("AdminCommandQueueResent", ltag)
+        .args(&[("count", resent), ("errors", failed)]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+async fn _queue_list(bot: MyBot, m: &Message, ltag: LocaleTag) -> MyResult<()> {
+    let (voices, gifs) = tokio::try_join!(
+        DB.other.get_pending_voices(),
+        DB.other.get_pending_gifs(),
+    )?;
+
+    if voices.is_empty() && gifs.is_empty() {
+        let text = lng("AdminCommandQueueEmpty", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    }
+
+    let voice_lines = voices.iter().take(QUEUE_LIST_LIMIT).map(|(v, u)| {
+        _queue_line("🎙", v.id, u, v.submitted_at, v.review_message_id)
+    });
+    let gif_lines = gifs.iter().take(QUEUE_LIST_LIMIT).map(|(g, u)| {
+        _queue_line("🐖", g.id, u, g.submitted_at, g.review_message_id)
     });
+    let lines = voice_lines.chain(gif_lines).collect::<Vec<_>>().join("\n");
+
+    let text = lng("AdminCommandQueueList", ltag).args(&[
+        ("voices", voices.len().to_string()),
+        ("gifs", gifs.len().to_string()),
+        ("lines", lines),
+    ]);
+    bot.send_message(m.chat.id, text)
+        .disable_web_page_preview(true)
+        .maybe_thread_id(m)
+        .await?;
+
+    Ok(())
+}
+
+/// A missing card is flagged rather than linked — that is what
+/// `/queue resend` is for.
+fn _queue_line(
+    kind: &str,
+    id: i16,
+    user: &User,
+    submitted_at: Option<NaiveDateTime>,
+    review_message_id: Option<i32>,
+) -> String {
+    let submitted = submitted_at.map_or_else(
+        || "-".to_owned(),
+        |d| d.format("%d.%m %H:%M").to_string(),
+    );
+    let card = review_message_id.map_or_else(
+        || "⚠️".to_owned(),
+        |mid| {
+            format!(
+                "<a href=\"https://t.me/{}/{}\">↗️</a>",
+                BOT_CONFIG.content_check_channel_name, mid
+            )
+        },
+    );
+
+    format!(
+        "{kind} <b>#{id}</b> · <code>{}</code> · {submitted} · {card}",
+        user.user_id
+    )
+}
+
+async fn admin_command_staff(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+) -> MyResult<()> {
+    let staff = DB.other.get_staff().await?;
+
+    let lines = staff
+        .iter()
+        .filter_map(|(s, u)| {
+            let role = StaffRole::from_i16(s.role)?;
+            Some(format!(
+                "• <b>{}</b> · #{} · <code>{}</code> · {}",
+                _role_name(role, ltag),
+                u.id,
+                u.user_id,
+                escape(&u.first_name)
+            ))
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let text = lng("AdminCommandStaffList", ltag).args(&[
+        ("creator", BOT_CONFIG.creator_id.to_string()),
+        ("lines", lines),
+    ]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+async fn admin_command_grant(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    granter: &User,
+    arg: &str,
+) -> MyResult<()> {
+    let (Some(target), Some(role)) = _parse_staff_arg(arg) else {
+        let text = lng("AdminCommandGrantUsage", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let Some(user) = DB.other.get_user_by_id(target).await? else {
+        let text = lng("AdminCommandPromoteNotFound", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let entry = Staff {
+        uid: user.id,
+        role: role as i16,
+        granted_by: Some(granter.id),
+        granted_at: get_datetime(),
+    };
+    DB.other.set_staff(entry).await?;
+
+    let text = lng("AdminCommandGrantSuccess", ltag).args(&[
+        ("name", escape(&user.first_name)),
+        ("role", _role_name(role, ltag)),
+    ]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+async fn admin_command_revoke(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    arg: &str,
+) -> MyResult<()> {
+    let (Some(target), None) = _parse_staff_arg(arg) else {
+        let text = lng("AdminCommandRevokeUsage", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let key = if DB.other.remove_staff(target).await? {
+        "AdminCommandRevokeSuccess"
+    } else {
+        "AdminCommandRevokeNotStaff"
+    };
+    bot.send_message(m.chat.id, lng(key, ltag)).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+/// `<users.id> [role]`, the id being the one `/id` shows.
+fn _parse_staff_arg(arg: &str) -> (Option<i32>, Option<StaffRole>) {
+    let mut words = arg.split_whitespace();
+    let target = words.next().and_then(|w| w.parse().ok());
+    let role = words.next().and_then(|w| StaffRole::from_str(w).ok());
+
+    if words.next().is_some() {
+        return (None, None);
+    }
+
+    (target, role)
+}
+
+fn _role_name(role: StaffRole, ltag: LocaleTag) -> String {
+    let key = match role {
+        StaffRole::Moderator => "StaffRoleModerator",
+        StaffRole::Admin => "StaffRoleAdmin",
+        StaffRole::Owner => "StaffRoleOwner",
+    };
+
+    lng(key, ltag)
+}
+
+/// `<voice|gif> <id> <hide|restore|replace|caption <text>>`. Ids never
+/// change, so results already shared in chats keep pointing at the row.
+async fn admin_command_content(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    arg: &str,
+) -> MyResult<()> {
+    let mut words = arg.trim().splitn(4, char::is_whitespace);
+    let kind = words.next().unwrap_or_default();
+    let id = words.next().and_then(|w| w.parse::<i16>().ok());
+    let action = words.next().unwrap_or_default();
+    let rest = words.next().unwrap_or_default().trim();
+
+    let Some(id) = id.filter(|_| matches!(kind, "voice" | "gif")) else {
+        let text = lng("AdminCommandContentUsage", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let (approved, hidden) = (INLINE_CONTENT_APPROVED, INLINE_CONTENT_HIDDEN);
+
+    let done = match (kind, action) {
+        ("voice", "hide") => {
+            DB.other.change_voice_status(id, approved, hidden).await?.is_some()
+        },
+        ("voice", "restore") => {
+            DB.other.change_voice_status(id, hidden, approved).await?.is_some()
+        },
+        ("gif", "hide") => {
+            DB.other.change_gif_status(id, approved, hidden).await?.is_some()
+        },
+        ("gif", "restore") => {
+            DB.other.change_gif_status(id, hidden, approved).await?.is_some()
+        },
+        ("voice", "replace") => {
+            return _content_replace_voice(bot, m, ltag, id).await;
+        },
+        ("gif", "replace") => {
+            return _content_replace_gif(bot, m, ltag, id).await;
+        },
+        (_, "caption") if !rest.is_empty() => {
+            if rest.chars().count() > INLINE_CAPTION_LIMIT {
+                let text = lng("AdminCommandContentCaptionTooLong", ltag)
+                    .args(&[("limit", INLINE_CAPTION_LIMIT)]);
+                bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+                return Ok(());
+            }
+
+            // `-` drops the caption, bringing back the numbered default.
+            let caption = if rest == "-" { "" } else { rest };
+            if kind == "voice" {
+                DB.other.set_voice_caption(id, caption).await?
+            } else {
+                DB.other.set_gif_caption(id, caption).await?
+            }
+        },
+        _ => {
+            let text = lng("AdminCommandContentUsage", ltag);
+            bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+            return Ok(());
+        },
+    };
+
+    let key = if done {
+        "AdminCommandContentDone"
+    } else {
+        "AdminCommandContentNotFound"
+    };
+    bot.send_message(m.chat.id, lng(key, ltag)).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+/// The browser links voices to a public post, so the new file gets one in
+/// the check channel, the same as an approval.
+async fn _content_replace_voice(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    id: i16,
+) -> MyResult<()> {
+    let Some(voice) = m.reply_to_message().and_then(|r| r.voice()) else {
+        let text = lng("AdminCommandContentReplyWithFile", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let unique_id = &voice.file.unique_id.0;
+    if let Some(existing) =
+        DB.other.get_voice_by_file_unique_id(unique_id).await?
+    {
+        let text = lng("AdminCommandContentDuplicate", ltag)
+            .args(&[("id", existing.id)]);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    }
+
+    let fingerprint = moderation::fingerprint_voice(&bot, &voice.file.id).await;
+
+    let channel = ChatId(BOT_CONFIG.content_check_channel_id);
+    let post = bot
+        .send_voice(channel, InputFile::file_id(voice.file.id.clone()))
+        .caption(format!("ID: {}", id))
+        .await?;
+    let url = format!("{}/{}", BOT_CONFIG.content_check_channel_name, post.id);
+
+    let done = DB
+        .other
+        .replace_voice_file(id, &voice.file.id.0, unique_id, fingerprint, &url)
+        .await?;
+    if !done {
+        let _ = bot.delete_message(channel, post.id).await;
+    }
+
+    let key = if done {
+        "AdminCommandContentDone"
+    } else {
+        "AdminCommandContentNotFound"
+    };
+    bot.send_message(m.chat.id, lng(key, ltag)).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+async fn _content_replace_gif(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    id: i16,
+) -> MyResult<()> {
+    let Some(animation) = m.reply_to_message().and_then(|r| r.animation())
+    else {
+        let text = lng("AdminCommandContentReplyWithFile", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let unique_id = &animation.file.unique_id.0;
+    if let Some(existing) =
+        DB.other.get_gif_by_file_unique_id(unique_id).await?
+    {
+        let text = lng("AdminCommandContentDuplicate", ltag)
+            .args(&[("id", existing.id)]);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    }
+
+    let done =
+        DB.other.replace_gif_file(id, &animation.file.id.0, unique_id).await?;
+
+    if done {
+        let file = InputFile::file_id(animation.file.id.clone());
+        bot.send_animation(ChatId(BOT_CONFIG.gif_content_channel_id), file)
+            .caption(format!("ID: {}", id))
+            .await?;
+    }
+
+    let key = if done {
+        "AdminCommandContentDone"
+    } else {
+        "AdminCommandContentNotFound"
+    };
+    bot.send_message(m.chat.id, lng(key, ltag)).maybe_thread_id(m).await?;
 
     Ok(())
 }
 
-async fn _inner_admin_command_repost(
+async fn admin_command_repost(
     bot: MyBot,
     m: &Message,
-    _ltag: LocaleTag,
+    ltag: LocaleTag,
+    user: &User,
     arg: &str,
 ) -> MyResult<()> {
-    let Some(reply) = m.reply_to_message() else {
+    let audience = match Audience::parse(arg) {
+        Ok(audience) => audience,
+        Err(word) => {
+            let text =
+                lng("AdminCommandRepostUsage", ltag).args(&[("flag", word)]);
+            bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+            return Ok(());
+        },
+    };
+
+    let reply = m.reply_to_message();
+    if reply.is_none() && !audience.dry_run {
         bot.send_message(m.chat.id, "Where is no reply.")
             .maybe_thread_id(m)
             .await?;
         return Ok(());
+    }
+
+    let chats =
+        if audience.chats { DB.other.get_chats().await? } else { Vec::new() };
+    let pigs = if audience.chats && audience.min_pigs.is_some() {
+        DB.chat_pig.count_pigs_per_group().await?
+    } else {
+        Default::default()
     };
+    let users =
+        if audience.users { DB.other.get_users().await? } else { Vec::new() };
 
-    let mut flags = AHashSet::new();
-    for word in arg.split_whitespace() {
-        match word {
-            "+dm" => flags.insert(RepostTarget::DM),
-            "+chats" => flags.insert(RepostTarget::Chats),
-            _ => {
-                let error_text = format!("Unknown flag: {}", word);
-                log::error!("{}", error_text);
-                bot.send_message(m.chat.id, error_text)
-                    .maybe_thread_id(m)
-                    .await?;
-                return Ok(());
-            },
-        };
+    let (chats, users) = audience.select(&chats, &pigs, &users, get_datetime());
+
+    if audience.dry_run {
+        return _repost_dry_run(bot, m, ltag, &chats, &users).await;
     }
+    let Some(reply) = reply else { return Ok(()) };
 
-    let mut post_stats = PostStats {
-        chat_sended_count: 0,
-        chat_sended_error_count: 0,
-        user_sended_count: 0,
-        user_sended_error_count: 0,
+    let targets: Vec<_> = chats
+        .iter()
+        .map(|c| (c.chat_id, BROADCAST_TARGET_CHAT))
+        .chain(users.iter().map(|u| (u.user_id, BROADCAST_TARGET_USER)))
+        .collect();
+
+    let new_broadcast = NewBroadcast {
+        from_chat_id: reply.chat.id.0,
+        message_id: reply.id.0,
+        created_by: user.id,
+        report_chat_id: m.chat.id.0,
+        created_at: get_datetime(),
     };
+    let job = DB.other.add_broadcast(new_broadcast, &targets).await?;
+    broadcast::wake_worker();
 
-    if flags.contains(&RepostTarget::Chats) {
-        let chats = DB.other.get_chats().await?;
+    let text = lng("AdminCommandBroadcastCreated", ltag).args(&[
+        ("id", job.id as usize),
+        ("chats", chats.len()),
+        ("users", users.len()),
+    ]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
 
-        for chat in chats {
-            _repost_to_chat(&bot, chat, reply, &mut post_stats).await;
-        }
-    }
+    Ok(())
+}
 
-    let text = format!(
-        "Sending chats: {}\nErrors: {}",
-        post_stats.chat_sended_count, post_stats.chat_sended_error_count
-    );
-    let _ = bot.send_message(UserId(BOT_CONFIG.creator_id), text).await;
+async fn _repost_dry_run(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    chats: &[&Groups],
+    users: &[&User],
+) -> MyResult<()> {
+    let chat_lines = chats.iter().take(BROADCAST_SAMPLE_SIZE).map(|c| {
+        format!("💬 {} [<code>{}</code>]", escape(&c.title), c.chat_id)
+    });
+    let user_lines = users.iter().take(BROADCAST_SAMPLE_SIZE).map(|u| {
+        format!("👤 {} [<code>{}</code>]", escape(&u.first_name), u.user_id)
+    });
+    let sample = chat_lines.chain(user_lines).collect::<Vec<_>>().join("\n");
 
-    if flags.contains(&RepostTarget::DM) {
-        let users = DB.other.get_users().await?;
+    let text = lng("AdminCommandRepostDryRun", ltag).args(&[
+        ("chats", chats.len().to_string()),
+        ("users", users.len().to_string()),
+        ("sample", sample),
+    ]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
 
-        for user in users {
-            _repost_to_user(&bot, user, reply, &mut post_stats).await;
-        }
+    Ok(())
+}
+
+/// `/broadcast [id | pause id | resume id | cancel id]`: the jobs `/repost`
+/// queued, run by [`broadcast::run_broadcast_worker`].
+async fn admin_command_broadcast(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    arg: &str,
+) -> MyResult<()> {
+    let words = arg.split_whitespace().collect::<Vec<_>>();
+
+    let (action, id) = match words[..] {
+        [] => return _broadcast_list(bot, m, ltag).await,
+        [id] => (None, id),
+        [action, id] => (Some(action), id),
+        _ => (None, ""),
+    };
+
+    let Ok(id) = id.parse::<i32>() else {
+        let text = lng("AdminCommandBroadcastUsage", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let (from, to, finished): (&[i16], i16, _) = match action {
+        None => return _broadcast_progress(bot, m, ltag, id).await,
+        Some("pause") => (&[BROADCAST_RUNNING], BROADCAST_PAUSED, None),
+        Some("resume") => (&[BROADCAST_PAUSED], BROADCAST_RUNNING, None),
+        Some("cancel") => (
+            &[BROADCAST_RUNNING, BROADCAST_PAUSED],
+            BROADCAST_CANCELLED,
+            Some(get_datetime()),
+        ),
+        Some(_) => {
+            let text = lng("AdminCommandBroadcastUsage", ltag);
+            bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+            return Ok(());
+        },
+    };
+
+    if !DB.other.change_broadcast_status(id, from, to, finished).await? {
+        let text = lng("AdminCommandBroadcastUnchanged", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
     }
 
-    let text = format!(
-        "Sended users: {}\nErrors: {}",
-        post_stats.user_sended_count, post_stats.user_sended_error_count
-    );
-    let _ = bot.send_message(UserId(BOT_CONFIG.creator_id), text).await;
+    if to == BROADCAST_RUNNING {
+        broadcast::wake_worker();
+    }
+
+    let text = lng("AdminCommandBroadcastUpdated", ltag).args(&[
+        ("id", id.to_string()),
+        ("status", _broadcast_status(to, ltag)),
+    ]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
 
     Ok(())
 }
 
-async fn _repost_to_chat(
-    bot: &MyBot,
-    chat: Groups,
-    reply: &Message,
-    post_stats: &mut PostStats,
-) {
-    if !chat.active {
-        return;
+async fn _broadcast_list(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+) -> MyResult<()> {
+    let jobs = DB.other.get_broadcasts(BROADCAST_LIST_LIMIT).await?;
+
+    if jobs.is_empty() {
+        let text = lng("AdminCommandBroadcastEmpty", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
     }
-    let res = bot
-        .forward_message(ChatId(chat.chat_id), reply.chat.id, reply.id)
-        .await;
 
-    if let Err(err) = res {
-        log::error!("Failed to repost to chat [{}] {}", chat.chat_id, err);
-        if let RequestError::RetryAfter(sec) = err {
-            log::info!(
-                "Retrying to repost to chat [{}] in {} seconds...",
-                chat.id,
-                sec.seconds()
-            );
-            tokio::time::sleep(sec.duration()).await;
-            return Box::pin(_repost_to_chat(bot, chat, reply, post_stats))
-                .await;
-        } else if matches!(
-            err,
-            RequestError::Api(
-                ApiError::BotKicked
-                    | ApiError::BotKickedFromSupergroup
-                    | ApiError::BotKickedFromChannel
-                    | ApiError::ChatNotFound,
-            ) | RequestError::MigrateToChatId(_)
-        ) {
-            let chat_info = UpdateGroups { active: false, ..chat.to_update() };
-            let chat_id = chat_info.chat_id;
-            let _ = DB.other.update_chat(chat_id, chat_info).await;
-            log::info!("Chat already deactivated: {}", chat_id);
-            post_stats.chat_sended_error_count += 1;
-        } else {
-            post_stats.chat_sended_error_count += 1;
-        }
+    let mut lines = Vec::with_capacity(jobs.len());
+    for job in jobs {
+        let counts = DB.other.get_broadcast_progress(job.id).await?;
+        let progress = BroadcastProgress::from_counts(&counts);
+        let done = progress.total() - progress.pending;
+
+        lines.push(format!(
+            "#{} {} — {}/{} · {}",
+            job.id,
+            _broadcast_status(job.status, ltag),
+            done,
+            progress.total(),
+            job.created_at.format("%d.%m.%Y %H:%M"),
+        ));
+    }
+
+    let text = lng("AdminCommandBroadcastList", ltag)
+        .args(&[("lines", lines.join("\n"))]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+async fn _broadcast_progress(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    id: i32,
+) -> MyResult<()> {
+    let Some(job) = DB.other.get_broadcast(id).await? else {
+        let text = lng("AdminCommandBroadcastNotFound", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let counts = DB.other.get_broadcast_progress(job.id).await?;
+    let progress = BroadcastProgress::from_counts(&counts);
+
+    let text = lng("AdminCommandBroadcastProgress", ltag).args(&[
+        ("id", job.id.to_string()),
+        ("status", _broadcast_status(job.status, ltag)),
+        ("created", job.created_at.format("%d.%m.%Y %H:%M").to_string()),
+        ("chats_sent", progress.chats_sent.to_string()),
+        ("chats_failed", progress.chats_failed.to_string()),
+        ("users_sent", progress.users_sent.to_string()),
+        ("users_failed", progress.users_failed.to_string()),
+        ("pending", progress.pending.to_string()),
+    ]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+fn _broadcast_status(status: i16, ltag: LocaleTag) -> String {
+    lng(&format!("BroadcastStatus_{}", status), ltag)
+}
+
+/// `/stats`: bot-wide totals plus the last day and week. Duels only count
+/// from when `duel_log` was introduced.
+async fn admin_command_stats(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+) -> MyResult<()> {
+    let now = get_datetime();
+    let day_ago = now - chrono::Duration::days(1);
+    let week_ago = now - chrono::Duration::days(7);
+
+    let (users, chats, chat_pigs, hand_pigs, pending) = tokio::try_join!(
+        DB.other.count_users(),
+        DB.other.count_active_chats(),
+        DB.chat_pig.count_chat_pigs(),
+        DB.hand_pig.count_hand_pigs(),
+        DB.other.count_pending_submissions(),
+    )?;
+    let (users, started, banned) = users;
+
+    let (feeds_day, feeds_week, duels_day, duels_week, top) = tokio::try_join!(
+        DB.chat_pig.count_feeds_since(day_ago),
+        DB.chat_pig.count_feeds_since(week_ago),
+        DB.hand_pig.count_duels_since(day_ago),
+        DB.hand_pig.count_duels_since(week_ago),
+        DB.chat_pig.get_top_growing_chats(week_ago, STATS_TOP_CHATS),
+    )?;
+
+    let top_lines = if top.is_empty() {
+        "-".to_owned()
     } else {
-        post_stats.chat_sended_count += 1;
-        log::info!("Reposted to chat [{}]", chat.chat_id);
+        top.iter()
+            .enumerate()
+            .map(|(i, (title, gain))| {
+                format!("{}. {} — {:+} kg", i + 1, escape(title), gain)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let text = lng("AdminCommandStats", ltag).args(&[
+        ("users", users.to_string()),
+        ("started", started.to_string()),
+        ("banned", banned.to_string()),
+        ("chats", chats.to_string()),
+        ("chat_pigs", chat_pigs.to_string()),
+        ("hand_pigs", hand_pigs.to_string()),
+        ("feeds_day", feeds_day.to_string()),
+        ("feeds_week", feeds_week.to_string()),
+        ("duels_day", duels_day.to_string()),
+        ("duels_week", duels_week.to_string()),
+        ("voices", pending.0.to_string()),
+        ("gifs", pending.1.to_string()),
+        ("top", top_lines),
+    ]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    if top.is_empty() {
+        return Ok(());
     }
-    tokio::time::sleep(Duration::from_millis(CHAT_SENDING_THROTTLE_TIME_MS))
-        .await;
+
+    if let Some(chart) = charts::generate_growth_chart(top, ltag).await {
+        bot.send_photo(m.chat.id, InputFile::memory(chart))
+            .maybe_thread_id(m)
+            .await?;
+    }
+
+    Ok(())
 }
 
-async fn _repost_to_user(
-    bot: &MyBot,
-    user: User,
-    reply: &Message,
-    post_stats: &mut PostStats,
-) {
-    if !user.started || user.banned {
-        return;
+/// `/ban <user> [reason]`. Staff can't be banned; revoke their role first.
+async fn admin_command_ban(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    moderator: &User,
+    arg: &str,
+) -> MyResult<()> {
+    let Some((target, note)) = _parse_target_arg(arg) else {
+        let text = lng("AdminCommandBanUsage", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let Some(user) = target.resolve().await? else {
+        let text = lng("AdminCommandPromoteNotFound", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let user_id = UserId(user.user_id as u64);
+
+    if staff::role_of(BOT_CONFIG.creator_id, user_id).await?.is_some() {
+        let text = lng("AdminCommandBanStaff", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
     }
-    let res = bot
-        .forward_message(UserId(user.user_id as u64), reply.chat.id, reply.id)
-        .await;
 
-    if let Err(err) = res {
-        log::error!("Failed to repost to user [{}] {}", user.user_id, err);
-        if let RequestError::RetryAfter(sec) = err {
-            log::info!(
-                "Retrying to repost to user [{}] in {} seconds...",
-                user.user_id,
-                sec.seconds()
-            );
-            tokio::time::sleep(sec.duration()).await;
-            return Box::pin(_repost_to_user(bot, user, reply, post_stats))
-                .await;
-        } else if matches!(
-            err,
-            RequestError::Api(
-                ApiError::UserDeactivated
-                    | ApiError::BotBlocked
-                    | ApiError::UserNotFound
-                    | ApiError::ChatNotFound
-            )
-        ) {
-            let user_status = UserStatus {
-                banned: true,
-                started: user.started,
-                supported: user.supported,
-                subscribed: user.subscribed,
-            };
-
-            let _ =
-                DB.other.change_user_status(user.user_id, user_status).await;
-            log::info!("User already banned: {}", user.user_id);
-            post_stats.user_sended_error_count += 1;
-        } else {
-            post_stats.user_sended_error_count += 1;
+    let key = if DB.other.ban_user(user.id, get_datetime()).await? {
+        user_moderation::set_banned(user_id, true);
+        _log_moderation(moderator, &user, ModerationAction::Ban, note).await?;
+        "AdminCommandBanDone"
+    } else {
+        "AdminCommandBanAlready"
+    };
+
+    let text = lng(key, ltag).args(&[("name", escape(&user.first_name))]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+/// `/unban <user> [note]`.
+async fn admin_command_unban(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    moderator: &User,
+    arg: &str,
+) -> MyResult<()> {
+    let Some((target, note)) = _parse_target_arg(arg) else {
+        let text = lng("AdminCommandUnbanUsage", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let Some(user) = target.resolve().await? else {
+        let text = lng("AdminCommandPromoteNotFound", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let key = if DB.other.unban_user(user.id).await? {
+        user_moderation::set_banned(UserId(user.user_id as u64), false);
+        _log_moderation(moderator, &user, ModerationAction::Unban, note)
+            .await?;
+        "AdminCommandUnbanDone"
+    } else {
+        "AdminCommandUnbanNotBanned"
+    };
+
+    let text = lng(key, ltag).args(&[("name", escape(&user.first_name))]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+/// `/rename <user> <chat_id|hand> <name>`. The name goes through the same
+/// limits as `/name` and the inline rename.
+async fn admin_command_rename(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    moderator: &User,
+    arg: &str,
+) -> MyResult<()> {
+    let parsed = _parse_target_arg(arg).and_then(|(target, rest)| {
+        let (scope, name) = rest.split_once(char::is_whitespace)?;
+        Some((target, scope, name.trim()))
+    });
+
+    let Some((target, scope, name)) = parsed else {
+        let text = lng("AdminCommandRenameUsage", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let Some(user) = target.resolve().await? else {
+        let text = lng("AdminCommandPromoteNotFound", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let renamed = if scope == "hand" {
+        _rename_hand_pig(&user, name).await?
+    } else if let Ok(chat_id) = scope.parse::<i64>() {
+        let name = escape(name);
+        if name.len() > 64 {
+            let text = lng("GameNameTagLetterLimit", ltag);
+            bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+            return Ok(());
         }
+        _rename_chat_pig(&user, chat_id, name).await?
     } else {
-        post_stats.user_sended_count += 1;
-        log::info!("Reposted to user [{}]", user.user_id);
-    }
-    tokio::time::sleep(Duration::from_millis(USER_SENDING_THROTTLE_TIME_MS))
-        .await;
+        let text = lng("AdminCommandRenameUsage", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let Some((action, note)) = renamed else {
+        let text = lng("AdminCommandPigNotFound", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    _log_moderation(moderator, &user, action, &note).await?;
+
+    let text =
+        lng("AdminCommandRenameDone", ltag).args(&[("change", escape(&note))]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+/// The log action and an `old → new` note, or `None` without a pig.
+async fn _rename_hand_pig(
+    user: &User,
+    name: &str,
+) -> MyResult<Option<(ModerationAction, String)>> {
+    let Some((pig, _)) = DB.hand_pig.get_hrundel(user.user_id).await? else {
+        return Ok(None);
+    };
+
+    let (name, _) = helpers::truncate(name, INLINE_NAME_SET_LIMIT);
+    DB.hand_pig.update_hrundel_name(user.user_id, name).await?;
+
+    let note = format!("{} → {}", pig.name, name);
+    Ok(Some((ModerationAction::RenameHandPig, note)))
+}
+
+/// See [`_rename_hand_pig`]; `name` is already escaped, as `/name` stores it.
+async fn _rename_chat_pig(
+    user: &User,
+    chat_id: i64,
+    name: String,
+) -> MyResult<Option<(ModerationAction, String)>> {
+    let Some(pig) = DB.chat_pig.get_chat_pig(user.user_id, chat_id).await?
+    else {
+        return Ok(None);
+    };
+
+    let note = format!("{}: {} → {}", chat_id, pig.name, name);
+    DB.chat_pig.set_chat_pig_name(user.user_id, chat_id, name).await?;
+
+    Ok(Some((ModerationAction::RenameChatPig, note)))
+}
+
+/// `/mass <user> <chat_id> <±kg> <note>`. Chat pigs only: a hand pig's
+/// weight is recomputed every day, so an adjustment wouldn't last.
+async fn admin_command_mass(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    moderator: &User,
+    arg: &str,
+) -> MyResult<()> {
+    let parsed = _parse_target_arg(arg).and_then(|(target, rest)| {
+        let mut words = rest.splitn(3, char::is_whitespace);
+        let chat_id = words.next()?.parse::<i64>().ok()?;
+        let delta = words.next()?.parse::<i32>().ok().filter(|d| *d != 0)?;
+        let note = words.next().map(str::trim).filter(|n| !n.is_empty())?;
+        Some((target, chat_id, delta, note))
+    });
+
+    let Some((target, chat_id, delta, note)) = parsed else {
+        let text = lng("AdminCommandMassUsage", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let Some(user) = target.resolve().await? else {
+        let text = lng("AdminCommandPromoteNotFound", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let Some(pig) = DB.chat_pig.get_chat_pig(user.user_id, chat_id).await?
+    else {
+        let text = lng("AdminCommandPigNotFound", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let mass = DB.chat_pig.adjust_chat_pig_mass(pig.id, delta).await?;
+
+    let change = format!("{}: {} → {} kg", chat_id, pig.mass, mass);
+    let audit = format!("{}. {}", change, note);
+    _log_moderation(moderator, &user, ModerationAction::AdjustMass, &audit)
+        .await?;
+
+    let text = lng("AdminCommandMassDone", ltag).args(&[("change", change)]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+/// `/modlog <user>`: what staff did to this user, newest first.
+async fn admin_command_modlog(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+    arg: &str,
+) -> MyResult<()> {
+    let Some((target, "")) = _parse_target_arg(arg) else {
+        let text = lng("AdminCommandModlogUsage", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let Some(user) = target.resolve().await? else {
+        let text = lng("AdminCommandPromoteNotFound", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    };
+
+    let entries =
+        DB.other.get_moderation_log(user.id, MODERATION_LOG_LIMIT).await?;
+
+    let lines = if entries.is_empty() {
+        "-".to_owned()
+    } else {
+        entries
+            .iter()
+            .map(|e| {
+                format!(
+                    "{} · {} · #{} · {}",
+                    e.created_at.format("%d.%m.%Y %H:%M"),
+                    lng(&format!("ModerationAction_{}", e.action), ltag),
+                    e.staff_uid,
+                    escape(&e.note),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let status = if user_moderation::is_banned(UserId(user.user_id as u64)) {
+        lng("AdminCommandModlogBanned", ltag)
+    } else {
+        String::new()
+    };
+
+    let text = lng("AdminCommandModlog", ltag).args(&[
+        ("name", escape(&user.first_name)),
+        ("status", status),
+        ("lines", lines),
+    ]);
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+async fn admin_command_reload_locales(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+) -> MyResult<()> {
+    // A tag is an index and the languages cannot change, so `ltag` still
+    // points at the same language after the swap
+    let text = match lang::reload() {
+        Ok(()) => lng("AdminCommandReloadLocalesDone", ltag)
+            .args(&[("langs", lang::get_langs().join(" "))]),
+        Err(err) => {
+            log::error!("{err}");
+
+            let problems = err
+                .problems
+                .iter()
+                .take(RELOAD_PROBLEM_LIMIT)
+                .map(|problem| format!("• {}", escape(problem)))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            lng("AdminCommandReloadLocalesFailed", ltag)
+                .args(&[("problems", problems)])
+        },
+    };
+
+    bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+    Ok(())
+}
+
+/// `<user> [rest]`, the target as [`UserTarget`] names it.
+fn _parse_target_arg(arg: &str) -> Option<(UserTarget, &str)> {
+    let arg = arg.trim();
+    let (target, rest) =
+        arg.split_once(char::is_whitespace).unwrap_or((arg, ""));
+
+    Some((UserTarget::parse(target)?, rest.trim()))
+}
+
+async fn _log_moderation(
+    moderator: &User,
+    target: &User,
+    action: ModerationAction,
+    note: &str,
+) -> MyResult<()> {
+    let entry = NewModerationLog {
+        staff_uid: moderator.id,
+        target_uid: target.id,
+        action: action as i16,
+        note: helpers::truncate_bytes(note, MODERATION_NOTE_LIMIT),
+        created_at: get_datetime(),
+    };
+
+    DB.other.add_moderation_log(entry).await
 }
@@ -1,11 +1,11 @@
 use chrono::NaiveDate;
 use futures::{FutureExt, future::BoxFuture};
-use std::{str::FromStr, sync::Arc};
+use num_traits::FromPrimitive;
+use std::{str::FromStr, sync::Arc, time::Instant};
 use teloxide::{
     prelude::*,
     requests::Requester,
     types::{CallbackQuery, InputFile, LinkPreviewOptions, UserId},
-    utils::html::user_mention,
 };
 use tokio::{
     sync::Mutex,
@@ -26,10 +26,16 @@ use crate::{
         models::{InlineUser, UpdateInlineUser, User, UserStatus},
         shortcuts,
     },
-    enums::{CbActions, DuelResult, Top10Variant},
+    enums::{CbActions, DuelResult, RejectReason, StaffRole, Top10Variant},
+    handlers::settings,
     keyboards,
     lang::{InnerLang, LocaleTag, get_tag, lng, tag, tag_one_or},
-    services::{duel, reset_vote},
+    metrics::{self, KgSource},
+    services::{
+        duel, name_filter,
+        notifications::{self, DuelOutcome, Notice},
+        reset_vote, staff,
+    },
     traits::{MaybeMessageSetter, SimpleDisableWebPagePreview},
     types::{MyBot, MyError, MyResult, ParsedCallbackData},
     utils::{
@@ -47,7 +53,7 @@ pub async fn filter_callback_commands(
     q: CallbackQuery,
     game_state: Arc<GameState>,
 ) -> MyResult<()> {
-    crate::metrics::CALLBACK_COUNTER.inc();
+    let started = Instant::now();
 
     let (collected, user) = tokio::join!(
         _try_join_groups_with_inline(&q),
@@ -69,6 +75,10 @@ pub async fn filter_callback_commands(
     let decoded_data =
         q.data.as_deref().and_then(helpers::decode_callback_data);
 
+    let action = decoded_data
+        .and_then(|d| CbActions::from_str(d.0).ok())
+        .map_or("unknown", <&'static str>::from);
+
     let function = match decoded_data {
         Some(payload) => _inner_filter(bot, &q, ltag, payload, game_state.clone()),
         None => callback_empty(bot, &q, ltag).boxed(),
@@ -76,6 +86,8 @@ pub async fn filter_callback_commands(
 
     let response = function.await;
 
+    metrics::CALLBACKS.observe(action, response.is_ok(), started);
+
     if let Err(err) = response {
         _handle_error(temp_bot, q, ltag, err, game_state).await?;
     } else {
@@ -121,6 +133,19 @@ fn _inner_filter<'a>(
             callback_gif_decision(bot, q, ltag, d).boxed()
         },
         CbActions::ResetVote => callback_reset_vote(bot, q, ltag, d, game_state).boxed(),
+        CbActions::ReviewBack => callback_review_back(bot, q, ltag, d).boxed(),
+        CbActions::Settings => {
+            settings::callback_settings(bot, q, ltag, d).boxed()
+        },
+        CbActions::SettingsLang => {
+            settings::callback_settings_lang(bot, q, ltag, d).boxed()
+        },
+        CbActions::SettingsFlag => {
+            settings::callback_settings_flag(bot, q, ltag, d).boxed()
+        },
+        CbActions::SettingsToggle => {
+            settings::callback_settings_toggle(bot, q, ltag, d).boxed()
+        },
     }
 }
 
@@ -216,6 +241,13 @@ async fn callback_give_hand_pig_name(
 
     let new_name = data.2;
 
+    // Callback data can be crafted by hand, so the button isn't proof.
+    if !name_filter::is_allowed(new_name, false) {
+        let text = lng("PigNameRejected", ltag);
+        bot.answer_callback_query(q.id.clone()).text(text).await?;
+        return Ok(());
+    }
+
     DB.hand_pig.update_hrundel_name(q.from.id.0 as i64, new_name).await?;
 
     let text = lng("HandPigNameChangedResponse", ltag);
@@ -249,15 +281,16 @@ async fn callback_find_day_pig(
     let decoded_chat_id = decode_inline_message_id(im_id)
         .map(|mut d| { d.normalize(); d.chat_id });
 
-    let (ig_id, group_id) = {
+    let (ig_id, group_id, chat_title) = {
         let chat_info = if let Some(chat_id) = decoded_chat_id {
             DB.other.get_chat(chat_id).await?
         } else {
             None
         };
+        let chat_title = chat_info.as_ref().map(|c| c.title.clone());
 
         match chat_info.and_then(|c| c.ig_id.map(|ig| (ig, c.id))) {
-            Some((ig, gid)) => (ig, gid),
+            Some((ig, gid)) => (ig, gid, chat_title),
             None => {
                 // Fall back: look up inline group via chat_instance (hand pigs only)
                 let Some(ig) =
@@ -268,7 +301,7 @@ async fn callback_find_day_pig(
                         .await?;
                     return Ok(());
                 };
-                (ig.id, 0)
+                (ig.id, 0, chat_title)
             },
         }
     };
@@ -310,10 +343,7 @@ async fn callback_find_day_pig(
         Some(services::day_pig::DayPigSelectResult::Selected(selected)) => {
             bot.answer_callback_query(q.id.clone()).await?;
 
-            let mention = user_mention(
-                UserId(selected.user.user_id as u64),
-                &selected.user.first_name,
-            );
+            let mention = services::day_pig::mention(&selected.user).await?;
 
             bot.edit_message_text_inline(im_id, lng("DayPigLabel1", ltag))
                 .await?;
@@ -330,6 +360,9 @@ async fn callback_find_day_pig(
             )
             .await?;
 
+            let notice = Notice::DayPig { chat: chat_title };
+            notifications::push(selected.user.id, ltag, notice);
+
             if let (Some(gid), Some(chat_id)) =
                 (selected.game_id, decoded_chat_id)
             {
@@ -577,8 +610,6 @@ async fn callback_start_duel(
         data.1,
     );
 
-    crate::metrics::DUEL_NUMBERS.inc();
-
     let text = lng("InlineDuelGoingMessage", ltag).args(&[
         ("first_name", &first.0.name),
         ("secnd_name", &second.0.name),
@@ -603,10 +634,9 @@ async fn callback_start_duel(
     ]);
 
     let lng_key = &format!("InlineDuelMessage_{}", status.into_str());
-    let text = lng(lng_key, ltag).args(&[
+    let text = lng(lng_key, ltag).plural(ltag, "diff", damage.into()).args(&[
         ("winner_name", &winner.0.name),
         ("looser_name", &looser.0.name),
-        ("diff", &damage.to_string()),
         ("stats", &stats),
     ]);
 
@@ -617,6 +647,18 @@ async fn callback_start_duel(
 
     DB.hand_pig.update_hrundel_duel(winner_id, damage, true).await?;
     DB.hand_pig.update_hrundel_duel(looser_id, damage, looser_is_win).await?;
+    DB.hand_pig
+        .add_duel_log(winner.1.id, looser.1.id, damage, get_datetime())
+        .await?;
+
+    // The loser never drops below 1 kg, so it may lose less than `damage`.
+    let looser_change = if looser_is_win {
+        damage
+    } else {
+        (looser.0.weight - damage).max(1) - looser.0.weight
+    };
+    metrics::record_kg(KgSource::Duel, damage);
+    metrics::record_kg(KgSource::Duel, looser_change);
 
     let mut request = bot
         .edit_message_text_inline(im_id, text)
@@ -633,6 +675,21 @@ async fn callback_start_duel(
     drop(user_locked_threads);
     game_state.duel_list.write().await.retain(|&x| x != thread_identifier);
 
+    // The one who pressed saw the result; the one who posted the duel
+    // might not be watching.
+    let challenger_won = winner.1.user_id == data.1.0 as i64;
+    let (challenger, opponent) =
+        if challenger_won { (&winner, &looser) } else { (&looser, &winner) };
+    let outcome = match status {
+        DuelResult::Draw => DuelOutcome::Draw,
+        _ if challenger_won => DuelOutcome::Won,
+        _ => DuelOutcome::Lost,
+    };
+    let diff = if challenger_won { damage } else { looser_change.abs() };
+    let notice =
+        Notice::Duel { opponent: opponent.0.name.clone(), outcome, diff };
+    notifications::push(challenger.1.id, ltag, notice);
+
     log::info!(
         "Ended duel [{}] from user [{}] to [{}]",
         thread_identifier,
@@ -836,16 +893,37 @@ async fn callback_allow_voice(
     let user_id = data.1;
 
     log::info!("Allowed voice from [{}]", user_id);
-    if q.from.id.0 != BOT_CONFIG.creator_id {
+    if !staff::has_role(q.from.id, StaffRole::Moderator).await? {
         let text = lng("AccessDenied", ltag);
         bot.answer_callback_query(q.id.clone()).text(text).await?;
         return Ok(());
     }
-    let text = lng("VoiceAccepted", ltag);
-    bot.answer_callback_query(q.id.clone()).text(text).await?;
+
+    let Some(user) = DB.other.get_user(user_id.0 as i64).await? else {
+        return Ok(());
+    };
+
     let probably_url =
         format!("{}/{}", &BOT_CONFIG.content_check_channel_name, &m.id());
 
+    // Cards sent before the moderation queue carry no row id.
+    let number = match data.2.parse::<i16>() {
+        Ok(voice_id) => {
+            match DB.other.approve_voice(voice_id, probably_url).await? {
+                Some(voice) => voice.id,
+                None => return _cb_content_already_decided(bot, q, ltag).await,
+            }
+        },
+        Err(_) => {
+            DB.other.add_voice(user.id, probably_url).await?;
+            let voices = DB.other.get_voices_by_user(user.id).await?;
+            voices.last().map_or(0, |v| v.id)
+        },
+    };
+
+    let text = lng("VoiceAccepted", ltag);
+    bot.answer_callback_query(q.id.clone()).text(text).await?;
+
     let accepted = lng("Accepted", ltag);
     let edited_text = format!("{} {}", accepted, user_id);
 
@@ -866,18 +944,10 @@ async fn callback_allow_voice(
                 cur_date,
             )
             .await?;
+        metrics::record_kg(KgSource::ContentReward, INLINE_VOICE_REWARD_KG);
         ltag = tag_one_or(hrundel.1.lang.as_deref(), DEFAULT_LANG_TAG);
     }
 
-    let Some(user) = DB.other.get_user(user_id.0 as i64).await? else {
-        return Ok(());
-    };
-
-    DB.other.add_voice(user.id, probably_url).await?;
-
-    let voices = DB.other.get_voices_by_user(user.id).await?;
-    let number = voices.last().map_or(0, |v| v.id);
-
     let text = lng("VoiceAcceptedCongrats", ltag)
         .args(&[("number", number as i32), ("amount", INLINE_VOICE_REWARD_KG)]);
     bot.send_message(user_id, text).await?;
@@ -888,37 +958,167 @@ async fn callback_allow_voice(
 async fn callback_disallow_voice(
     bot: MyBot,
     q: &CallbackQuery,
-    mut ltag: LocaleTag,
+    ltag: LocaleTag,
     data: ParsedCallbackData<'_>,
 ) -> MyResult<()> {
     let Some(m) = &q.message else { return Ok(()) };
 
     let user_id = data.1;
 
-    log::info!("Disallowed voice from [{}]", user_id);
-
-    if q.from.id.0 != BOT_CONFIG.creator_id {
+    if !staff::has_role(q.from.id, StaffRole::Moderator).await? {
         let text = lng("AccessDenied", ltag);
         bot.answer_callback_query(q.id.clone()).text(text).await?;
         return Ok(());
     }
-    let text = lng("VoiceNotAccepted", ltag);
+
+    let (voice_id, reason) = _parse_reject_payload(data.2);
+
+    // ❌ only opens the reasons; cards from before the moderation queue have
+    // no row to attach one to and are rejected straight away.
+    if let Some(voice_id) = voice_id
+        && reason.is_none()
+    {
+        bot.answer_callback_query(q.id.clone()).await?;
+        bot.edit_message_reply_markup(m.chat().id, m.id())
+            .reply_markup(keyboards::keyboard_voice_reject(
+                ltag, user_id, voice_id,
+            ))
+            .await?;
+        return Ok(());
+    }
+
+    log::info!("Disallowed voice from [{}]", user_id);
+
+    if let Some(voice_id) = voice_id
+        && DB
+            .other
+            .reject_voice(voice_id, reason.map(|r| r as i16))
+            .await?
+            .is_none()
+    {
+        return _cb_content_already_decided(bot, q, ltag).await;
+    }
+
+    _cb_content_rejected(
+        bot,
+        q,
+        ltag,
+        user_id,
+        reason,
+        "VoiceNotAccepted",
+        "VoiceNotAcceptedMsg",
+    )
+    .await
+}
+
+/// `<id>` or `<id>:<reason>`, after any decision sign.
+fn _parse_reject_payload(payload: &str) -> (Option<i16>, Option<RejectReason>) {
+    let (id, reason) = payload.split_once(':').unwrap_or((payload, ""));
+
+    let id = id.parse::<i16>().ok();
+    let reason = reason.parse::<i16>().ok().and_then(RejectReason::from_i16);
+
+    (id, reason)
+}
+
+/// The card keeps the reason for the other moderators; the submitter gets it
+/// in their own language.
+async fn _cb_content_rejected(
+    bot: MyBot,
+    q: &CallbackQuery,
+    mut ltag: LocaleTag,
+    user_id: UserId,
+    reason: Option<RejectReason>,
+    answer_key: &str,
+    message_key: &str,
+) -> MyResult<()> {
+    let Some(m) = &q.message else { return Ok(()) };
+
+    let text = lng(answer_key, ltag);
     bot.answer_callback_query(q.id.clone()).text(text).await?;
 
     let not_accepted = lng("NotAccepted", ltag);
-    let edited_text = format!("{} {}", not_accepted, user_id);
+    let mut edited_text = format!("{} {}", not_accepted, user_id);
+    if let Some(reason) = reason {
+        edited_text.push_str(" · ");
+        edited_text.push_str(&_reject_reason_text(reason, ltag));
+    }
     bot.edit_message_caption(m.chat().id, m.id())
         .caption(edited_text)
         .reply_markup(keyboards::keyboard_empty())
         .await?;
 
-    let hrundel = DB.hand_pig.get_hrundel(user_id.0 as i64).await?;
-    if let Some(hrundel) = hrundel {
-        ltag = tag_one_or(hrundel.1.lang.as_deref(), DEFAULT_LANG_TAG);
-    }
+    // The submitter's language, hand pig or not; never the moderator's.
+    let user = DB.other.get_user(user_id.0 as i64).await?;
+    ltag = tag_one_or(user.and_then(|u| u.lang).as_deref(), DEFAULT_LANG_TAG);
 
-    let text = lng("VoiceNotAcceptedMsg", ltag);
+    let mut text = lng(message_key, ltag);
+    if let Some(reason) = reason {
+        let reason = _reject_reason_text(reason, ltag);
+        text.push_str("\n\n");
+        text.push_str(
+            &lng("RejectReasonMsg", ltag).args(&[("reason", reason)]),
+        );
+    }
     bot.send_message(user_id, text).await?;
+
+    Ok(())
+}
+
+fn _reject_reason_text(reason: RejectReason, ltag: LocaleTag) -> String {
+    lng(&format!("RejectReason_{}", reason as i16), ltag)
+}
+
+/// The ↩️ under the reasons: puts the ✅/❌ card back.
+async fn callback_review_back(
+    bot: MyBot,
+    q: &CallbackQuery,
+    ltag: LocaleTag,
+    data: ParsedCallbackData<'_>,
+) -> MyResult<()> {
+    let Some(m) = &q.message else { return Ok(()) };
+
+    if !staff::has_role(q.from.id, StaffRole::Moderator).await? {
+        let text = lng("AccessDenied", ltag);
+        bot.answer_callback_query(q.id.clone()).text(text).await?;
+        return Ok(());
+    }
+
+    let (kind, id) = data.2.split_at_checked(1).unwrap_or((data.2, ""));
+    let Ok(id) = id.parse::<i16>() else {
+        return callback_empty(bot, q, ltag).await;
+    };
+
+    let markup = match kind {
+        "v" => keyboards::keyboard_voice_check(data.1, id),
+        "g" => keyboards::keyboard_gif_check(data.1, id),
+        _ => return callback_empty(bot, q, ltag).await,
+    };
+
+    bot.answer_callback_query(q.id.clone()).await?;
+    bot.edit_message_reply_markup(m.chat().id, m.id())
+        .reply_markup(markup)
+        .await?;
+
+    Ok(())
+}
+
+/// A re-sent review card leaves the old one live too; whichever is pressed
+/// second just loses its buttons.
+async fn _cb_content_already_decided(
+    bot: MyBot,
+    q: &CallbackQuery,
+    ltag: LocaleTag,
+) -> MyResult<()> {
+    let text = lng("ContentAlreadyDecided", ltag);
+    bot.answer_callback_query(q.id.clone()).text(text).await?;
+
+    if let Some(m) = &q.message {
+        bot.edit_message_reply_markup(m.chat().id, m.id())
+            .reply_markup(keyboards::keyboard_empty())
+            .await?;
+    }
+
     Ok(())
 }
 
@@ -990,9 +1190,13 @@ async fn callback_gif_decision(
     ltag: LocaleTag,
     data: ParsedCallbackData<'_>,
 ) -> MyResult<()> {
-    match data.2 {
-        "+" => _cb_allow_gif(bot, q, ltag, data).await,
-        "-" => _cb_disallow_gif(bot, q, ltag, data).await,
+    // `+<id>` / `-<id>[:<reason>]`; cards sent before the moderation queue
+    // carry a bare sign.
+    let (decision, rest) = data.2.split_at_checked(1).unwrap_or((data.2, ""));
+
+    match decision {
+        "+" => _cb_allow_gif(bot, q, ltag, data, rest.parse().ok()).await,
+        "-" => _cb_disallow_gif(bot, q, ltag, data, rest).await,
         _ => callback_empty(bot, q, ltag).await,
     }
 }
@@ -1002,20 +1206,18 @@ async fn _cb_allow_gif(
     q: &CallbackQuery,
     mut ltag: LocaleTag,
     data: ParsedCallbackData<'_>,
+    gif_id: Option<i16>,
 ) -> MyResult<()> {
     let Some(m) = &q.message else { return Ok(()) };
     let user_id = data.1;
 
     log::info!("Allowed gif from [{}]", user_id);
-    if q.from.id.0 != BOT_CONFIG.creator_id {
+    if !staff::has_role(q.from.id, StaffRole::Moderator).await? {
         let text = lng("AccessDenied", ltag);
         bot.answer_callback_query(q.id.clone()).text(text).await?;
         return Ok(());
     }
 
-    let text = lng("GifAccepted", ltag);
-    bot.answer_callback_query(q.id.clone()).text(text).await?;
-
     let Some(accepted_animation) =
         m.regular_message().and_then(|v| v.animation())
     else {
@@ -1023,6 +1225,33 @@ async fn _cb_allow_gif(
         return Ok(());
     };
 
+    let Some(user) = DB.other.get_user(user_id.0 as i64).await? else {
+        crate::myerr!("Some not working...");
+        return Ok(());
+    };
+
+    let number = match gif_id {
+        Some(gif_id) => match DB.other.approve_gif(gif_id).await? {
+            Some(gif) => gif.id,
+            None => return _cb_content_already_decided(bot, q, ltag).await,
+        },
+        None => {
+            DB.other
+                .add_gif(
+                    user.id,
+                    accepted_animation.file.id.to_string(),
+                    accepted_animation.file.unique_id.to_string(),
+                )
+                .await?;
+
+            let gifs = DB.other.get_gifs_by_user(user.id).await?;
+            gifs.last().map_or(0, |v| v.id)
+        },
+    };
+
+    let text = lng("GifAccepted", ltag);
+    bot.answer_callback_query(q.id.clone()).text(text).await?;
+
     let accepted = lng("Accepted", ltag);
     let edited_text = format!("{} {}", accepted, user_id);
 
@@ -1043,25 +1272,10 @@ async fn _cb_allow_gif(
                 cur_date,
             )
             .await?;
+        metrics::record_kg(KgSource::ContentReward, INLINE_GIF_REWARD_KG);
         ltag = tag_one_or(hrundel.1.lang.as_deref(), DEFAULT_LANG_TAG);
     }
 
-    let Some(user) = DB.other.get_user(user_id.0 as i64).await? else {
-        crate::myerr!("Some not working...");
-        return Ok(());
-    };
-
-    DB.other
-        .add_gif(
-            user.id,
-            accepted_animation.file.id.to_string(),
-            accepted_animation.file.unique_id.to_string(),
-        )
-        .await?;
-
-    let gifs = DB.other.get_gifs_by_user(user.id).await?;
-    let number = gifs.last().map_or(0, |v| v.id);
-
     let file = InputFile::file_id(accepted_animation.file.id.clone());
     let res = bot
         .send_animation(ChatId(BOT_CONFIG.gif_content_channel_id), file)
@@ -1083,38 +1297,55 @@ async fn _cb_allow_gif(
 async fn _cb_disallow_gif(
     bot: MyBot,
     q: &CallbackQuery,
-    mut ltag: LocaleTag,
+    ltag: LocaleTag,
     data: ParsedCallbackData<'_>,
+    payload: &str,
 ) -> MyResult<()> {
     let Some(m) = &q.message else { return Ok(()) };
 
     let user_id = data.1;
 
-    log::info!("Disallowed gif from [{}]", user_id);
-
-    if q.from.id.0 != BOT_CONFIG.creator_id {
+    if !staff::has_role(q.from.id, StaffRole::Moderator).await? {
         let text = lng("AccessDenied", ltag);
         bot.answer_callback_query(q.id.clone()).text(text).await?;
         return Ok(());
     }
-    let text = lng("GifNotAccepted", ltag);
-    bot.answer_callback_query(q.id.clone()).text(text).await?;
 
-    let not_accepted = lng("NotAccepted", ltag);
-    let edited_text = format!("{} {}", not_accepted, user_id);
-    bot.edit_message_caption(m.chat().id, m.id())
-        .caption(edited_text)
-        .reply_markup(keyboards::keyboard_empty())
-        .await?;
+    let (gif_id, reason) = _parse_reject_payload(payload);
 
-    let hrundel = DB.hand_pig.get_hrundel(user_id.0 as i64).await?;
-    if let Some(hrundel) = hrundel {
-        ltag = tag_one_or(hrundel.1.lang.as_deref(), DEFAULT_LANG_TAG);
+    // See `callback_disallow_voice`.
+    if let Some(gif_id) = gif_id
+        && reason.is_none()
+    {
+        bot.answer_callback_query(q.id.clone()).await?;
+        bot.edit_message_reply_markup(m.chat().id, m.id())
+            .reply_markup(keyboards::keyboard_gif_reject(ltag, user_id, gif_id))
+            .await?;
+        return Ok(());
     }
 
-    let text = lng("GifNotAcceptedMsg", ltag);
-    bot.send_message(user_id, text).await?;
-    Ok(())
+    log::info!("Disallowed gif from [{}]", user_id);
+
+    if let Some(gif_id) = gif_id
+        && DB
+            .other
+            .reject_gif(gif_id, reason.map(|r| r as i16))
+            .await?
+            .is_none()
+    {
+        return _cb_content_already_decided(bot, q, ltag).await;
+    }
+
+    _cb_content_rejected(
+        bot,
+        q,
+        ltag,
+        user_id,
+        reason,
+        "GifNotAccepted",
+        "GifNotAcceptedMsg",
+    )
+    .await
 }
 
 async fn _get_biggest_chat_pig_mass(id_user: i64) -> MyResult<i32> {
@@ -1160,6 +1391,7 @@ async fn callback_gift<'a>(
     };
 
     DB.hand_pig.update_hrundel(hrundel_on_update).await?;
+    metrics::record_kg(KgSource::DailyGift, DAILY_GIFT_AMOUNT);
 
     let text = lng("GiftThanksReceive500", ltag)
         .args(&[("amount", DAILY_GIFT_AMOUNT)]);
@@ -1211,6 +1443,7 @@ async fn callback_check_subscribe<'a>(
     };
     DB.hand_pig.update_hrundel(hrundel_on_update).await?;
     DB.other.change_user_status(q.from.id.0 as i64, user_status).await?;
+    metrics::record_kg(KgSource::SubscriptionGift, SUBSCRIBE_GIFT);
 
     let text =
         lng("GiftThanksReceive100", ltag).args(&[("amount", SUBSCRIBE_GIFT)]);
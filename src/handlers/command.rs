@@ -2,6 +2,7 @@ use ahash::AHashSet;
 use chrono::NaiveDateTime;
 use futures::{FutureExt, StreamExt};
 use std::sync::Arc;
+use std::time::Instant;
 use strum::{EnumCount, VariantArray};
 use teloxide::RequestError;
 use teloxide::prelude::*;
@@ -15,6 +16,10 @@ use crate::config::consts::{CHAT_PIG_START_MASS, LOUDER_DEFAULT_RATIO};
 use crate::config::consts::{
     GameState, LOUDER_DEFAULT_VOICE_LIMIT, SUBSCRIBE_GIFT,
 };
+use crate::config::consts::{
+    INLINE_CONTENT_APPROVED, INLINE_CONTENT_HIDDEN, INLINE_CONTENT_PENDING,
+    MY_SUBMISSIONS_LIMIT,
+};
 use crate::config::consts::{
     LOUDER_PREMIUM_VOICE_LIMIT, TOP_LIMIT, TOP_LIMIT_WITH_CHARTS,
 };
@@ -23,10 +28,14 @@ use crate::db::DB;
 use crate::db::models::{GrowLogAdd, UserStatus};
 use crate::db::shortcuts;
 use crate::enums::MyCommands;
+use crate::handlers::settings;
 use crate::keyboards;
 use crate::lang::{InnerLang, LocaleTag, get_tag_opt, lng, tag_one_two_or};
+use crate::metrics::KgSource;
 use crate::services::achievements::{self, Ach};
 use crate::services::charts::{generate_charts, generate_my_chart};
+use crate::services::name_filter;
+use crate::services::notifications::{self, Notice};
 use crate::services::reset_vote;
 use crate::traits::{
     MaybeMessageSetter, MaybePhotoSetter, MaybeVoiceSetter,
@@ -37,7 +46,7 @@ use crate::utils::date::{
     get_datetime, get_datetime_from_message_date, get_timediff,
 };
 use crate::utils::formulas::calculate_chat_pig_grow;
-use crate::utils::helpers::{escape, get_file_from_stream, plural, truncate};
+use crate::utils::helpers::{escape, get_file_from_stream, truncate};
 use crate::utils::ogg::increase_sound;
 use crate::utils::text::generate_chat_top_text;
 
@@ -47,7 +56,7 @@ pub async fn filter_commands(
     cmd: MyCommands,
     game_state: Arc<GameState>,
 ) -> MyResult<()> {
-    crate::metrics::CMD_COUNTER.inc();
+    let started = Instant::now();
 
     // Neither lookup feeds the other, so they cost one round trip together
     // instead of two — and this runs before every single command.
@@ -97,10 +106,18 @@ pub async fn filter_commands(
         MyCommands::ResetPigs => {
             command_reset_pigs(bot, &m, ltag, game_state).boxed()
         },
+        MyCommands::MySubmissions => {
+            command_my_submissions(bot, &m, ltag).boxed()
+        },
+        MyCommands::Settings => {
+            settings::command_settings(bot, &m, ltag).boxed()
+        },
     };
 
     let response = function.await;
 
+    crate::metrics::COMMANDS.observe((&cmd).into(), response.is_ok(), started);
+
     let user_id = m.from.map_or(0, |u| u.id.0);
 
     if let Err(err) = response {
@@ -129,7 +146,6 @@ async fn command_start(
     ltag: LocaleTag,
 ) -> MyResult<()> {
     let Some(from) = &m.from else { return Ok(()) };
-    crate::metrics::CMD_START_COUNTER.inc();
 
     let text = lng("ChatGreetingFirst", ltag)
         .args(&[("channel", &BOT_CONFIG.channel_name)]);
@@ -155,6 +171,11 @@ async fn command_start(
                 subscribed: user.subscribed,
             };
             DB.other.change_user_status(from.id.0 as i64, user_status).await?;
+            if !user.started {
+                DB.other
+                    .set_user_started_at(from.id.0 as i64, get_datetime())
+                    .await?;
+            }
         } else {
             shortcuts::maybe_get_or_insert_user(from, true).await?;
         };
@@ -188,7 +209,6 @@ async fn command_help(
     m: &Message,
     ltag: LocaleTag,
 ) -> MyResult<()> {
-    crate::metrics::CMD_HELP_COUNTER.inc();
     let link = lng("HelpLink", ltag);
     let text = lng("HelpMessage", ltag).args(&[("link", link)]);
     bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
@@ -321,6 +341,7 @@ async fn command_grow(
     let current = pig.mass + offset;
 
     DB.chat_pig.set_chat_pig_mass_n_date(pig.id, current, cur_date).await?;
+    crate::metrics::record_kg(KgSource::Grow, offset);
 
     let grow_log_info = GrowLogAdd {
         game_id: pig.id,
@@ -354,12 +375,13 @@ async fn command_grow(
 
     let grow_status_key = format!("GamePigGrowMessage_{}", status.into_str());
 
-    let text = lng(&grow_status_key, ltag).args(&[
-        ("name", pig.name),
-        ("value", offset.abs().to_string()),
-        ("current", current.to_string()),
-        ("mention", mention),
-    ]);
+    let text = lng(&grow_status_key, ltag)
+        .plural(ltag, "value", offset.abs().into())
+        .args(&[
+            ("name", pig.name),
+            ("current", current.to_string()),
+            ("mention", mention),
+        ]);
 
     bot.send_message(m.chat.id, text)
         .link_preview_options(LinkPreviewOptions::disable(true))
@@ -388,29 +410,10 @@ async fn _game_already_feeded(
 ) -> MyResult<()> {
     let (hours, minutes, seconds) = get_timediff(cur_datetime);
 
-    let h_rule = plural(hours);
-    let m_rule = plural(minutes);
-    let s_rule = plural(seconds);
-
-    let h_lang = format!("hour_{}", h_rule);
-    let m_lang = format!("minute_{}", m_rule);
-    let s_lang = format!("second_{}", s_rule);
-
-    let hours_text = format!("{} {}", hours, lng(&h_lang, ltag));
-    let minut_text = format!("{} {}", minutes, lng(&m_lang, ltag));
-
-    let key = grow_cooldown_key(hours, minutes);
-
-    let next_feed = if hours != 0 {
-        lng(key, ltag)
-            .args(&[("to_hours", &hours_text), ("to_minutes", &minut_text)])
-    } else if minutes != 0 {
-        lng(key, ltag).args(&[("to_minutes", &minut_text)])
-    } else {
-        let secnd_text = format!("{} {}", seconds, lng(&s_lang, ltag));
-
-        lng(key, ltag).args(&[("to_seconds", &secnd_text)])
-    };
+    let next_feed = lng(grow_cooldown_key(hours, minutes), ltag)
+        .plural(ltag, "hours", hours)
+        .plural(ltag, "minutes", minutes)
+        .plural(ltag, "seconds", seconds);
 
     let text = lng("GameAlreadyFeeded", ltag)
         .args(&[("next_feed", &italic(&next_feed))]);
@@ -438,6 +441,7 @@ async fn command_name(
         return Ok(());
     };
 
+    let raw_payload = payload;
     let payload = escape(payload);
     if payload.is_empty() {
         let text = lng("GameNamePig", ltag).args(&[("name", &pig.name)]);
@@ -452,6 +456,14 @@ async fn command_name(
         return Ok(());
     }
 
+    let chat_info = DB.other.get_chat(m.chat.id.0).await?;
+    let strict = chat_info.is_some_and(|c| c.strict_names);
+    if !name_filter::is_allowed(raw_payload, strict) {
+        let text = lng("PigNameRejected", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    }
+
     let text = lng("GameNameNewPig", ltag).args(&[("new_name", &payload)]);
 
     DB.chat_pig
@@ -688,10 +700,7 @@ async fn command_day_pig(
             bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
         },
         Some(services::day_pig::DayPigSelectResult::Selected(selected)) => {
-            let mention = user_mention(
-                UserId(selected.user.user_id as u64),
-                &selected.user.first_name,
-            );
+            let mention = services::day_pig::mention(&selected.user).await?;
             let msg = bot
                 .send_message(m.chat.id, lng("DayPigLabel1", ltag))
                 .maybe_thread_id(m)
@@ -718,6 +727,13 @@ async fn command_day_pig(
             )
             .await?;
 
+            let chat = m.chat.title().map(str::to_owned);
+            notifications::push(
+                selected.user.id,
+                ltag,
+                Notice::DayPig { chat },
+            );
+
             if let Some(gid) = selected.game_id {
                 let _ = services::day_pig::notify_achievements(
                     &bot,
@@ -856,6 +872,74 @@ async fn command_id(bot: MyBot, m: &Message, ltag: LocaleTag) -> MyResult<()> {
     Ok(())
 }
 
+async fn command_my_submissions(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+) -> MyResult<()> {
+    let Some(from) = &m.from else { return Ok(()) };
+
+    if !m.chat.is_private() {
+        let text = lng("CommandOnlyForPrivate", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    }
+
+    let Some(user) = DB.other.get_user(from.id.0 as i64).await? else {
+        return Ok(());
+    };
+
+    let (voices, gifs) = tokio::try_join!(
+        DB.other.get_voices_by_user(user.id),
+        DB.other.get_gifs_by_user(user.id),
+    )?;
+
+    if voices.is_empty() && gifs.is_empty() {
+        let text = lng("MySubmissionsEmpty", ltag);
+        bot.send_message(m.chat.id, text).await?;
+        return Ok(());
+    }
+
+    let voice_lines = voices.iter().rev().take(MY_SUBMISSIONS_LIMIT).map(|v| {
+        let status = _submission_status(v.status, v.reject_reason, ltag);
+        format!("🎙 <b>#{}</b> · {}", v.id, status)
+    });
+    let gif_lines = gifs.iter().rev().take(MY_SUBMISSIONS_LIMIT).map(|g| {
+        let status = _submission_status(g.status, g.reject_reason, ltag);
+        format!("🐖 <b>#{}</b> · {}", g.id, status)
+    });
+    let lines = voice_lines.chain(gif_lines).collect::<Vec<_>>().join("\n");
+
+    let text = lng("MySubmissionsList", ltag).args(&[
+        ("voices", voices.len().to_string()),
+        ("gifs", gifs.len().to_string()),
+        ("lines", lines),
+    ]);
+    bot.send_message(m.chat.id, text).await?;
+
+    Ok(())
+}
+
+fn _submission_status(
+    status: i16,
+    reject_reason: Option<i16>,
+    ltag: LocaleTag,
+) -> String {
+    match status {
+        INLINE_CONTENT_PENDING => lng("SubmissionStatusPending", ltag),
+        INLINE_CONTENT_APPROVED => lng("SubmissionStatusApproved", ltag),
+        INLINE_CONTENT_HIDDEN => lng("SubmissionStatusHidden", ltag),
+        _ => {
+            let mut text = lng("SubmissionStatusRejected", ltag);
+            if let Some(reason) = reject_reason {
+                text.push_str(" · ");
+                text.push_str(&lng(&format!("RejectReason_{reason}"), ltag));
+            }
+            text
+        },
+    }
+}
+
 async fn command_louder(
     bot: MyBot,
     m: &Message,
@@ -1000,8 +1084,8 @@ async fn command_achievements(
         ("not_done_achievements", &not_done_list_text),
         ("chat_count", &chat_count),
         ("chat_all_count", &all_count),
-        ("global_count", &global_count),
         ("global_all_count", &all_count),
+        ("global_count", &global_count),
     ]);
 
     bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
@@ -1057,11 +1141,13 @@ pub async fn _handle_new_achievements(
             ("achievement_name", &achievement_name),
             ("chat_count", &chat_count),
             ("chat_all_count", &all_count),
-            ("global_count", &global_count),
             ("global_all_count", &all_count),
+            ("global_count", &global_count),
         ]);
 
         bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+
+        notifications::push(id_uid, ltag, Notice::Achievement(achievement));
     }
 
     Ok(())
@@ -1130,6 +1216,8 @@ async fn command_reset_pigs(
     // One `get_chat_member` per owner, a few at a time. Serially this was a
     // round trip per pig before the vote could even open — several seconds in
     // a large chat. The cap keeps the burst well inside Telegram's limits.
+    // The bot adaptor doesn't queue `get_chat_member`, so a `RetryAfter` is
+    // waited out here rather than counting a member as gone.
     const MEMBER_CHECK_CONCURRENCY: usize = 8;
 
     let bot_ref = &bot;
@@ -1,8 +1,9 @@
 use crate::db::DB;
 use crate::db::models::UpdateGroups;
-use crate::enums::EpycCommands;
+use crate::enums::EpycCommand;
 use crate::lang::{
-    InnerLang, LocaleTag, get_langs, get_tag_opt, lng, tag, tag_one_two_or,
+    self, InnerLang, LocaleTag, get_langs, get_tag_opt, lng, tag,
+    tag_one_two_or,
 };
 use crate::traits::MaybeMessageSetter;
 use crate::types::{MyBot, MyResult};
@@ -11,20 +12,16 @@ use futures::FutureExt;
 use teloxide::prelude::*;
 use teloxide::types::ChatKind;
 
-impl std::fmt::Display for EpycCommands {
+impl std::fmt::Display for EpycCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self {
-            EpycCommands::EpycUA(args) => write!(f, "епік {}", args),
-            EpycCommands::EpycRU(args) => write!(f, "эпик {}", args),
-            EpycCommands::EpycEN(args) => write!(f, "epyc {}", args),
-        }
+        write!(f, "{} {}", self.name, self.args)
     }
 }
 
 pub async fn filter_commands(
     bot: MyBot,
     m: Message,
-    cmd: EpycCommands,
+    cmd: EpycCommand,
 ) -> MyResult<()> {
     let Some(from) = &m.from else { return Ok(()) };
 
@@ -43,13 +40,7 @@ pub async fn filter_commands(
         return Ok(());
     }
 
-    let function = match &cmd {
-        EpycCommands::EpycUA(arg)
-        | EpycCommands::EpycRU(arg)
-        | EpycCommands::EpycEN(arg) => command_epyc(bot, m, ltag, arg).boxed(),
-    };
-
-    let response = function.await;
+    let response = command_epyc(bot, m, ltag, &cmd.args).await;
 
     if let Err(err) = response {
         crate::myerr!("Error {:?} in command: !{cmd}", err);
@@ -66,6 +57,7 @@ pub enum EpycSetting {
     Greetings,
     Top,
     Lang,
+    Names,
 }
 
 /// `None` for an unrecognised sub-command — the caller answers "function not
@@ -75,14 +67,14 @@ pub fn parse_epyc_arg(arg: &str) -> (Option<EpycSetting>, Option<&str>) {
     let Some(option) = splitted.next() else { return (None, None) };
     let setting = splitted.next();
 
-    let parsed = match option {
-        "привітання" | "приветствие" | "greetings" => {
-            Some(EpycSetting::Greetings)
-        },
-        "топ" | "top" => Some(EpycSetting::Top),
-        "мова" | "язык" | "lang" => Some(EpycSetting::Lang),
-        _ => None,
-    };
+    let parsed = [
+        (EpycSetting::Greetings, "EpycAlias_Greetings"),
+        (EpycSetting::Top, "EpycAlias_Top"),
+        (EpycSetting::Lang, "EpycAlias_Lang"),
+        (EpycSetting::Names, "EpycAlias_Names"),
+    ]
+    .into_iter()
+    .find_map(|(setting, key)| lang::is_alias(key, option).then_some(setting));
 
     (parsed, setting)
 }
@@ -96,6 +88,17 @@ pub fn parse_greetings_setting(setting: &str) -> Option<(i16, &'static str)> {
     }
 }
 
+/// The `groups.strict_names` value and the locale key confirming it.
+pub fn parse_strict_names_setting(
+    setting: &str,
+) -> Option<(bool, &'static str)> {
+    match setting {
+        "+" => Some((true, "StrictNamesEnabled")),
+        "-" => Some((false, "StrictNamesDisabled")),
+        _ => None,
+    }
+}
+
 // Command center
 async fn command_epyc(
     bot: MyBot,
@@ -136,6 +139,9 @@ async fn command_epyc(
         Some(EpycSetting::Lang) => {
             _epyc_chat_lang_setting(bot, m, ltag, setting).boxed()
         },
+        Some(EpycSetting::Names) => {
+            _epyc_strict_names_setting(bot, m, ltag, setting).boxed()
+        },
         None => _epyc_function_not_exist(bot, m, ltag).boxed(),
     };
 
@@ -217,6 +223,29 @@ async fn _epyc_greetings_setting(
     Ok(())
 }
 
+async fn _epyc_strict_names_setting(
+    bot: MyBot,
+    m: Message,
+    ltag: LocaleTag,
+    setting: Option<&str>,
+) -> MyResult<()> {
+    let Some(setting) = setting else {
+        let text = lng("OptionExistIncorrectParam", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(&m).await?;
+        return Ok(());
+    };
+
+    let Some(key) = parse_strict_names_setting(setting) else {
+        _epyc_invalid_arg(bot, m, ltag, "names").await?;
+        return Ok(());
+    };
+
+    DB.other.set_strict_names(m.chat.id.0, key.0).await?;
+    bot.send_message(m.chat.id, lng(key.1, ltag)).maybe_thread_id(&m).await?;
+
+    Ok(())
+}
+
 async fn _epyc_chat_lang_setting(
     bot: MyBot,
     m: Message,
@@ -258,9 +287,12 @@ async fn _epyc_chat_lang_setting(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::init_lang;
 
     #[test]
     fn each_sub_command_is_recognised_in_every_language() {
+        init_lang();
+
         let cases = [
             ("привітання", EpycSetting::Greetings),
             ("приветствие", EpycSetting::Greetings),
@@ -270,6 +302,9 @@ mod tests {
             ("мова", EpycSetting::Lang),
             ("язык", EpycSetting::Lang),
             ("lang", EpycSetting::Lang),
+            ("імена", EpycSetting::Names),
+            ("имена", EpycSetting::Names),
+            ("names", EpycSetting::Names),
         ];
 
         for (word, expected) in cases {
@@ -279,6 +314,8 @@ mod tests {
 
     #[test]
     fn the_parameter_is_the_second_token() {
+        init_lang();
+
         assert_eq!(
             parse_epyc_arg("greetings +"),
             (Some(EpycSetting::Greetings), Some("+"))
@@ -289,6 +326,8 @@ mod tests {
 
     #[test]
     fn extra_whitespace_is_ignored() {
+        init_lang();
+
         assert_eq!(
             parse_epyc_arg("   top    10   "),
             (Some(EpycSetting::Top), Some("10"))
@@ -297,6 +336,8 @@ mod tests {
 
     #[test]
     fn trailing_tokens_beyond_the_parameter_are_dropped() {
+        init_lang();
+
         assert_eq!(
             parse_epyc_arg("top 10 20 30"),
             (Some(EpycSetting::Top), Some("10"))
@@ -305,11 +346,15 @@ mod tests {
 
     #[test]
     fn a_missing_parameter_is_reported_as_none() {
+        init_lang();
+
         assert_eq!(parse_epyc_arg("top"), (Some(EpycSetting::Top), None));
     }
 
     #[test]
     fn an_unknown_sub_command_is_rejected() {
+        init_lang();
+
         for word in ["nope", "TOP", "Топ", "прив", ""] {
             assert_eq!(parse_epyc_arg(word).0, None, "{word:?}");
         }
@@ -328,4 +373,20 @@ mod tests {
             assert_eq!(parse_greetings_setting(bad), None, "{bad:?}");
         }
     }
+
+    #[test]
+    fn strict_names_maps_plus_and_minus_to_a_flag() {
+        assert_eq!(
+            parse_strict_names_setting("+"),
+            Some((true, "StrictNamesEnabled"))
+        );
+        assert_eq!(
+            parse_strict_names_setting("-"),
+            Some((false, "StrictNamesDisabled"))
+        );
+
+        for bad in ["", "on", "1", "+-"] {
+            assert_eq!(parse_strict_names_setting(bad), None, "{bad:?}");
+        }
+    }
 }
@@ -6,9 +6,10 @@ use crate::config::consts::INLINE_NAME_SET_LIMIT;
 use crate::db::DB;
 use crate::enums::InlineResults;
 use crate::lang::{InnerLang, LocaleTag, get_tag, lng, tag, tag_one_or};
+use crate::services::name_filter;
 use crate::types::MyBot;
 use crate::types::{MyError, MyResult};
-use crate::utils::date::get_date;
+use crate::utils::date::{get_date, month_start};
 use crate::utils::decode::decode_inline_message_id;
 use crate::utils::flag::Flags;
 use crate::utils::helpers;
@@ -43,6 +44,8 @@ pub async fn filter_inline_feedback_commands(
             chosen_change_lang(bot, &q, ltag, v).boxed()
         },
         InlineResults::DayPigInfo => chosen_day_pig(bot, &q, ltag).boxed(),
+        InlineResults::HruVoice(id) => chosen_hru_voice(bot, &q, *id).boxed(),
+        InlineResults::PigGif(id) => chosen_pig_gif(bot, &q, *id).boxed(),
         _ => {
             chosen_unhandled(bot, &q).await?;
             return Ok(());
@@ -71,6 +74,13 @@ async fn chosen_rename_hryak(
     let (new_name, _) =
         helpers::truncate(name_from_query, INLINE_NAME_SET_LIMIT);
 
+    // The inline answer already refused it, but this is what gets stored.
+    if !name_filter::is_allowed(new_name, false) {
+        let text = lng("PigNameRejected", ltag);
+        bot.edit_message_text_inline(im_id, text).await?;
+        return Ok(());
+    }
+
     DB.hand_pig.update_hrundel_name(q.from.id.0 as i64, new_name).await?;
 
     let text = lng("HandPigNameNowIs", ltag).args(&[("new_name", new_name)]);
@@ -170,6 +180,22 @@ async fn chosen_day_pig(
     Ok(())
 }
 
+async fn chosen_hru_voice(
+    _bot: MyBot,
+    _q: &ChosenInlineResult,
+    id: i16,
+) -> MyResult<()> {
+    DB.other.add_voice_use(id, month_start(get_date())).await
+}
+
+async fn chosen_pig_gif(
+    _bot: MyBot,
+    _q: &ChosenInlineResult,
+    id: i16,
+) -> MyResult<()> {
+    DB.other.add_gif_use(id, month_start(get_date())).await
+}
+
 async fn chosen_unhandled(_bot: MyBot, q: &ChosenInlineResult) -> MyResult<()> {
     log::info!(
         "Unhandled chosen inline: [{}] user: [{}]",
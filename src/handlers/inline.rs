@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::str::FromStr;
+use std::time::Instant;
 
 use futures::FutureExt;
 
@@ -12,7 +12,9 @@ use teloxide::{
     },
 };
 
-use crate::config::consts::{DEFAULT_LANG_TAG, INLINE_QUERY_LIMIT};
+use crate::config::consts::{
+    DEFAULT_LANG_TAG, INLINE_NEW_CONTENT_COUNT, INLINE_QUERY_LIMIT,
+};
 use crate::db::DB;
 use crate::db::models::{
     InlineGif, InlineVoice, NewInlineUser, UpdateInlineUser,
@@ -20,6 +22,8 @@ use crate::db::models::{
 use crate::db::shortcuts;
 use crate::enums::{InlineCommands, InlineKeywords, Top10Variant};
 use crate::lang::{InnerLang, LocaleTag, get_langs, get_tag, lng, tag_one_or};
+use crate::services::inline_content::order_by_popularity;
+use crate::services::name_filter;
 use crate::types::MyBot;
 use crate::types::{MyError, MyResult};
 use crate::utils::date::get_date;
@@ -51,7 +55,7 @@ pub async fn filter_inline_commands(
     bot: MyBot,
     q: InlineQuery,
 ) -> MyResult<()> {
-    crate::metrics::INLINE_COUNTER.inc();
+    let started = Instant::now();
     let user = shortcuts::maybe_get_or_insert_user(&q.from, false).await?;
     let ltag =
         tag_one_or(user.and_then(|u| u.lang).as_deref(), get_tag(&q.from));
@@ -61,8 +65,8 @@ pub async fn filter_inline_commands(
     let split_command = q.query.split_once(' ');
 
     let function = match split_command {
-        Some((action, payload)) => match InlineCommands::from_str(action) {
-            Ok(cmd) => match cmd {
+        Some((action, payload)) => match InlineCommands::from_alias(action) {
+            Some(cmd) => match cmd {
                 InlineCommands::Name => {
                     inline_rename_hrundel(bot, &q, ltag, payload).boxed()
                 },
@@ -76,10 +80,10 @@ pub async fn filter_inline_commands(
                     inline_gif(bot, &q, ltag, payload).boxed()
                 },
             },
-            Err(_) => inline_hrundel(bot, &q, ltag).boxed(),
+            None => inline_hrundel(bot, &q, ltag).boxed(),
         },
-        None => match InlineKeywords::from_str(&q.query) {
-            Ok(kwd) => match kwd {
+        None => match InlineKeywords::from_alias(&q.query) {
+            Some(kwd) => match kwd {
                 InlineKeywords::Name => {
                     inline_name_hrundel(bot, &q, ltag).boxed()
                 },
@@ -90,12 +94,18 @@ pub async fn filter_inline_commands(
                 InlineKeywords::Lang => inline_lang(bot, &q, ltag).boxed(),
                 InlineKeywords::Gif => inline_gif(bot, &q, ltag, "").boxed(),
             },
-            Err(_) => inline_hrundel(bot, &q, ltag).boxed(),
+            None => inline_hrundel(bot, &q, ltag).boxed(),
         },
     };
 
     let response = function.await;
 
+    crate::metrics::INLINE_QUERIES.observe(
+        inline_label(&q.query),
+        response.is_ok(),
+        started,
+    );
+
     if let Err(err) = response {
         handle_error(temp_bot, q, ltag, err).await;
     } else {
@@ -105,6 +115,18 @@ pub async fn filter_inline_commands(
     Ok(())
 }
 
+/// The keyword for the metrics; anything unrecognised is the plain pig card.
+fn inline_label(query: &str) -> &'static str {
+    match query.split_once(' ') {
+        Some((action, _)) => {
+            InlineCommands::from_alias(action).map_or("pig", |cmd| cmd.label())
+        },
+        None => {
+            InlineKeywords::from_alias(query).map_or("pig", |kwd| kwd.label())
+        },
+    }
+}
+
 async fn inline_hrundel(
     bot: MyBot,
     q: &InlineQuery,
@@ -236,6 +258,14 @@ async fn inline_rename_hrundel(
         return Ok(());
     };
 
+    if !name_filter::is_allowed(new_name, false) {
+        let article = iq_results::name_rejected_info(ltag);
+        let results = vec![InlineQueryResult::Article(article)];
+
+        bot.answer_inline_query(q.id.clone(), results).cache_time(0).await?;
+        return Ok(());
+    }
+
     let new_name = escape(new_name);
 
     let new_name =
@@ -294,7 +324,13 @@ async fn inline_hruks(
     payload: &str,
 ) -> MyResult<()> {
     let voices: Vec<InlineVoice> = if payload.is_empty() {
-        DB.other.get_inline_voices().await?
+        let (voices, uses) = tokio::try_join!(
+            DB.other.get_inline_voices(),
+            DB.other.get_voice_use_counts(),
+        )?;
+        let uses = uses.into_iter().collect();
+
+        order_by_popularity(voices, |v| v.id, &uses, INLINE_NEW_CONTENT_COUNT)
     } else {
         let Ok(id) = payload.parse::<i16>() else {
             bot.answer_inline_query(
@@ -325,9 +361,20 @@ async fn inline_hruks(
     let paged_voices = &voices[page.start..page.end];
     let results: Vec<_> = paged_voices
         .iter()
-        .map(|item| {
-            let caption = lng("InlineHrukCaptionNumber", ltag)
-                .args(&[("number", &item.id.to_string())]);
+        .enumerate()
+        .map(|(i, item)| {
+            let is_new =
+                payload.is_empty() && page.start + i < INLINE_NEW_CONTENT_COUNT;
+            let caption_key = if is_new {
+                "InlineHrukCaptionNumberNew"
+            } else {
+                "InlineHrukCaptionNumber"
+            };
+            let caption = if item.caption.is_empty() {
+                lng(caption_key, ltag).args(&[("number", &item.id.to_string())])
+            } else {
+                item.caption.clone()
+            };
             let voice_url = url.join(&item.url).unwrap_or_else(|_| url.clone());
 
             InlineQueryResult::Voice(iq_results::hru_voice_info(
@@ -463,7 +510,13 @@ async fn inline_gif(
     payload: &str,
 ) -> MyResult<()> {
     let gifs: Vec<InlineGif> = if payload.is_empty() {
-        DB.other.get_inline_gifs().await?
+        let (gifs, uses) = tokio::try_join!(
+            DB.other.get_inline_gifs(),
+            DB.other.get_gif_use_counts(),
+        )?;
+        let uses = uses.into_iter().collect();
+
+        order_by_popularity(gifs, |g| g.id, &uses, INLINE_NEW_CONTENT_COUNT)
     } else {
         let Ok(id) = payload.parse::<i16>() else {
             bot.answer_inline_query(
@@ -493,10 +546,14 @@ async fn inline_gif(
     let results: Vec<_> = paged_gifs
         .iter()
         .map(|item| {
-            InlineQueryResult::CachedGif(iq_results::gif_pig_info(
-                item.id,
-                item.file_id.clone(),
-            ))
+            let gif = iq_results::gif_pig_info(item.id, item.file_id.clone());
+            let gif = if item.caption.is_empty() {
+                gif
+            } else {
+                gif.caption(item.caption.clone())
+            };
+
+            InlineQueryResult::CachedGif(gif)
         })
         .collect();
 
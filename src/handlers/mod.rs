@@ -5,4 +5,5 @@ pub mod epyc;
 pub mod feedback;
 pub mod inline;
 pub mod message;
+pub mod settings;
 pub mod system;
@@ -0,0 +1,256 @@
+//! `/settings`: one private message whose text and keyboard are redrawn in
+//! place as the user moves between its pages.
+
+use std::str::FromStr;
+
+use teloxide::prelude::*;
+use teloxide::types::{CallbackQuery, InlineKeyboardMarkup, UserId};
+
+use crate::db::DB;
+use crate::db::models::User;
+use crate::enums::{SettingsPage, UserSetting};
+use crate::keyboards;
+use crate::lang::{InnerLang, LocaleTag, get_langs, get_tag, lng, tag_one_or};
+use crate::traits::MaybeMessageSetter;
+use crate::types::{MyBot, MyResult, ParsedCallbackData};
+use crate::utils::flag::Flags;
+
+pub async fn command_settings(
+    bot: MyBot,
+    m: &Message,
+    ltag: LocaleTag,
+) -> MyResult<()> {
+    let Some(from) = &m.from else { return Ok(()) };
+
+    if !m.chat.is_private() {
+        let text = lng("CommandOnlyForPrivate", ltag);
+        bot.send_message(m.chat.id, text).maybe_thread_id(m).await?;
+        return Ok(());
+    }
+
+    let Some(user) = DB.other.get_user(from.id.0 as i64).await? else {
+        return Ok(());
+    };
+
+    let (text, markup) = render(ltag, &user, SettingsPage::Main).await?;
+    bot.send_message(m.chat.id, text).reply_markup(markup).await?;
+
+    Ok(())
+}
+
+pub async fn callback_settings(
+    bot: MyBot,
+    q: &CallbackQuery,
+    ltag: LocaleTag,
+    data: ParsedCallbackData<'_>,
+) -> MyResult<()> {
+    let Some(user) = _callback_user(&bot, q, ltag, data).await? else {
+        return Ok(());
+    };
+
+    bot.answer_callback_query(q.id.clone()).await?;
+
+    let Some(page) = SettingsPage::from_payload(data.2) else { return Ok(()) };
+    _redraw(&bot, q, ltag, &user, page).await
+}
+
+/// `-` clears the override, so the Telegram app's language applies again.
+pub async fn callback_settings_lang(
+    bot: MyBot,
+    q: &CallbackQuery,
+    ltag: LocaleTag,
+    data: ParsedCallbackData<'_>,
+) -> MyResult<()> {
+    let Some(mut user) = _callback_user(&bot, q, ltag, data).await? else {
+        return Ok(());
+    };
+
+    let code = data.2;
+    let new_lang = (code != "-").then_some(code);
+
+    if new_lang.is_some_and(|code| !get_langs().iter().any(|l| l == code)) {
+        bot.answer_callback_query(q.id.clone()).await?;
+        return Ok(());
+    }
+
+    DB.other.change_user_lang(user.user_id, new_lang).await?;
+    user.lang = new_lang.map(str::to_owned);
+
+    // Everything from here on is already in the new language.
+    let ltag = tag_one_or(new_lang, get_tag(&q.from));
+
+    let text = lng("SettingsSaved", ltag);
+    bot.answer_callback_query(q.id.clone()).text(text).await?;
+    _redraw(&bot, q, ltag, &user, SettingsPage::Main).await
+}
+
+pub async fn callback_settings_flag(
+    bot: MyBot,
+    q: &CallbackQuery,
+    ltag: LocaleTag,
+    data: ParsedCallbackData<'_>,
+) -> MyResult<()> {
+    let Some(user) = _callback_user(&bot, q, ltag, data).await? else {
+        return Ok(());
+    };
+    let Some(flag) = Flags::from_code(data.2) else {
+        bot.answer_callback_query(q.id.clone()).await?;
+        return Ok(());
+    };
+
+    DB.hand_pig.update_hrundel_flag(user.user_id, flag.to_code()).await?;
+
+    let text = lng("SettingsSaved", ltag);
+    bot.answer_callback_query(q.id.clone()).text(text).await?;
+    _redraw(&bot, q, ltag, &user, SettingsPage::Main).await
+}
+
+pub async fn callback_settings_toggle(
+    bot: MyBot,
+    q: &CallbackQuery,
+    ltag: LocaleTag,
+    data: ParsedCallbackData<'_>,
+) -> MyResult<()> {
+    let Some(user) = _callback_user(&bot, q, ltag, data).await? else {
+        return Ok(());
+    };
+    let Ok(setting) = UserSetting::from_str(data.2) else {
+        bot.answer_callback_query(q.id.clone()).await?;
+        return Ok(());
+    };
+
+    let mut settings = DB.other.get_user_settings(user.id).await?;
+    settings.toggle(setting);
+    DB.other.set_user_settings(settings).await?;
+
+    let text = lng("SettingsSaved", ltag);
+    bot.answer_callback_query(q.id.clone()).text(text).await?;
+    _redraw(&bot, q, ltag, &user, setting.page()).await
+}
+
+async fn render(
+    ltag: LocaleTag,
+    user: &User,
+    page: SettingsPage,
+) -> MyResult<(String, InlineKeyboardMarkup)> {
+    let id_user = UserId(user.user_id as u64);
+
+    let rendered = match page {
+        SettingsPage::Main => {
+            let flag = _hand_pig_flag(user).await?;
+
+            let lang = match user.lang.as_deref() {
+                Some(code) => {
+                    let flag = Flags::from_code(code).unwrap_or(Flags::Us);
+                    let name = lng(&format!("lang_{code}"), ltag);
+                    format!("{} {name}", flag.to_emoji())
+                },
+                None => lng("SettingsLangAuto", ltag),
+            };
+
+            let text = lng("SettingsMenu", ltag).args(&[
+                ("lang", lang.as_str()),
+                ("flag", flag.map_or("—", Flags::to_emoji)),
+            ]);
+            (text, keyboards::keyboard_settings(ltag, id_user))
+        },
+        SettingsPage::Lang => {
+            let current = user.lang.as_deref();
+            let markup =
+                keyboards::keyboard_settings_lang(ltag, id_user, current);
+            (lng("SettingsLangMenu", ltag), markup)
+        },
+        SettingsPage::Flags(page) => match _hand_pig_flag(user).await? {
+            Some(flag) => {
+                let text = lng("SettingsFlagMenu", ltag)
+                    .args(&[("flag", flag.to_emoji())]);
+                let markup = keyboards::keyboard_settings_flags(
+                    ltag,
+                    id_user,
+                    page,
+                    flag.to_code(),
+                );
+                (text, markup)
+            },
+            None => {
+                let markup = keyboards::keyboard_settings_back(ltag, id_user);
+                (lng("HandPigNoInBarn", ltag), markup)
+            },
+        },
+        SettingsPage::Notifications => {
+            let switches = &UserSetting::NOTIFICATIONS;
+            _render_switches(ltag, user, "SettingsNotificationsMenu", switches)
+                .await?
+        },
+        SettingsPage::Privacy => {
+            let switches = &UserSetting::PRIVACY;
+            _render_switches(ltag, user, "SettingsPrivacyMenu", switches)
+                .await?
+        },
+    };
+
+    Ok(rendered)
+}
+
+async fn _render_switches(
+    ltag: LocaleTag,
+    user: &User,
+    key: &str,
+    switches: &[UserSetting],
+) -> MyResult<(String, InlineKeyboardMarkup)> {
+    let id_user = UserId(user.user_id as u64);
+    let settings = DB.other.get_user_settings(user.id).await?;
+
+    let markup = keyboards::keyboard_settings_toggles(
+        ltag, id_user, &settings, switches,
+    );
+
+    Ok((lng(key, ltag), markup))
+}
+
+/// `None` without a hand pig: the flag belongs to it.
+async fn _hand_pig_flag(user: &User) -> MyResult<Option<Flags>> {
+    let hand_pig = DB.hand_pig.get_hrundel(user.user_id).await?;
+
+    Ok(hand_pig
+        .map(|(pig, _)| Flags::from_code(&pig.flag).unwrap_or(Flags::Us)))
+}
+
+/// The user behind a settings button, or `None` once the press is answered:
+/// someone else's menu, or a user the bot does not know.
+async fn _callback_user(
+    bot: &MyBot,
+    q: &CallbackQuery,
+    ltag: LocaleTag,
+    data: ParsedCallbackData<'_>,
+) -> MyResult<Option<User>> {
+    if data.1 != q.from.id {
+        let text = lng("UserAccessDeniedResponse", ltag);
+        bot.answer_callback_query(q.id.clone()).text(text).await?;
+        return Ok(None);
+    }
+
+    let user = DB.other.get_user(q.from.id.0 as i64).await?;
+    if user.is_none() {
+        bot.answer_callback_query(q.id.clone()).await?;
+    }
+
+    Ok(user)
+}
+
+async fn _redraw(
+    bot: &MyBot,
+    q: &CallbackQuery,
+    ltag: LocaleTag,
+    user: &User,
+    page: SettingsPage,
+) -> MyResult<()> {
+    let Some(m) = &q.message else { return Ok(()) };
+
+    let (text, markup) = render(ltag, user, page).await?;
+    bot.edit_message_text(m.chat().id, m.id(), text)
+        .reply_markup(markup)
+        .await?;
+
+    Ok(())
+}
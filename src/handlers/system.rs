@@ -5,20 +5,25 @@ use crate::{
         models::{UpdateGroups, UserStatus},
         shortcuts,
     },
-    keyboards,
-    lang::{InnerLang, LocaleTag, get_tag, get_tag_opt, lng, tag_one_or},
+    lang::{
+        InnerLang, LocaleTag, get_tag, get_tag_opt, lng, tag_one_or,
+        tag_one_two_or,
+    },
+    services::{
+        moderation,
+        rate_limit::{Scope, Throttled},
+    },
     traits::MaybeMessageSetter,
     types::{MyBot, MyResult},
+    utils::date::get_datetime,
 };
 use teloxide::{
-    RequestError,
     prelude::*,
-    types::{InputFile, ReplyParameters, UpdateKind, User},
+    types::{ReplyParameters, UpdateKind, User},
     utils::html::user_mention,
 };
 use teloxide::{types::Message, utils::html::escape};
 use teloxide::{types::MessageKind, utils::html::bold};
-use tokio::time::sleep;
 
 pub async fn handle_new_member(bot: MyBot, m: Message) -> MyResult<()> {
     let Some(new_chat_members) = m.new_chat_members() else {
@@ -57,6 +62,37 @@ pub async fn handle_new_member(bot: MyBot, m: Message) -> MyResult<()> {
     Ok(())
 }
 
+pub async fn handle_throttled(
+    bot: MyBot,
+    m: Message,
+    throttled: Throttled,
+) -> MyResult<()> {
+    if !throttled.notify {
+        return Ok(());
+    }
+
+    let user_info = match &m.from {
+        Some(from) => DB.other.get_user(from.id.0 as i64).await?,
+        None => None,
+    };
+    let chat_info = DB.other.get_chat(m.chat.id.0).await?;
+
+    let ltag = tag_one_two_or(
+        user_info.and_then(|u| u.lang).as_deref(),
+        chat_info.and_then(|c| c.lang).as_deref(),
+        get_tag_opt(m.from.as_ref()),
+    );
+
+    let key = match throttled.scope {
+        Scope::User => "RateLimitUser",
+        Scope::Chat => "RateLimitChat",
+    };
+
+    bot.send_message(m.chat.id, lng(key, ltag)).maybe_thread_id(&m).await?;
+
+    Ok(())
+}
+
 pub async fn handle_left_member(bot: MyBot, m: Message) -> MyResult<()> {
     let Some(member) = m.left_chat_member() else {
         crate::myerr!("No left chat member in left chat members, wtf?");
@@ -167,18 +203,40 @@ pub async fn handle_voice_private(bot: MyBot, m: Message) -> MyResult<()> {
     };
 
     let ltag = tag_one_or(user.lang.as_deref(), get_tag_opt(m.from.as_ref()));
+    let voice = m.voice().unwrap();
+
+    let maybe_voice_with_same_id =
+        DB.other.get_voice_by_file_unique_id(&voice.file.unique_id.0).await?;
+
+    if let Some(existing) = maybe_voice_with_same_id {
+        let text =
+            lng("InlineVoiceAlreadyExist", ltag).args(&[("id", existing.id)]);
+        bot.send_message(m.chat.id, text).maybe_thread_id(&m).await?;
+        return Ok(());
+    }
+
     let text = lng("InlineHrukAddMessage", ltag);
 
     bot.send_message(m.chat.id, text).maybe_thread_id(&m).await?;
-    let voice = m.voice().unwrap();
 
-    bot.send_voice(
-        ChatId(BOT_CONFIG.content_check_channel_id),
-        InputFile::file_id(voice.file.id.clone()),
-    )
-    .caption(from.id.to_string())
-    .reply_markup(keyboards::keyboard_voice_check(from.id))
-    .await?;
+    let mut pending = DB
+        .other
+        .add_pending_voice(
+            user.id,
+            &voice.file.id.0,
+            &voice.file.unique_id.0,
+            get_datetime(),
+        )
+        .await?;
+
+    if let Some(fingerprint) =
+        moderation::fingerprint_voice(&bot, &voice.file.id).await
+    {
+        DB.other.set_voice_fingerprint(pending.id, fingerprint).await?;
+        pending.fingerprint = Some(fingerprint);
+    }
+
+    moderation::send_voice_review_card(&bot, &pending, from.id).await?;
 
     Ok(())
 }
@@ -207,12 +265,17 @@ pub async fn handle_animation_private(bot: MyBot, m: Message) -> MyResult<()> {
 
     bot.send_message(m.chat.id, text).maybe_thread_id(&m).await?;
 
-    let file = InputFile::file_id(animation.file.id.clone());
-
-    bot.send_animation(ChatId(BOT_CONFIG.content_check_channel_id), file)
-        .caption(from.id.to_string())
-        .reply_markup(keyboards::keyboard_gif_check(from.id))
+    let pending = DB
+        .other
+        .add_pending_gif(
+            user.id,
+            &animation.file.id.0,
+            &animation.file.unique_id.0,
+            get_datetime(),
+        )
         .await?;
+    moderation::send_gif_review_card(&bot, &pending, from.id).await?;
+
     Ok(())
 }
 
@@ -244,8 +307,7 @@ pub async fn _greeting_new_chat_member(
         .maybe_thread_id(&m)
         .await;
     log::info!("New chat member in chat [{}]", m.chat.id);
-    if let Err(RequestError::RetryAfter(sec)) = res {
-        sleep(sec.duration()).await;
-        Box::pin(_greeting_new_chat_member(bot, m, ltag, user)).await;
+    if let Err(err) = res {
+        log::error!("Greeting failed in chat [{}] {}", m.chat.id, err);
     }
 }
@@ -0,0 +1,95 @@
+//! Probes for the orchestrator.
+//!
+//! `/healthz` answers as long as the process serves HTTP at all. `/readyz`
+//! also needs a database connection, the locales and `BOT_ME`; a failing
+//! check is named in the body of the 503.
+
+use std::time::Duration;
+
+use axum::{Router, http::StatusCode, routing::get};
+
+use crate::{config::env::BOT_ME, db::init::Database, lang};
+
+/// Well inside a probe's usual timeout, unlike the pool's own wait.
+const DB_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub fn init() -> Router {
+    Router::new().route("/healthz", get(healthz)).route("/readyz", get(readyz))
+}
+
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+async fn readyz() -> (StatusCode, String) {
+    let checks = [
+        ("db", check_db().await),
+        ("locales", lang::is_loaded().then_some(()).ok_or("not loaded")),
+        ("bot_me", BOT_ME.get().map(|_| ()).ok_or("not set")),
+    ];
+
+    let (status, body) = readiness(&checks);
+
+    if status != StatusCode::OK {
+        log::warn!("Readiness: {}", body.replace('\n', "; "));
+    }
+
+    (status, body)
+}
+
+async fn check_db() -> Result<(), &'static str> {
+    let pool = Database::get_or_init_pool();
+
+    match tokio::time::timeout(DB_CHECK_TIMEOUT, pool.get()).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(err)) => {
+            log::warn!("Readiness: db checkout: {:?}", err);
+            Err("no connection")
+        },
+        Err(_) => Err("timed out"),
+    }
+}
+
+fn readiness(checks: &[(&str, Result<(), &str>)]) -> (StatusCode, String) {
+    let failed: Vec<_> = checks
+        .iter()
+        .filter_map(|(name, result)| {
+            result.err().map(|reason| format!("{name}: {reason}"))
+        })
+        .collect();
+
+    if failed.is_empty() {
+        (StatusCode::OK, "ok".to_owned())
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, failed.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ready_when_every_check_passes() {
+        let checks = [("db", Ok(())), ("locales", Ok(()))];
+
+        assert_eq!(readiness(&checks), (StatusCode::OK, "ok".to_owned()));
+    }
+
+    #[test]
+    fn every_failed_check_is_named() {
+        let checks = [
+            ("db", Err("timed out")),
+            ("locales", Ok(())),
+            ("bot_me", Err("not set")),
+        ];
+
+        assert_eq!(
+            readiness(&checks),
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "db: timed out\nbot_me: not set".to_owned()
+            )
+        );
+    }
+}
@@ -1,10 +1,15 @@
+use strum::VariantArray;
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, UserId};
 
 use crate::{
-    config::env::{BOT_CONFIG, bot_me},
-    enums::{CbActions, Top10Variant},
-    lang::{LocaleTag, lng},
-    utils::helpers::encode_callback_data,
+    config::{
+        consts::{SETTINGS_FLAG_ROWS, SETTINGS_FLAGS_PER_ROW},
+        env::{BOT_CONFIG, bot_me},
+    },
+    db::models::UserSettings,
+    enums::{CbActions, RejectReason, SettingsPage, Top10Variant, UserSetting},
+    lang::{LocaleTag, get_langs, lng},
+    utils::{flag::Flags, helpers::encode_callback_data},
 };
 
 pub fn keyboard_new_name(
@@ -157,11 +162,18 @@ pub fn keyboard_top(
     InlineKeyboardMarkup::new(keyboard)
 }
 
-pub fn keyboard_voice_check(id_user: UserId) -> InlineKeyboardMarkup {
-    let success_data = encode_callback_data(CbActions::AllowVoice, id_user, "");
+/// The payload is the pending row id, so the decision lands on that row even
+/// when the card was re-sent.
+pub fn keyboard_voice_check(
+    id_user: UserId,
+    voice_id: i16,
+) -> InlineKeyboardMarkup {
+    let payload = voice_id.to_string();
+    let success_data =
+        encode_callback_data(CbActions::AllowVoice, id_user, &payload);
     let success_button = InlineKeyboardButton::callback("✅", success_data);
     let denied_data =
-        encode_callback_data(CbActions::DisallowVoice, id_user, "");
+        encode_callback_data(CbActions::DisallowVoice, id_user, &payload);
     let denied_button = InlineKeyboardButton::callback("❌", denied_data);
 
     let keyboard = [[success_button, denied_button]];
@@ -169,13 +181,23 @@ pub fn keyboard_voice_check(id_user: UserId) -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(keyboard)
 }
 
-pub fn keyboard_gif_check(id_user: UserId) -> InlineKeyboardMarkup {
-    let success_data =
-        encode_callback_data(CbActions::GifDecision, id_user, "+");
+/// See [`keyboard_voice_check`]; the decision sign leads the row id.
+pub fn keyboard_gif_check(
+    id_user: UserId,
+    gif_id: i16,
+) -> InlineKeyboardMarkup {
+    let success_data = encode_callback_data(
+        CbActions::GifDecision,
+        id_user,
+        &format!("+{gif_id}"),
+    );
     let success_button = InlineKeyboardButton::callback("✅", success_data);
 
-    let denied_data =
-        encode_callback_data(CbActions::GifDecision, id_user, "-");
+    let denied_data = encode_callback_data(
+        CbActions::GifDecision,
+        id_user,
+        &format!("-{gif_id}"),
+    );
     let denied_button = InlineKeyboardButton::callback("❌", denied_data);
 
     let keyboard = [[success_button, denied_button]];
@@ -183,6 +205,63 @@ pub fn keyboard_gif_check(id_user: UserId) -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(keyboard)
 }
 
+/// Shown in place of [`keyboard_voice_check`] after ❌: one button per
+/// reason, `<id>:<reason>`, and a way back.
+pub fn keyboard_voice_reject(
+    ltag: LocaleTag,
+    id_user: UserId,
+    voice_id: i16,
+) -> InlineKeyboardMarkup {
+    _keyboard_reject_reasons(
+        ltag,
+        id_user,
+        CbActions::DisallowVoice,
+        &voice_id.to_string(),
+        &format!("v{voice_id}"),
+    )
+}
+
+/// See [`keyboard_voice_reject`]; `-<id>:<reason>`.
+pub fn keyboard_gif_reject(
+    ltag: LocaleTag,
+    id_user: UserId,
+    gif_id: i16,
+) -> InlineKeyboardMarkup {
+    _keyboard_reject_reasons(
+        ltag,
+        id_user,
+        CbActions::GifDecision,
+        &format!("-{gif_id}"),
+        &format!("g{gif_id}"),
+    )
+}
+
+fn _keyboard_reject_reasons(
+    ltag: LocaleTag,
+    id_user: UserId,
+    action: CbActions,
+    prefix: &str,
+    back_payload: &str,
+) -> InlineKeyboardMarkup {
+    let mut buttons: Vec<_> = RejectReason::VARIANTS
+        .iter()
+        .map(|&reason| {
+            let text = lng(&format!("RejectReason_{}", reason as i16), ltag);
+            let payload = format!("{prefix}:{}", reason as i16);
+            let data = encode_callback_data(action, id_user, payload);
+            InlineKeyboardButton::callback(text, data)
+        })
+        .collect();
+
+    let back_data =
+        encode_callback_data(CbActions::ReviewBack, id_user, back_payload);
+    buttons.push(InlineKeyboardButton::callback("↩️", back_data));
+
+    let keyboard = buttons.chunks(2).map(<[_]>::to_vec);
+
+    InlineKeyboardMarkup::new(keyboard)
+}
+
 pub fn keyboard_change_flag(
     ltag: LocaleTag,
     id_user: UserId,
@@ -267,6 +346,147 @@ pub fn keyboard_reset_vote(ltag: LocaleTag, initiator_id: UserId) -> InlineKeybo
     InlineKeyboardMarkup::new([[InlineKeyboardButton::callback(text, data)]])
 }
 
+pub fn keyboard_settings(
+    ltag: LocaleTag,
+    id_user: UserId,
+) -> InlineKeyboardMarkup {
+    let button = |key: &str, page: SettingsPage| {
+        let data = encode_callback_data(
+            CbActions::Settings,
+            id_user,
+            page.to_payload(),
+        );
+        InlineKeyboardButton::callback(lng(key, ltag), data)
+    };
+
+    let keyboard = [
+        vec![
+            button("SettingsButtonLang", SettingsPage::Lang),
+            button("SettingsButtonFlag", SettingsPage::Flags(0)),
+        ],
+        vec![
+            button("SettingsButtonNotifications", SettingsPage::Notifications),
+            button("SettingsButtonPrivacy", SettingsPage::Privacy),
+        ],
+    ];
+
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+/// Every loaded language, then `-` for whatever the Telegram app says.
+pub fn keyboard_settings_lang(
+    ltag: LocaleTag,
+    id_user: UserId,
+    current: Option<&str>,
+) -> InlineKeyboardMarkup {
+    let option = |code: &str, text: String| {
+        let mark = if current.unwrap_or("-") == code { "✅ " } else { "" };
+        let data = encode_callback_data(CbActions::SettingsLang, id_user, code);
+        InlineKeyboardButton::callback(format!("{mark}{text}"), data)
+    };
+
+    let mut buttons: Vec<_> = get_langs()
+        .iter()
+        .map(|code| {
+            let flag = Flags::from_code(code).unwrap_or(Flags::Us).to_emoji();
+            let name = lng(&format!("lang_{code}"), ltag);
+            option(code, format!("{flag} {name}"))
+        })
+        .collect();
+    buttons.push(option("-", lng("SettingsLangAuto", ltag)));
+
+    let mut keyboard: Vec<_> = buttons.chunks(2).map(<[_]>::to_vec).collect();
+    keyboard.push(vec![_button_settings_back(ltag, id_user)]);
+
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+/// One page of [`Flags::FLAGS`], clamped to the last one.
+pub fn keyboard_settings_flags(
+    ltag: LocaleTag,
+    id_user: UserId,
+    page: usize,
+    current: &str,
+) -> InlineKeyboardMarkup {
+    const PER_PAGE: usize = SETTINGS_FLAGS_PER_ROW * SETTINGS_FLAG_ROWS;
+
+    let pages = Flags::FLAGS.len().div_ceil(PER_PAGE);
+    let page = page.min(pages - 1);
+    let start = page * PER_PAGE;
+    let end = (start + PER_PAGE).min(Flags::FLAGS.len());
+
+    let mut keyboard: Vec<Vec<_>> = Flags::FLAGS[start..end]
+        .chunks(SETTINGS_FLAGS_PER_ROW)
+        .map(|row| {
+            row.iter()
+                .map(|flag| {
+                    let code = flag.to_code();
+                    let text =
+                        if code == current { "✅" } else { flag.to_emoji() };
+                    let data = encode_callback_data(
+                        CbActions::SettingsFlag,
+                        id_user,
+                        code,
+                    );
+                    InlineKeyboardButton::callback(text, data)
+                })
+                .collect()
+        })
+        .collect();
+
+    let arrow = |text: &str, page: usize| {
+        let payload = SettingsPage::Flags(page).to_payload();
+        let data = encode_callback_data(CbActions::Settings, id_user, payload);
+        InlineKeyboardButton::callback(text, data)
+    };
+
+    let mut nav = vec![];
+    if page > 0 {
+        nav.push(arrow("⬅️", page - 1));
+    }
+    nav.push(_button_settings_back(ltag, id_user));
+    if page + 1 < pages {
+        nav.push(arrow("➡️", page + 1));
+    }
+    keyboard.push(nav);
+
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+/// One row per switch, marked with its current state.
+pub fn keyboard_settings_toggles(
+    ltag: LocaleTag,
+    id_user: UserId,
+    settings: &UserSettings,
+    switches: &[UserSetting],
+) -> InlineKeyboardMarkup {
+    let mut keyboard: Vec<_> = switches
+        .iter()
+        .map(|&setting| {
+            let mark = if settings.get(setting) { "✅" } else { "❌" };
+            let name = setting.into_str();
+            let label = lng(&format!("UserSetting_{name}"), ltag);
+            let data =
+                encode_callback_data(CbActions::SettingsToggle, id_user, name);
+            vec![InlineKeyboardButton::callback(
+                format!("{mark} {label}"),
+                data,
+            )]
+        })
+        .collect();
+    keyboard.push(vec![_button_settings_back(ltag, id_user)]);
+
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+/// For a settings page with nothing to choose.
+pub fn keyboard_settings_back(
+    ltag: LocaleTag,
+    id_user: UserId,
+) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new([[_button_settings_back(ltag, id_user)]])
+}
+
 pub fn keyboard_empty() -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::default()
 }
@@ -286,3 +506,12 @@ fn _button_startgroup(ltag: LocaleTag) -> InlineKeyboardButton {
     let text = lng("BotAddToGroup", ltag);
     InlineKeyboardButton::url(text, url)
 }
+
+fn _button_settings_back(
+    ltag: LocaleTag,
+    id_user: UserId,
+) -> InlineKeyboardButton {
+    let payload = SettingsPage::Main.to_payload();
+    let data = encode_callback_data(CbActions::Settings, id_user, payload);
+    InlineKeyboardButton::callback(lng("SettingsButtonBack", ltag), data)
+}
@@ -1,15 +1,76 @@
+//! Translations from `locales/<tag>.json`.
+//!
+//! The loaded [`Locale`] is swapped whole by [`reload`], on SIGHUP or
+//! `/reloadlocales`, so a typo is fixed without a redeploy. A reload that
+//! fails [`Locale::check`] or changes the set of languages is refused and
+//! the current texts stay.
+//!
+//! A key a language lacks is served from the [`LocaleTag`]'s fallback
+//! language, then from the default one, and reported the first time.
+//!
+//! `*Alias_*` keys hold comma-separated words the bot answers to, such as
+//! inline keywords; see [`is_alias`]. A new language file brings its own.
+//!
+//! Besides `{name}` slots a value may hold `{name:one=…|few=…|many=…}`,
+//! which [`InnerLang::plural`] resolves by the CLDR plural category of the
+//! count in that language. [`PluralRule`] says which categories a language
+//! has to spell out.
+
+use ahash::AHashSet;
+use std::collections::BTreeSet;
+use std::fmt::{self, Display};
 use std::fs;
 use std::path::Path;
-use std::sync::OnceLock;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock, Mutex, PoisonError, RwLock};
+use strum::{EnumString, IntoStaticStr};
 use teloxide::types::User;
 use walkdir::WalkDir;
 
 use crate::config::consts::DEFAULT_LANG_TAG;
+use crate::metrics;
 
 // Access to localize
-pub static LANG: OnceLock<Locale> = OnceLock::new();
+static LANG: RwLock<Option<Arc<Locale>>> = RwLock::new(None);
 
-pub type LocaleTag = usize;
+/// Keys a locale may carry without the default locale having them.
+pub const LOCALE_LOCAL_KEYS: &[&str] = &["AUTHOR_OF_THIS_TRANSLATION"];
+
+/// `(language, key)` misses already reported, so a hot key logs once.
+static REPORTED_MISSES: LazyLock<Mutex<AHashSet<(usize, String)>>> =
+    LazyLock::new(Mutex::default);
+
+/// A language, as an index into the loaded ones, and where a key it lacks
+/// is looked up before the default language.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LocaleTag {
+    lang: usize,
+    fallback: Option<usize>,
+}
+
+impl LocaleTag {
+    pub const fn index(self) -> usize {
+        self.lang
+    }
+
+    /// The first of `candidates` that is loaded and is not this language.
+    fn with_fallback(self, candidates: &[Option<&str>]) -> Self {
+        let fallback = candidates
+            .iter()
+            .filter_map(|candidate| tag_opt(*candidate))
+            .map(Self::index)
+            .find(|&lang| lang != self.lang);
+
+        Self { fallback, ..self }
+    }
+}
+
+/// An index into [`get_langs`], with no fallback but the default language.
+impl From<usize> for LocaleTag {
+    fn from(lang: usize) -> Self {
+        Self { lang, fallback: None }
+    }
+}
 
 #[derive(Debug)]
 struct Lang {
@@ -23,8 +84,26 @@ pub struct Locale {
     def_tag: usize,
 }
 
+/// Every problem found in the locale files, one per line.
+#[derive(Debug)]
+pub struct LocaleError {
+    pub problems: Vec<String>,
+}
+
+impl Display for LocaleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid locales ({}):", self.problems.len())?;
+
+        for problem in &self.problems {
+            write!(f, "\n  - {problem}")?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Locale {
-    pub fn new(set_def_tag: &str) -> Self {
+    pub fn new(set_def_tag: &str) -> Result<Self, LocaleError> {
         Self::load_from("locales/", set_def_tag)
     }
 
@@ -33,8 +112,12 @@ impl Locale {
     /// [`Locale::new`] resolves `locales/` relative to the current working
     /// directory, which is fine for the bot but brittle for tests; they pass
     /// a path anchored to `CARGO_MANIFEST_DIR` instead.
-    pub fn load_from(dir: impl AsRef<Path>, set_def_tag: &str) -> Self {
+    pub fn load_from(
+        dir: impl AsRef<Path>,
+        set_def_tag: &str,
+    ) -> Result<Self, LocaleError> {
         let mut langs = vec![];
+        let mut problems = vec![];
 
         // Load "tag".json from directory
         for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
@@ -50,40 +133,38 @@ impl Locale {
 
             // Extract filename as tag
             let tag = file_name_parts.0.to_string();
+            let path = entry.path().display();
 
             // Open file
-            let Ok(file) = fs::File::open(entry.path()) else {
-                log::error!(
-                    "Locale::new() open error '{}'",
-                    entry.path().display()
-                );
-                continue;
+            let file = match fs::File::open(entry.path()) {
+                Ok(file) => file,
+                Err(err) => {
+                    problems.push(format!("{path}: {err}"));
+                    continue;
+                },
             };
 
             // Read data
-            let Ok(data) = serde_json::from_reader(file) else {
-                log::error!(
-                    "Locale::new() read error '{}'",
-                    entry.path().display()
-                );
-                continue;
+            let data = match serde_json::from_reader(file) {
+                Ok(data) => data,
+                Err(err) => {
+                    problems.push(format!("{path}: {err}"));
+                    continue;
+                },
             };
 
             // Get an json object
             let serde_json::Value::Object(json_map) = data else {
-                log::error!(
-                    "Locale::new() wrong json '{}'",
-                    entry.path().display()
-                );
+                problems.push(format!("{path}: not a JSON object"));
                 continue;
             };
 
             // Store
             let mut map = ahash::AHashMap::default();
             for (key, value) in json_map.into_iter() {
-                let value = match value {
-                    serde_json::Value::String(value) => value,
-                    _ => panic!("Locale::new(): only String can be passed!"),
+                let serde_json::Value::String(value) = value else {
+                    problems.push(format!("{path}: {key} is not a string"));
+                    continue;
                 };
                 map.insert(key, value);
             }
@@ -95,16 +176,383 @@ impl Locale {
         langs.sort_by(|a, b| a.tag.cmp(&b.tag));
 
         // After sort, store default locale
-        let def_tag = langs
-            .binary_search_by(|elem| elem.tag.as_str().cmp(set_def_tag))
-            .expect("Invalid default lang!");
+        let def_tag =
+            langs.binary_search_by(|elem| elem.tag.as_str().cmp(set_def_tag));
+
+        let def_tag = match def_tag {
+            Ok(def_tag) if problems.is_empty() => def_tag,
+            Ok(_) => return Err(LocaleError { problems }),
+            Err(_) => {
+                problems.push(format!("no default locale {set_def_tag}"));
+                return Err(LocaleError { problems });
+            },
+        };
+
+        let summary =
+            langs.iter().fold(String::from("Loaded lang:"), |acc, l| {
+                format!("{} {}", acc, l.tag)
+            });
+        log::info!("{} | default: {}", summary, set_def_tag);
+
+        Ok(Self { langs, def_tag })
+    }
+
+    /// [`Locale::missing_keys`] and [`Locale::problems`], one per line.
+    pub fn check(&self) -> Vec<String> {
+        let mut problems = self.problems();
+
+        for (tag, keys) in self.missing_keys() {
+            problems.extend(
+                keys.iter().map(|key| format!("{tag}: {key} is missing")),
+            );
+        }
+
+        problems.sort();
+        problems
+    }
+
+    /// Keys of the default locale each other one lacks, sorted.
+    pub fn missing_keys(&self) -> Vec<(&str, Vec<&str>)> {
+        let default = &self.langs[self.def_tag];
+
+        self.langs
+            .iter()
+            .filter(|lang| lang.tag != default.tag)
+            .map(|lang| {
+                let mut keys: Vec<&str> = default
+                    .map
+                    .keys()
+                    .filter(|key| !lang.map.contains_key(*key))
+                    .map(String::as_str)
+                    .collect();
+                keys.sort_unstable();
+
+                (lang.tag.as_str(), keys)
+            })
+            .filter(|(_, keys)| !keys.is_empty())
+            .collect()
+    }
+
+    /// Keys unknown to the default locale, translations whose
+    /// `{placeholders}` differ from the default one's, plural forms a
+    /// language lacks or does not know, and aliases that cannot be typed or
+    /// mean two things.
+    pub fn problems(&self) -> Vec<String> {
+        let default = &self.langs[self.def_tag];
+        let mut problems = self.alias_problems();
+
+        for lang in &self.langs {
+            let rule = PluralRule::for_tag(&lang.tag);
+
+            for (key, text) in &lang.map {
+                for (name, forms) in plural_blocks(text) {
+                    problems.extend(rule.problems(forms).map(|p| {
+                        format!("{}: {key} {{{name}}} {p}", lang.tag)
+                    }));
+                }
+            }
+
+            if lang.tag == default.tag {
+                continue;
+            }
+
+            for (key, text) in &default.map {
+                let Some(own) = lang.map.get(key) else { continue };
+
+                let (expected, found) = (placeholders(text), placeholders(own));
+                if expected != found {
+                    problems.push(format!(
+                        "{}: {key} has placeholders {found:?}, {} has {expected:?}",
+                        lang.tag, default.tag,
+                    ));
+                }
+            }
+
+            for key in lang.map.keys() {
+                let local = LOCALE_LOCAL_KEYS.contains(&key.as_str());
+
+                if !local && !default.map.contains_key(key) {
+                    problems.push(format!(
+                        "{}: {key} is not in {}",
+                        lang.tag, default.tag
+                    ));
+                }
+            }
+        }
+
+        problems.sort();
+        problems
+    }
+
+    fn alias_problems(&self) -> Vec<String> {
+        // (group, word) -> the key that has it, across all languages
+        let mut owners = ahash::AHashMap::default();
+        let mut problems = vec![];
+
+        for lang in &self.langs {
+            for (key, value) in &lang.map {
+                let Some(group) = alias_group(key) else { continue };
+
+                for word in aliases(value) {
+                    if word.is_empty() || word.contains(char::is_whitespace) {
+                        problems.push(format!(
+                            "{}: {key} has an alias {word:?} nobody can type",
+                            lang.tag
+                        ));
+                        continue;
+                    }
+
+                    let owner = *owners.entry((group, word)).or_insert(key);
+                    if owner != key {
+                        problems.push(format!(
+                            "{}: {key} and {owner} both have the alias {word:?}",
+                            lang.tag
+                        ));
+                    }
+                }
+            }
+        }
+
+        problems
+    }
+
+    fn tags(&self) -> Vec<&str> {
+        self.langs.iter().map(|lang| lang.tag.as_str()).collect()
+    }
+
+    /// The text of `key` and the language it came from: `tag`'s own, its
+    /// fallback, then the default.
+    fn text(&self, key: &str, tag: LocaleTag) -> Option<(&str, usize)> {
+        [Some(tag.lang), tag.fallback, Some(self.def_tag)]
+            .into_iter()
+            .flatten()
+            .find_map(|lang| {
+                let text = self.langs.get(lang)?.map.get(key)?;
+                Some((text.as_str(), lang))
+            })
+    }
+
+    fn report_miss(&self, key: &str, lang: usize) {
+        let first = REPORTED_MISSES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((lang, key.to_owned()));
+
+        if first {
+            let tag = &self.langs[lang].tag;
+
+            log::warn!("lang: {} has no key '{}', falling back", tag, key);
+            metrics::record_missing_key(tag);
+        }
+    }
+}
+
+/// Names of the `{name}` and `{name:…}` slots.
+fn placeholders(text: &str) -> BTreeSet<&str> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(slot, _)| slot))
+        .map(|slot| slot.split_once(':').map_or(slot, |(name, _)| name))
+        .filter(|name| is_slot_name(name))
+        .collect()
+}
+
+/// `InlineAlias` of `InlineAlias_Flag`: a word may be in one key per group.
+fn alias_group(key: &str) -> Option<&str> {
+    let group = key.split('_').next()?;
+    group.ends_with("Alias").then_some(group)
+}
+
+fn aliases(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim)
+}
+
+/// Whether `word` is one of the aliases under `key` in any language, so
+/// that the ones a user knows work whatever language is set.
+pub fn is_alias(key: &str, word: &str) -> bool {
+    current().is_some_and(|s| {
+        s.langs
+            .iter()
+            .filter_map(|lang| lang.map.get(key))
+            .any(|value| aliases(value).any(|alias| alias == word))
+    })
+}
+
+/// `(name, forms)` of every `{name:forms}` slot.
+fn plural_blocks(text: &str) -> impl Iterator<Item = (&str, &str)> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|part| part.split_once('}').map(|(slot, _)| slot))
+        .filter_map(|slot| slot.split_once(':'))
+        .filter(|(name, _)| is_slot_name(name))
+}
+
+fn is_slot_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// CLDR plural categories; only the ones our languages use.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, IntoStaticStr, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum PluralCategory {
+    One,
+    Few,
+    Many,
+    Other,
+}
+
+/// How a language picks the [`PluralCategory`] of an integer.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PluralRule {
+    /// uk, ru: 1, 21, 31… / 2-4, 22-24… / the rest. `other` is for
+    /// fractions, which we never count.
+    EastSlavic,
+    /// en, az and anything we have no rule for: 1 / the rest.
+    OneOther,
+}
+
+impl PluralRule {
+    pub fn for_tag(tag: &str) -> Self {
+        match tag {
+            "uk" | "ru" => Self::EastSlavic,
+            _ => Self::OneOther,
+        }
+    }
+
+    pub fn category(self, n: i64) -> PluralCategory {
+        // `unsigned_abs` over `abs` so `i64::MIN` cannot overflow; a loss
+        // of 5 kg reads like a gain of 5.
+        let magnitude = n.unsigned_abs();
+
+        match self {
+            Self::EastSlavic => {
+                let (last_digit, last_two) = (magnitude % 10, magnitude % 100);
+
+                if last_digit == 1 && last_two != 11 {
+                    PluralCategory::One
+                } else if (2..=4).contains(&last_digit)
+                    && !(12..=14).contains(&last_two)
+                {
+                    PluralCategory::Few
+                } else {
+                    PluralCategory::Many
+                }
+            },
+            Self::OneOther if magnitude == 1 => PluralCategory::One,
+            Self::OneOther => PluralCategory::Other,
+        }
+    }
+
+    /// The categories an integer can land in, so every slot needs them.
+    pub const fn required(self) -> &'static [PluralCategory] {
+        match self {
+            Self::EastSlavic => &[
+                PluralCategory::One,
+                PluralCategory::Few,
+                PluralCategory::Many,
+            ],
+            Self::OneOther => &[PluralCategory::One, PluralCategory::Other],
+        }
+    }
+
+    fn problems(self, forms: &str) -> impl Iterator<Item = String> {
+        let mut known = vec![];
+        let mut problems = vec![];
+
+        for form in forms.split('|') {
+            let category = form.split_once('=').map(|(category, _)| category);
+
+            match category.map(PluralCategory::from_str) {
+                Some(Ok(category)) => known.push(category),
+                _ => problems.push(format!("has a bad plural form {form:?}")),
+            }
+        }
+
+        for category in self.required() {
+            if !known.contains(category) {
+                let category: &str = category.into();
+                problems.push(format!("lacks the {category} form"));
+            }
+        }
+
+        problems.into_iter()
+    }
+}
+
+/// The form for `category`, else `other`, else the first one.
+fn plural_form(forms: &str, category: PluralCategory) -> &str {
+    let category: &str = category.into();
+    let mut fallback = "";
+
+    for form in forms.split('|') {
+        let (name, text) = form.split_once('=').unwrap_or(("", form));
+
+        if name == category {
+            return text;
+        }
+
+        if name == "other" || fallback.is_empty() {
+            fallback = text;
+        }
+    }
+
+    fallback
+}
+
+fn current() -> Option<Arc<Locale>> {
+    LANG.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+pub fn set_locale(locale: Locale) {
+    *LANG.write().unwrap_or_else(PoisonError::into_inner) =
+        Some(Arc::new(locale));
+
+    // The new files may have the keys, or lack others
+    REPORTED_MISSES.lock().unwrap_or_else(PoisonError::into_inner).clear();
+}
+
+/// Loads `locales/` again and swaps it in. The languages must stay the
+/// same: a [`LocaleTag`] is an index, and requests in flight hold some.
+pub fn reload() -> Result<(), LocaleError> {
+    let locale = Locale::new(DEFAULT_LANG_TAG)?;
+    let mut problems = locale.check();
+
+    if let Some(current) = current()
+        && current.tags() != locale.tags()
+    {
+        problems.push(format!(
+            "languages changed from {:?} to {:?}, that needs a restart",
+            current.tags(),
+            locale.tags()
+        ));
+    }
+
+    if !problems.is_empty() {
+        return Err(LocaleError { problems });
+    }
 
-        let info = langs.iter().fold(String::from("Loaded lang:"), |acc, l| {
-            format!("{} {}", acc, l.tag)
-        });
-        log::info!("{} | default: {}", info, set_def_tag);
+    set_locale(locale);
 
-        Self { langs, def_tag }
+    Ok(())
+}
+
+#[cfg(unix)]
+pub async fn run_reload_on_sighup() {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(err) => {
+            log::error!("Locales: no SIGHUP handler: {:?}", err);
+            return;
+        },
+    };
+
+    while hangups.recv().await.is_some() {
+        match reload() {
+            Ok(()) => log::info!("Locales reloaded on SIGHUP"),
+            Err(err) => crate::myerr!("Locales not reloaded on SIGHUP: {err}"),
+        }
     }
 }
 
@@ -112,6 +560,10 @@ pub trait InnerLang {
     fn args<T>(self, hash_args: &[(&str, T)]) -> String
     where
         T: std::fmt::Display;
+
+    /// Fills `{key}` with `n` and `{key:…}` with the form `n` takes in the
+    /// language of `tag`.
+    fn plural(self, tag: LocaleTag, key: &str, n: i64) -> String;
 }
 
 impl InnerLang for String {
@@ -130,26 +582,55 @@ impl InnerLang for String {
         }
         self
     }
+
+    fn plural(self, tag: LocaleTag, key: &str, n: i64) -> String {
+        let rule = current()
+            .and_then(|s| {
+                s.langs.get(tag.lang).map(|l| PluralRule::for_tag(&l.tag))
+            })
+            .unwrap_or(PluralRule::OneOther);
+        let category = rule.category(n);
+
+        let open = format!("{{{key}:");
+        let mut out = String::with_capacity(self.len());
+        let mut rest = self.as_str();
+
+        while let Some(start) = rest.find(&open) {
+            let Some(len) = rest[start..].find('}') else { break };
+            let forms = &rest[start + open.len()..start + len];
+
+            out.push_str(&rest[..start]);
+            out.push_str(plural_form(forms, category));
+            rest = &rest[start + len + 1..];
+        }
+
+        out.push_str(rest);
+        out.args(&[(key, n)])
+    }
 }
 
 #[inline]
-pub fn lng(key: &str, tag: LocaleTag) -> String {
-    let s = LANG.get().expect("Lang is not set!");
+pub fn lng(key: &str, tag: impl Into<LocaleTag>) -> String {
+    let tag = tag.into();
+    let s = current().expect("Lang is not set!");
 
-    if tag >= s.langs.len() {
+    if tag.lang >= s.langs.len() {
         return format!(
             "lang: too big tag '{}' for langs '{}'",
-            tag,
+            tag.lang,
             s.langs.len()
         );
     }
 
-    let res = &s.langs[tag].map;
-
-    let Some(res) = res.get(key) else {
+    let Some((res, from)) = s.text(key, tag) else {
+        s.report_miss(key, tag.lang);
         return format!("lang: key '{}' not found", key);
     };
 
+    if from != tag.lang {
+        s.report_miss(key, tag.lang);
+    }
+
     res.to_owned()
 }
 
@@ -175,15 +656,13 @@ pub fn tag_one_two_or(
     second_opt_tag: Option<&str>,
     fallback_tag: &str,
 ) -> LocaleTag {
-    if let Some(tag) = tag_opt(first_opt_tag) {
-        return tag;
-    }
+    let tag = tag_opt(first_opt_tag)
+        .or_else(|| tag_opt(second_opt_tag))
+        .unwrap_or_else(|| tag(fallback_tag));
 
-    if let Some(tag) = tag_opt(second_opt_tag) {
-        return tag;
-    }
-
-    tag(fallback_tag)
+    // A key the picked language lacks: chat lang, then user lang, then the
+    // default. The client language is only a pick, never a fallback.
+    tag.with_fallback(&[second_opt_tag, first_opt_tag])
 }
 
 /// Priority by "if exists"
@@ -195,27 +674,49 @@ pub fn tag_one_or(
     first_opt_tag: Option<&str>,
     fallback_tag: &str,
 ) -> LocaleTag {
-    tag_opt(first_opt_tag).unwrap_or_else(|| tag(fallback_tag))
+    tag_opt(first_opt_tag)
+        .unwrap_or_else(|| tag(fallback_tag))
+        .with_fallback(&[first_opt_tag])
 }
 
 #[inline]
 pub fn tag_opt(opt_tag: Option<&str>) -> Option<LocaleTag> {
     let tag = opt_tag?;
 
-    let s = LANG.get()?;
+    let s = current()?;
 
-    s.langs.binary_search_by_key(&tag, |elem| &elem.tag).ok()
+    s.langs
+        .binary_search_by_key(&tag, |elem| &elem.tag)
+        .ok()
+        .map(LocaleTag::from)
 }
 
 #[inline]
 pub fn tag(tag: &str) -> LocaleTag {
-    let Some(s) = LANG.get() else { return 0 };
+    let Some(s) = current() else { return LocaleTag::from(0) };
+
+    s.langs
+        .binary_search_by_key(&tag, |elem| &elem.tag)
+        .unwrap_or(s.def_tag)
+        .into()
+}
 
-    s.langs.binary_search_by_key(&tag, |elem| &elem.tag).unwrap_or(s.def_tag)
+/// Whether `tag`'s own language has `key`, without any fallback.
+pub fn has_key(key: &str, tag: impl Into<LocaleTag>) -> bool {
+    let tag = tag.into();
+
+    current().is_some_and(|s| {
+        s.langs.get(tag.lang).is_some_and(|lang| lang.map.contains_key(key))
+    })
+}
+
+/// `false` until the locales are set.
+pub fn is_loaded() -> bool {
+    current().is_some()
 }
 
 pub fn get_langs() -> Vec<String> {
-    let s = LANG.get().expect("No langs set currently!");
+    let s = current().expect("No langs set currently!");
 
     s.langs.iter().map(|item| item.tag.clone()).collect()
 }
@@ -281,7 +782,7 @@ mod tests {
 
         for known in ["uk", "en", "ru", "az"] {
             assert_eq!(
-                tag_opt(Some(known)).map(|t| get_langs()[t].clone()),
+                tag_opt(Some(known)).map(|t| get_langs()[t.index()].clone()),
                 Some(known.to_owned())
             );
         }
@@ -297,18 +798,38 @@ mod tests {
     fn the_language_priority_chain_prefers_user_then_chat_then_client() {
         init_lang();
 
-        let user = tag("en");
-        let chat = tag("ru");
-        let client = tag("az");
+        let pick = |a, b, c| tag_one_two_or(a, b, c).index();
+
+        let user = tag("en").index();
+        let chat = tag("ru").index();
+        let client = tag("az").index();
 
         // User setting wins.
-        assert_eq!(tag_one_two_or(Some("en"), Some("ru"), "az"), user);
+        assert_eq!(pick(Some("en"), Some("ru"), "az"), user);
         // No user setting: chat wins.
-        assert_eq!(tag_one_two_or(None, Some("ru"), "az"), chat);
+        assert_eq!(pick(None, Some("ru"), "az"), chat);
         // Neither: the Telegram client language.
-        assert_eq!(tag_one_two_or(None, None, "az"), client);
+        assert_eq!(pick(None, None, "az"), client);
         // Unknown client language: the default.
-        assert_eq!(tag_one_two_or(None, None, "zz"), tag(DEFAULT_LANG_TAG));
+        assert_eq!(pick(None, None, "zz"), tag(DEFAULT_LANG_TAG).index());
+    }
+
+    #[test]
+    fn a_missing_key_falls_back_to_the_chat_then_the_user_language() {
+        init_lang();
+
+        let fallback = |a, b, c| tag_one_two_or(a, b, c).fallback;
+        let (ru, az) = (Some(tag("ru").lang), Some(tag("az").lang));
+
+        assert_eq!(fallback(Some("en"), Some("ru"), "az"), ru);
+        assert_eq!(fallback(Some("ru"), Some("az"), "en"), az);
+        // Nothing else to try: straight to the default, never to the
+        // client language.
+        assert_eq!(fallback(Some("en"), None, "az"), None);
+        assert_eq!(fallback(None, Some("ru"), "az"), None);
+        assert_eq!(fallback(None, None, "az"), None);
+        assert_eq!(fallback(Some("en"), Some("en"), "en"), None);
+        assert_eq!(tag_one_or(Some("en"), "az").fallback, None);
     }
 
     #[test]
@@ -316,8 +837,11 @@ mod tests {
         init_lang();
 
         // "zz" is not a locale, so it is skipped and the chat setting applies.
-        assert_eq!(tag_one_two_or(Some("zz"), Some("ru"), "az"), tag("ru"));
-        assert_eq!(tag_one_or(Some("zz"), "az"), tag("az"));
+        assert_eq!(
+            tag_one_two_or(Some("zz"), Some("ru"), "az").index(),
+            tag("ru").index()
+        );
+        assert_eq!(tag_one_or(Some("zz"), "az").index(), tag("az").index());
     }
 
     #[test]
@@ -354,7 +878,7 @@ mod tests {
     fn lng_returns_a_marker_string_for_an_out_of_range_tag() {
         init_lang();
 
-        let out = lng("HelpMessage", 9_999);
+        let out = lng("HelpMessage", 9_999usize);
         assert!(out.starts_with("lang: too big tag"), "{out}");
     }
 
@@ -366,4 +890,224 @@ mod tests {
         assert!(!text.starts_with("lang:"), "{text}");
         assert!(!text.is_empty());
     }
+
+    #[test]
+    fn the_east_slavic_rule_picks_one_few_or_many() {
+        use PluralCategory::{Few, Many, One};
+
+        let ones = [1, 21, 31, 101, 121];
+        let few = [2, 3, 4, 22, 23, 24, 102];
+        let many = [0, 5, 6, 9, 10, 11, 12, 13, 14, 15, 19, 20, 25, 100, 111];
+
+        let category = |n| PluralRule::EastSlavic.category(n);
+
+        for n in ones {
+            assert_eq!(category(n), One, "n = {n}");
+        }
+        for n in few {
+            assert_eq!(category(n), Few, "n = {n}");
+        }
+        for n in many {
+            assert_eq!(category(n), Many, "n = {n}");
+        }
+    }
+
+    #[test]
+    fn the_one_other_rule_only_singles_out_one() {
+        assert_eq!(PluralRule::OneOther.category(1), PluralCategory::One);
+
+        for n in [0, 2, 11, 21, 101] {
+            assert_eq!(PluralRule::OneOther.category(n), PluralCategory::Other);
+        }
+    }
+
+    #[test]
+    fn plural_categories_go_by_magnitude_without_overflowing() {
+        for rule in [PluralRule::EastSlavic, PluralRule::OneOther] {
+            for n in 1..500i64 {
+                assert_eq!(rule.category(-n), rule.category(n), "{n}");
+            }
+
+            // `unsigned_abs`, not `abs`: `i64::MIN.abs()` would panic.
+            for n in [i64::MIN, i64::MIN + 1, i64::MAX] {
+                rule.category(n);
+            }
+        }
+    }
+
+    #[test]
+    fn plural_fills_the_count_and_its_form() {
+        init_lang();
+
+        let text = "{n} {n:one=годину|few=години|many=годин}".to_owned();
+        let cases = [(1, "1 годину"), (3, "3 години"), (11, "11 годин")];
+
+        for (n, expected) in cases {
+            assert_eq!(text.clone().plural(tag("uk"), "n", n), expected);
+        }
+
+        let text = "{n} {n:one=hour|other=hours}, {n:one=it|other=they}";
+        let out = text.to_owned().plural(tag("en"), "n", 2);
+        assert_eq!(out, "2 hours, they");
+    }
+
+    #[test]
+    fn plural_leaves_other_slots_alone() {
+        init_lang();
+
+        let out = "{a:one=x|other=y} {b}".to_owned().plural(tag("en"), "b", 1);
+        assert_eq!(out, "{a:one=x|other=y} 1");
+    }
+
+    #[test]
+    fn a_missing_form_falls_back_to_other() {
+        assert_eq!(plural_form("one=a|other=b", PluralCategory::Many), "b");
+        assert_eq!(plural_form("one=a|few=b", PluralCategory::Many), "a");
+    }
+
+    #[test]
+    fn placeholders_are_the_named_slots_only() {
+        let found = placeholders("{name} has {weight} kg {} {not a slot}");
+        assert_eq!(found, BTreeSet::from(["name", "weight"]));
+
+        let found = placeholders("{n} {n:one=kilogram|other=kilograms}");
+        assert_eq!(found, BTreeSet::from(["n"]));
+    }
+
+    fn locale_dir(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("hrundel-locales-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        for (tag, json) in files {
+            fs::write(dir.join(format!("{tag}.json")), json).unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn a_broken_locale_is_reported_instead_of_panicking() {
+        let dir = locale_dir(
+            "broken",
+            &[("uk", r#"{"A": "a", "B": 2}"#), ("en", "not json")],
+        );
+
+        let err = Locale::load_from(&dir, "uk").unwrap_err();
+        assert_eq!(err.problems.len(), 2, "{err}");
+        assert!(err.problems.iter().any(|p| p.contains("B is not a string")));
+
+        let dir = locale_dir("no-default", &[("en", r#"{"A": "a"}"#)]);
+        let err = Locale::load_from(&dir, "uk").unwrap_err();
+        assert_eq!(err.problems, ["no default locale uk"]);
+    }
+
+    #[test]
+    fn check_names_missing_unknown_and_mismatched_keys() {
+        let dir = locale_dir(
+            "check",
+            &[
+                ("uk", r#"{"A": "{name} a", "B": "b"}"#),
+                (
+                    "en",
+                    r#"{"A": "{nmae} a", "C": "c", "AUTHOR_OF_THIS_TRANSLATION": "x"}"#,
+                ),
+            ],
+        );
+
+        let problems = Locale::load_from(&dir, "uk").unwrap().check();
+
+        assert_eq!(problems.len(), 3, "{problems:?}");
+        assert!(problems[0].starts_with("en: A has placeholders"));
+        assert_eq!(problems[1], "en: B is missing");
+        assert_eq!(problems[2], "en: C is not in uk");
+    }
+
+    #[test]
+    fn check_names_missing_and_unknown_plural_forms() {
+        let dir = locale_dir(
+            "plural",
+            &[
+                ("uk", r#"{"A": "{n} {n:one=год|many=годин}"}"#),
+                ("en", r#"{"A": "{n} {n:one=hour|others=hours}"}"#),
+            ],
+        );
+
+        let problems = Locale::load_from(&dir, "uk").unwrap().check();
+
+        assert_eq!(
+            problems,
+            [
+                r#"en: A {n} has a bad plural form "others=hours""#,
+                "en: A {n} lacks the other form",
+                "uk: A {n} lacks the few form",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_names_untypable_and_ambiguous_aliases() {
+        let dir = locale_dir(
+            "alias",
+            &[
+                (
+                    "uk",
+                    r#"{"TestAlias_A": "а, x", "TestAlias_B": "б,", "C": "x"}"#,
+                ),
+                (
+                    "en",
+                    r#"{"TestAlias_A": "a", "TestAlias_B": "x, b b", "C": "x"}"#,
+                ),
+            ],
+        );
+
+        let problems = Locale::load_from(&dir, "uk").unwrap().check();
+
+        // The languages are read in tag order, so uk is the one in conflict.
+        assert_eq!(
+            problems,
+            [
+                r#"en: TestAlias_B has an alias "b b" nobody can type"#,
+                r#"uk: TestAlias_A and TestAlias_B both have the alias "x""#,
+                r#"uk: TestAlias_B has an alias "" nobody can type"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn an_alias_works_whatever_language_it_comes_from() {
+        init_lang();
+
+        for word in ["flag", "прапор", "флаг", "bayraq"] {
+            assert!(is_alias("InlineAlias_Flag", word), "{word}");
+        }
+        assert!(!is_alias("InlineAlias_Flag", "flag, bayraq"));
+        assert!(!is_alias("InlineAlias_Gif", "flag"));
+        assert!(!is_alias("NoSuchKey", "flag"));
+    }
+
+    #[test]
+    fn text_comes_from_the_language_then_its_fallback_then_the_default() {
+        let dir = locale_dir(
+            "fallback",
+            &[
+                ("uk", r#"{"A": "uk a", "B": "uk b", "C": "uk c"}"#),
+                ("ru", r#"{"A": "ru a", "B": "ru b"}"#),
+                ("en", r#"{"A": "en a"}"#),
+            ],
+        );
+        let locale = Locale::load_from(&dir, "uk").unwrap();
+        let index = |tag| locale.tags().iter().position(|t| *t == tag).unwrap();
+
+        let en = LocaleTag { lang: index("en"), fallback: Some(index("ru")) };
+
+        assert_eq!(locale.text("A", en), Some(("en a", index("en"))));
+        assert_eq!(locale.text("B", en), Some(("ru b", index("ru"))));
+        assert_eq!(locale.text("C", en), Some(("uk c", index("uk"))));
+        assert_eq!(locale.text("D", en), None);
+
+        let missing = [("en", vec!["B", "C"]), ("ru", vec!["C"])];
+        assert_eq!(locale.missing_keys(), missing);
+    }
 }
@@ -3,6 +3,7 @@ mod db;
 mod dispatch;
 mod enums;
 mod handlers;
+mod health;
 mod keyboards;
 mod lang;
 mod metrics;
@@ -22,7 +23,7 @@ use teloxide::prelude::*;
 use std::sync::Arc;
 
 use crate::{
-    config::{consts::BOT_PARSE_MODE, consts::GameState, env::BOT_CONFIG},
+    config::{consts::GameState, env::BOT_CONFIG},
     dispatch::build_handler,
     utils::{helpers::get_chat_kind, mylog},
 };
@@ -32,17 +33,32 @@ use crate::{
 async fn main() {
     dotenvy::dotenv().ok();
     pretty_env_logger::init_timed();
+
+    if std::env::args().any(|arg| arg == "--check-config") {
+        std::process::exit(setup::check_config());
+    }
+
     run().await
 }
 
 async fn run() {
     log::info!("Starting new version of @fr0staman_bot in Rust!");
 
-    let bot = Bot::from_env().parse_mode(BOT_PARSE_MODE);
+    setup::setup_config();
+
+    let token = &BOT_CONFIG.teloxide_token;
+    let client = teloxide::net::client_from_env();
+    let mut bot = Bot::with_client(token, client);
+    if let Some(api_url) = &BOT_CONFIG.api_url {
+        bot = bot.set_api_url(api_url.clone());
+    }
+    let bot = setup::setup_bot(bot);
 
     setup::setup_me(&bot).await;
     setup::setup_lang();
+    setup::setup_blocklist();
     setup::setup_db().await;
+    setup::setup_bans().await;
     setup::setup_commands(&bot).await;
 
     let listener =
@@ -52,6 +68,18 @@ async fn run() {
 
     let game_state = Arc::new(GameState::new());
 
+    tokio::spawn(services::inline_content::run_top_grunt_announcer(
+        bot.clone(),
+    ));
+    tokio::spawn(services::moderation::run_voice_backfill(bot.clone()));
+    tokio::spawn(services::broadcast::run_broadcast_worker(bot.clone()));
+    tokio::spawn(services::notifications::run_notifier(bot.clone()));
+    tokio::spawn(services::user_moderation::run_ban_refresher());
+    tokio::spawn(mylog::run_error_digest());
+    tokio::spawn(metrics::run_game_gauges(game_state.clone()));
+    #[cfg(unix)]
+    tokio::spawn(lang::run_reload_on_sighup());
+
     Dispatcher::builder(bot, handler)
         .dependencies(dptree::deps![game_state])
         .default_handler(default_log_handler)
@@ -3,19 +3,86 @@
 
 use axum::{Router, body::Body, http::Request, routing::get};
 use axum_prometheus::PrometheusMetricLayer;
-use prometheus_client::{metrics::counter::Counter, registry::Registry};
-use std::sync::{LazyLock, OnceLock};
+use prometheus_client::{
+    encoding::EncodeLabelSet,
+    metrics::{
+        counter::Counter,
+        family::Family,
+        gauge::Gauge,
+        histogram::{Histogram, exponential_buckets},
+    },
+    registry::Registry,
+};
+use std::sync::{Arc, LazyLock, OnceLock};
+use std::time::{Duration, Instant};
 
-use crate::config::env::BOT_CONFIG;
+use crate::config::{consts::GameState, env::BOT_CONFIG};
+use crate::db::{DB, init::Database};
 
 static REGISTRY: OnceLock<Registry> = OnceLock::new();
 
-// Export special preconstructed counters for Teloxide's handlers.
-pub static INLINE_COUNTER: LazyLock<Counter<u64>> =
-    LazyLock::new(Counter::default);
+/// `handler` is the command, the callback action or the inline keyword;
+/// `outcome` is `ok` or `error`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct HandlerLabels {
+    handler: &'static str,
+    outcome: &'static str,
+}
 
-pub static CALLBACK_COUNTER: LazyLock<Counter<u64>> =
-    LazyLock::new(Counter::default);
+/// A count and a latency histogram for one kind of update.
+pub struct HandlerMetrics {
+    handled: Family<HandlerLabels, Counter>,
+    latency: Family<HandlerLabels, Histogram>,
+}
+
+impl HandlerMetrics {
+    fn new() -> Self {
+        Self {
+            handled: Family::default(),
+            latency: Family::new_with_constructor(latency_histogram),
+        }
+    }
+
+    /// Call once the handler is done; `started` is taken on entry.
+    pub fn observe(&self, handler: &'static str, ok: bool, started: Instant) {
+        let outcome = if ok { "ok" } else { "error" };
+        let labels = HandlerLabels { handler, outcome };
+
+        self.handled.get_or_create(&labels).inc();
+        self.latency
+            .get_or_create(&labels)
+            .observe(started.elapsed().as_secs_f64());
+    }
+
+    fn register(&self, registry: &mut Registry, name: &str, what: &str) {
+        registry.register(
+            name,
+            format!("count of handled {what}"),
+            self.handled.clone(),
+        );
+        registry.register(
+            format!("{name}_duration_seconds"),
+            format!("time to handle {what}"),
+            self.latency.clone(),
+        );
+    }
+}
+
+/// 5 ms to ~10 s: a cached reply up to a `/top` with charts over a slow
+/// network.
+fn latency_histogram() -> Histogram {
+    Histogram::new(exponential_buckets(0.005, 2.0, 12))
+}
+
+// Export special preconstructed metrics for Teloxide's handlers.
+pub static COMMANDS: LazyLock<HandlerMetrics> =
+    LazyLock::new(HandlerMetrics::new);
+
+pub static CALLBACKS: LazyLock<HandlerMetrics> =
+    LazyLock::new(HandlerMetrics::new);
+
+pub static INLINE_QUERIES: LazyLock<HandlerMetrics> =
+    LazyLock::new(HandlerMetrics::new);
 
 pub static MESSAGE_COUNTER: LazyLock<Counter<u64>> =
     LazyLock::new(Counter::default);
@@ -23,33 +90,144 @@ pub static MESSAGE_COUNTER: LazyLock<Counter<u64>> =
 pub static MESSAGE_HANDLED_COUNTER: LazyLock<Counter<u64>> =
     LazyLock::new(Counter::default);
 
-pub static CMD_START_COUNTER: LazyLock<Counter<u64>> =
+pub static UNHANDLED_COUNTER: LazyLock<Counter<u64>> =
     LazyLock::new(Counter::default);
 
-pub static CMD_HELP_COUNTER: LazyLock<Counter<u64>> =
-    LazyLock::new(Counter::default);
+// Sampled every `GAME_GAUGES_INTERVAL` by `run_game_gauges`.
+static DUEL_NUMBERS: LazyLock<Gauge> = LazyLock::new(Gauge::default);
+static RESET_VOTES: LazyLock<Gauge> = LazyLock::new(Gauge::default);
+static CHAT_PIGS: LazyLock<Gauge> = LazyLock::new(Gauge::default);
+static CHAT_PIGS_MASS: LazyLock<Gauge> = LazyLock::new(Gauge::default);
 
-pub static CMD_COUNTER: LazyLock<Counter<u64>> =
-    LazyLock::new(Counter::default);
+const GAME_GAUGES_INTERVAL: Duration = Duration::from_secs(30);
 
-pub static UNHANDLED_COUNTER: LazyLock<Counter<u64>> =
+/// Where kilograms enter or leave the game.
+#[derive(Clone, Copy)]
+pub enum KgSource {
+    Grow,
+    Duel,
+    DailyGift,
+    SubscriptionGift,
+    ContentReward,
+}
+
+impl KgSource {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Grow => "grow",
+            Self::Duel => "duel",
+            Self::DailyGift => "daily_gift",
+            Self::SubscriptionGift => "subscription_gift",
+            Self::ContentReward => "content_reward",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct KgLabels {
+    source: &'static str,
+}
+
+static KG_CREATED: LazyLock<Family<KgLabels, Counter>> =
+    LazyLock::new(Family::default);
+static KG_DESTROYED: LazyLock<Family<KgLabels, Counter>> =
+    LazyLock::new(Family::default);
+
+/// A gain counts as created, a loss as destroyed.
+pub fn record_kg(source: KgSource, change: i32) {
+    let labels = KgLabels { source: source.label() };
+    let amount = change.unsigned_abs() as u64;
+
+    if change > 0 {
+        KG_CREATED.get_or_create(&labels).inc_by(amount);
+    } else if change < 0 {
+        KG_DESTROYED.get_or_create(&labels).inc_by(amount);
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct LocaleLabels {
+    lang: String,
+}
+
+static LOCALE_MISSING_KEYS: LazyLock<Family<LocaleLabels, Counter>> =
+    LazyLock::new(Family::default);
+
+/// Once per key `lang` lacked, however often it is asked for.
+pub fn record_missing_key(lang: &str) {
+    let labels = LocaleLabels { lang: lang.to_owned() };
+    LOCALE_MISSING_KEYS.get_or_create(&labels).inc();
+}
+
+pub async fn run_game_gauges(game_state: Arc<GameState>) {
+    let mut interval = tokio::time::interval(GAME_GAUGES_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        DUEL_NUMBERS.set(game_state.duel_list.read().await.len() as i64);
+        RESET_VOTES.set(game_state.reset_votes.read().await.len() as i64);
+
+        match DB.chat_pig.count_chat_pigs().await {
+            Ok(count) => CHAT_PIGS.set(count),
+            Err(err) => log::error!("Metrics: chat pigs count: {:?}", err),
+        };
+
+        match DB.chat_pig.sum_chat_pig_mass().await {
+            Ok(mass) => CHAT_PIGS_MASS.set(mass),
+            Err(err) => log::error!("Metrics: chat pigs mass: {:?}", err),
+        };
+    }
+}
+
+pub static THROTTLED_USER_COUNTER: LazyLock<Counter<u64>> =
     LazyLock::new(Counter::default);
 
-pub static DUEL_NUMBERS: LazyLock<Counter<u64>> =
+pub static THROTTLED_CHAT_COUNTER: LazyLock<Counter<u64>> =
     LazyLock::new(Counter::default);
 
+pub static TG_QUEUE_DEPTH: LazyLock<Gauge> = LazyLock::new(Gauge::default);
+
+/// `api` is `chat_pig`, `hand_pig` or `other`; `method` the function on it.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct QueryLabels {
+    pub api: &'static str,
+    pub method: &'static str,
+}
+
+/// How long a connection was held, from checkout to its return. The wait
+/// for the checkout itself shows up in [`DB_POOL_WAITING`] instead.
+pub static DB_QUERY_DURATION: LazyLock<Family<QueryLabels, Histogram>> =
+    LazyLock::new(|| {
+        Family::new_with_constructor(|| {
+            Histogram::new(exponential_buckets(0.001, 2.0, 12))
+        })
+    });
+
+// Set from the pool's status on every scrape.
+static DB_POOL_MAX_SIZE: LazyLock<Gauge> = LazyLock::new(Gauge::default);
+static DB_POOL_SIZE: LazyLock<Gauge> = LazyLock::new(Gauge::default);
+static DB_POOL_AVAILABLE: LazyLock<Gauge> = LazyLock::new(Gauge::default);
+static DB_POOL_WAITING: LazyLock<Gauge> = LazyLock::new(Gauge::default);
+
+fn observe_db_pool() {
+    let status = Database::get_or_init_pool().status();
+
+    DB_POOL_MAX_SIZE.set(status.max_size as i64);
+    DB_POOL_SIZE.set(status.size as i64);
+    DB_POOL_AVAILABLE.set(status.available as i64);
+    DB_POOL_WAITING.set(status.waiting as i64);
+}
+
 pub fn init() -> axum::Router {
     let mut prometheus = Registry::default();
 
-    prometheus.register(
-        "inline_usage",
-        "count of inline queries processed by the bot",
-        INLINE_COUNTER.clone(),
-    );
-    prometheus.register(
-        "callback",
-        "count of callbacks",
-        CALLBACK_COUNTER.clone(),
+    COMMANDS.register(&mut prometheus, "commands", "commands");
+    CALLBACKS.register(&mut prometheus, "callbacks", "callback queries");
+    INLINE_QUERIES.register(
+        &mut prometheus,
+        "inline_queries",
+        "inline queries",
     );
     prometheus.register(
         "message_usage",
@@ -64,33 +242,95 @@ pub fn init() -> axum::Router {
     );
 
     prometheus.register(
-        "command_start_usage",
-        "count of /start invocations",
-        CMD_START_COUNTER.clone(),
+        "unhandled",
+        "count of unhandled updates",
+        UNHANDLED_COUNTER.clone(),
     );
 
     prometheus.register(
-        "command_help_usage",
-        "count of /help invocations",
-        CMD_HELP_COUNTER.clone(),
+        "duel_numbers",
+        "Active duels on time",
+        DUEL_NUMBERS.clone(),
     );
 
     prometheus.register(
-        "command_all_usage",
-        "count of commands invocations",
-        CMD_COUNTER.clone(),
+        "reset_votes",
+        "open /resetpigs votes",
+        RESET_VOTES.clone(),
     );
 
+    prometheus.register("chat_pigs", "chat pigs in total", CHAT_PIGS.clone());
+
     prometheus.register(
-        "unhandled",
-        "count of unhandled updates",
-        UNHANDLED_COUNTER.clone(),
+        "chat_pigs_mass",
+        "kg of all chat pigs together",
+        CHAT_PIGS_MASS.clone(),
     );
 
     prometheus.register(
-        "duel_numbers",
-        "Active duels on time",
-        DUEL_NUMBERS.clone(),
+        "kg_created",
+        "kg gained by pigs, by source",
+        KG_CREATED.clone(),
+    );
+
+    prometheus.register(
+        "kg_destroyed",
+        "kg lost by pigs, by source",
+        KG_DESTROYED.clone(),
+    );
+
+    prometheus.register(
+        "locale_missing_keys",
+        "keys a language lacked and took from a fallback, by language",
+        LOCALE_MISSING_KEYS.clone(),
+    );
+
+    prometheus.register(
+        "throttled_user",
+        "count of commands dropped by the per-user rate limit",
+        THROTTLED_USER_COUNTER.clone(),
+    );
+
+    prometheus.register(
+        "throttled_chat",
+        "count of commands dropped by the per-chat rate limit",
+        THROTTLED_CHAT_COUNTER.clone(),
+    );
+
+    prometheus.register(
+        "telegram_queue_depth",
+        "requests waiting on Telegram's limits while the queue is full",
+        TG_QUEUE_DEPTH.clone(),
+    );
+
+    prometheus.register(
+        "db_query_duration_seconds",
+        "time a database API method held its connection",
+        DB_QUERY_DURATION.clone(),
+    );
+
+    prometheus.register(
+        "db_pool_max_size",
+        "connections the pool may open",
+        DB_POOL_MAX_SIZE.clone(),
+    );
+
+    prometheus.register(
+        "db_pool_size",
+        "connections currently open",
+        DB_POOL_SIZE.clone(),
+    );
+
+    prometheus.register(
+        "db_pool_available",
+        "open connections not in use",
+        DB_POOL_AVAILABLE.clone(),
+    );
+
+    prometheus.register(
+        "db_pool_waiting",
+        "tasks waiting for a connection",
+        DB_POOL_WAITING.clone(),
     );
 
     REGISTRY.set(prometheus).unwrap();
@@ -104,6 +344,7 @@ pub fn init() -> axum::Router {
         {
             if auth.len() > 7 && auth[7..] == BOT_CONFIG.prometheus_token {
                 log::info!("Metrics: captured data");
+                observe_db_pool();
                 let mut buf = String::new();
 
                 match prometheus_client::encoding::text::encode(
@@ -129,3 +370,22 @@ pub fn init() -> axum::Router {
         .route("/metrics", get(metrics_endpoint))
         .layer(prometheus_layer)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_gain_is_created_and_a_loss_destroyed() {
+        let labels = KgLabels { source: KgSource::Duel.label() };
+        let created = || KG_CREATED.get_or_create(&labels).get();
+        let destroyed = || KG_DESTROYED.get_or_create(&labels).get();
+        let before = (created(), destroyed());
+
+        record_kg(KgSource::Duel, 30);
+        record_kg(KgSource::Duel, -12);
+        record_kg(KgSource::Duel, 0);
+
+        assert_eq!((created(), destroyed()), (before.0 + 30, before.1 + 12));
+    }
+}
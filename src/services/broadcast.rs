@@ -0,0 +1,508 @@
+use std::{sync::LazyLock, time::Duration};
+
+use ahash::AHashMap;
+use chrono::NaiveDateTime;
+use teloxide::{
+    ApiError, RequestError,
+    prelude::Requester,
+    types::{ChatId, MessageId},
+};
+use tokio::sync::Notify;
+
+use crate::{
+    config::consts::{
+        BROADCAST_DONE, BROADCAST_RUNNING, BROADCAST_TARGET_CHAT,
+        BROADCAST_TARGET_CLAIMED, BROADCAST_TARGET_FAILED,
+        BROADCAST_TARGET_PENDING, BROADCAST_TARGET_SENT, BROADCAST_TARGET_USER,
+        DEFAULT_LANG_TAG,
+    },
+    db::{
+        DB,
+        models::{Broadcast, Groups, UpdateGroups, User, UserStatus},
+    },
+    lang::{InnerLang, lng, tag_one_or},
+    types::{MyBot, MyResult},
+    utils::date::get_datetime,
+};
+
+/// Pending targets fetched per query. The job status is re-read between
+/// batches, so this is also how many sends a pause or cancel can lag by.
+const BATCH_SIZE: i64 = 20;
+/// Between two deliveries. The bot adaptor alone would let a broadcast take
+/// the whole global limit; this leaves room for replies to live updates.
+const SEND_THROTTLE: Duration = Duration::from_millis(333);
+/// How long an idle worker sleeps when nobody wakes it; only matters if a
+/// wake-up is lost, e.g. a job resumed from another replica.
+const IDLE_POLL: Duration = Duration::from_secs(60);
+/// A batch takes `BATCH_SIZE` × `SEND_THROTTLE` plus whatever the bot
+/// adaptor waits out; a claim older than this is from a worker that died.
+const CLAIM_TIMEOUT: Duration = Duration::from_secs(600);
+
+static WAKE: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// Per kind and delivery status, from `get_broadcast_progress`.
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct BroadcastProgress {
+    pub chats_sent: i64,
+    pub chats_failed: i64,
+    pub users_sent: i64,
+    pub users_failed: i64,
+    pub pending: i64,
+}
+
+impl BroadcastProgress {
+    pub fn from_counts(counts: &[(i16, i16, i64)]) -> Self {
+        let mut progress = Self::default();
+
+        for &(kind, status, count) in counts {
+            let slot = match (kind, status) {
+                (_, BROADCAST_TARGET_PENDING | BROADCAST_TARGET_CLAIMED) => {
+                    &mut progress.pending
+                },
+                (BROADCAST_TARGET_CHAT, BROADCAST_TARGET_SENT) => {
+                    &mut progress.chats_sent
+                },
+                (BROADCAST_TARGET_CHAT, _) => &mut progress.chats_failed,
+                (_, BROADCAST_TARGET_SENT) => &mut progress.users_sent,
+                _ => &mut progress.users_failed,
+            };
+            *slot += count;
+        }
+
+        progress
+    }
+
+    pub fn total(&self) -> i64 {
+        self.chats_sent
+            + self.chats_failed
+            + self.users_sent
+            + self.users_failed
+            + self.pending
+    }
+}
+
+/// Who a `/repost` goes to, parsed from its flags. Filters only narrow the
+/// kinds picked with `+chats` / `+dm`; ones that don't apply to a kind are
+/// ignored for it.
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct Audience {
+    pub chats: bool,
+    pub users: bool,
+    /// `--lang=uk`: the language forced with `!epyc` / `/lang`, so chats and
+    /// users that never picked one are left out.
+    pub lang: Option<String>,
+    /// `--min-pigs=N`, chats only.
+    pub min_pigs: Option<u32>,
+    /// `--days=N`, users only: started the bot within the last N days.
+    pub new_days: Option<u32>,
+    /// `--dry-run`: report the audience instead of queueing a job.
+    pub dry_run: bool,
+}
+
+impl Audience {
+    /// `Err` is the first word that isn't a known flag or has a bad value.
+    pub fn parse(arg: &str) -> Result<Self, &str> {
+        let mut audience = Self::default();
+
+        for word in arg.split_whitespace() {
+            let (flag, value) = word.split_once('=').unwrap_or((word, ""));
+
+            match (flag, value) {
+                ("+chats", "") => audience.chats = true,
+                ("+dm", "") => audience.users = true,
+                ("--dry-run", "") => audience.dry_run = true,
+                ("--lang", lang) if !lang.is_empty() => {
+                    audience.lang = Some(lang.to_owned())
+                },
+                ("--min-pigs", n) => {
+                    audience.min_pigs = Some(n.parse().map_err(|_| word)?)
+                },
+                ("--days", n) => {
+                    audience.new_days = Some(n.parse().map_err(|_| word)?)
+                },
+                _ => return Err(word),
+            }
+        }
+
+        Ok(audience)
+    }
+
+    /// Active chats and started, non-banned users that pass every filter.
+    /// `pigs` is `count_pigs_per_group`, only read with `--min-pigs`.
+    pub fn select<'a>(
+        &self,
+        chats: &'a [Groups],
+        pigs: &AHashMap<i32, i64>,
+        users: &'a [User],
+        now: NaiveDateTime,
+    ) -> (Vec<&'a Groups>, Vec<&'a User>) {
+        let lang_matches = |lang: &Option<String>| {
+            self.lang
+                .as_ref()
+                .is_none_or(|wanted| lang.as_ref() == Some(wanted))
+        };
+
+        let chats = chats
+            .iter()
+            .filter(|_| self.chats)
+            .filter(|c| c.active && lang_matches(&c.lang))
+            .filter(|c| {
+                self.min_pigs.is_none_or(|min| {
+                    pigs.get(&c.id).copied().unwrap_or(0) >= min as i64
+                })
+            })
+            .collect();
+
+        let since =
+            self.new_days.map(|days| now - chrono::Duration::days(days as i64));
+        let users = users
+            .iter()
+            .filter(|_| self.users)
+            .filter(|u| u.started && !u.banned && lang_matches(&u.lang))
+            .filter(|u| {
+                since.is_none_or(|since| {
+                    u.started_at.is_some_and(|at| at >= since)
+                })
+            })
+            .collect();
+
+        (chats, users)
+    }
+}
+
+/// Call after creating or resuming a job so the worker doesn't wait out
+/// [`IDLE_POLL`].
+pub fn wake_worker() {
+    WAKE.notify_one();
+}
+
+/// Runs for the whole process lifetime; spawned from `main`. Jobs left
+/// running by a previous process are picked up first, from the first target
+/// not yet claimed. Every replica runs one; targets are claimed before they
+/// are sent, so none gets a message twice — except one claimed by a worker
+/// that crashed mid-batch, which is sent again after [`CLAIM_TIMEOUT`].
+pub async fn run_broadcast_worker(bot: MyBot) {
+    loop {
+        match DB.other.get_next_running_broadcast().await {
+            Ok(Some(job)) => {
+                if let Err(err) = run_job(&bot, &job).await {
+                    crate::myerr!("Broadcast [{}] failed: {:?}", job.id, err);
+                    tokio::time::sleep(IDLE_POLL).await;
+                }
+            },
+            Ok(None) => {
+                let _ = tokio::time::timeout(IDLE_POLL, WAKE.notified()).await;
+            },
+            Err(err) => {
+                crate::myerr!("Broadcast worker failed: {:?}", err);
+                tokio::time::sleep(IDLE_POLL).await;
+            },
+        }
+    }
+}
+
+async fn run_job(bot: &MyBot, job: &Broadcast) -> MyResult<()> {
+    loop {
+        let Some(current) = DB.other.get_broadcast(job.id).await? else {
+            return Ok(());
+        };
+        if current.status != BROADCAST_RUNNING {
+            log::info!("Broadcast [{}] stopped: {}", job.id, current.status);
+            return Ok(());
+        }
+
+        let now = get_datetime();
+        let stale_before =
+            now - chrono::Duration::seconds(CLAIM_TIMEOUT.as_secs() as i64);
+        let targets = DB
+            .other
+            .claim_broadcast_targets(job.id, BATCH_SIZE, now, stale_before)
+            .await?;
+        if targets.is_empty() {
+            // Another replica may still be sending its last batch.
+            if DB.other.has_claimed_broadcast_targets(job.id).await? {
+                tokio::time::sleep(SEND_THROTTLE * BATCH_SIZE as u32).await;
+                continue;
+            }
+            break;
+        }
+
+        for (chat_id, kind) in targets {
+            let status = deliver(bot, job, chat_id, kind).await;
+            DB.other
+                .set_broadcast_target_status(
+                    job.id,
+                    chat_id,
+                    status,
+                    get_datetime(),
+                )
+                .await?;
+            tokio::time::sleep(SEND_THROTTLE).await;
+        }
+    }
+
+    let finished = DB
+        .other
+        .change_broadcast_status(
+            job.id,
+            &[BROADCAST_RUNNING],
+            BROADCAST_DONE,
+            Some(get_datetime()),
+        )
+        .await?;
+
+    if finished {
+        report(bot, job).await?;
+    }
+
+    Ok(())
+}
+
+async fn deliver(bot: &MyBot, job: &Broadcast, chat_id: i64, kind: i16) -> i16 {
+    let from = ChatId(job.from_chat_id);
+    let message_id = MessageId(job.message_id);
+
+    // `RetryAfter` never gets here: the bot adaptor waits and resends.
+    let res = bot.forward_message(ChatId(chat_id), from, message_id).await;
+
+    match res {
+        Ok(_) => {
+            log::info!("Broadcast [{}] sent to [{}]", job.id, chat_id);
+            BROADCAST_TARGET_SENT
+        },
+        Err(err) => {
+            log::error!(
+                "Broadcast [{}] failed for [{}] {}",
+                job.id,
+                chat_id,
+                err
+            );
+            let deactivated = deactivate_target(chat_id, kind, &err).await;
+            if let Err(err) = deactivated {
+                log::error!("Failed to deactivate [{}] {}", chat_id, err);
+            }
+            BROADCAST_TARGET_FAILED
+        },
+    }
+}
+
+/// A chat that kicked the bot is deactivated and a user who blocked it is
+/// marked banned, so the next broadcast doesn't pick them up again.
+pub async fn deactivate_target(
+    chat_id: i64,
+    kind: i16,
+    err: &RequestError,
+) -> MyResult<()> {
+    if kind == BROADCAST_TARGET_USER {
+        let gone = matches!(
+            err,
+            RequestError::Api(
+                ApiError::UserDeactivated
+                    | ApiError::BotBlocked
+                    | ApiError::UserNotFound
+                    | ApiError::ChatNotFound
+            )
+        );
+        if !gone {
+            return Ok(());
+        }
+        let Some(user) = DB.other.get_user(chat_id).await? else {
+            return Ok(());
+        };
+
+        let user_status = UserStatus {
+            banned: true,
+            started: user.started,
+            supported: user.supported,
+            subscribed: user.subscribed,
+        };
+        DB.other.change_user_status(chat_id, user_status).await?;
+        log::info!("User already banned: {}", chat_id);
+    } else {
+        let gone = matches!(
+            err,
+            RequestError::Api(
+                ApiError::BotKicked
+                    | ApiError::BotKickedFromSupergroup
+                    | ApiError::BotKickedFromChannel
+                    | ApiError::ChatNotFound,
+            ) | RequestError::MigrateToChatId(_)
+        );
+        if !gone {
+            return Ok(());
+        }
+        let Some(chat) = DB.other.get_chat(chat_id).await? else {
+            return Ok(());
+        };
+
+        let chat_info = UpdateGroups { active: false, ..chat.to_update() };
+        DB.other.update_chat(chat_id, chat_info).await?;
+        log::info!("Chat already deactivated: {}", chat_id);
+    }
+
+    Ok(())
+}
+
+async fn report(bot: &MyBot, job: &Broadcast) -> MyResult<()> {
+    let counts = DB.other.get_broadcast_progress(job.id).await?;
+    let progress = BroadcastProgress::from_counts(&counts);
+
+    let creator = DB.other.get_user_by_id(job.created_by).await?;
+    let lang = creator.as_ref().and_then(|u| u.lang.as_deref());
+    let ltag = tag_one_or(lang, DEFAULT_LANG_TAG);
+
+    let text = lng("BroadcastFinished", ltag).args(&[
+        ("id", job.id as i64),
+        ("chats_sent", progress.chats_sent),
+        ("chats_failed", progress.chats_failed),
+        ("users_sent", progress.users_sent),
+        ("users_failed", progress.users_failed),
+    ]);
+    bot.send_message(ChatId(job.report_chat_id), text).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{datetime, group, user};
+
+    #[test]
+    fn audience_flags_parse() {
+        let audience = Audience::parse(
+            "+chats +dm --lang=uk --min-pigs=4 --days=7 --dry-run",
+        )
+        .unwrap();
+
+        assert_eq!(
+            audience,
+            Audience {
+                chats: true,
+                users: true,
+                lang: Some("uk".to_owned()),
+                min_pigs: Some(4),
+                new_days: Some(7),
+                dry_run: true,
+            }
+        );
+    }
+
+    #[test]
+    fn an_unknown_flag_or_a_bad_value_is_reported() {
+        assert_eq!(Audience::parse("+chats +everyone"), Err("+everyone"));
+        assert_eq!(Audience::parse("--min-pigs=many"), Err("--min-pigs=many"));
+        assert_eq!(Audience::parse("--days=-1"), Err("--days=-1"));
+        assert_eq!(Audience::parse("--lang="), Err("--lang="));
+        assert_eq!(Audience::parse("+dm=1"), Err("+dm=1"));
+    }
+
+    #[test]
+    fn without_filters_everyone_reachable_is_selected() {
+        let mut inactive = group(2, -100_002);
+        inactive.active = false;
+        let chats = [group(1, -100_001), inactive];
+        let mut banned = user(2, 1_002);
+        banned.banned = true;
+        let users = [user(1, 1_001), banned];
+
+        let audience = Audience::parse("+chats +dm").unwrap();
+        let (chats, users) = audience.select(
+            &chats,
+            &AHashMap::default(),
+            &users,
+            datetime(2026, 6, 1, 0, 0),
+        );
+
+        assert_eq!(chats.iter().map(|c| c.id).collect::<Vec<_>>(), [1]);
+        assert_eq!(users.iter().map(|u| u.id).collect::<Vec<_>>(), [1]);
+    }
+
+    #[test]
+    fn filters_narrow_each_kind() {
+        let mut uk_chat = group(1, -100_001);
+        uk_chat.lang = Some("uk".to_owned());
+        let mut small_uk_chat = group(2, -100_002);
+        small_uk_chat.lang = Some("uk".to_owned());
+        let chats = [uk_chat, small_uk_chat, group(3, -100_003)];
+        let pigs = [(1, 4), (2, 3), (3, 10)].into_iter().collect();
+
+        let mut new_uk_user = user(1, 1_001);
+        new_uk_user.lang = Some("uk".to_owned());
+        new_uk_user.started_at = Some(datetime(2026, 5, 30, 0, 0));
+        let mut old_uk_user = user(2, 1_002);
+        old_uk_user.lang = Some("uk".to_owned());
+        let users = [new_uk_user, old_uk_user, user(3, 1_003)];
+
+        let audience =
+            Audience::parse("+chats +dm --lang=uk --min-pigs=4 --days=7")
+                .unwrap();
+        let (chats, users) =
+            audience.select(&chats, &pigs, &users, datetime(2026, 6, 1, 0, 0));
+
+        assert_eq!(chats.iter().map(|c| c.id).collect::<Vec<_>>(), [1]);
+        assert_eq!(users.iter().map(|u| u.id).collect::<Vec<_>>(), [1]);
+    }
+
+    #[test]
+    fn a_kind_that_was_not_asked_for_is_empty() {
+        let chats = [group(1, -100_001)];
+        let users = [user(1, 1_001)];
+
+        let audience = Audience::parse("+dm").unwrap();
+        let (chats, users) = audience.select(
+            &chats,
+            &AHashMap::default(),
+            &users,
+            datetime(2026, 6, 1, 0, 0),
+        );
+
+        assert!(chats.is_empty());
+        assert_eq!(users.len(), 1);
+    }
+
+    #[test]
+    fn progress_splits_counts_by_kind_and_status() {
+        let counts = [
+            (BROADCAST_TARGET_CHAT, BROADCAST_TARGET_SENT, 5),
+            (BROADCAST_TARGET_CHAT, BROADCAST_TARGET_FAILED, 1),
+            (BROADCAST_TARGET_CHAT, BROADCAST_TARGET_PENDING, 2),
+            (BROADCAST_TARGET_USER, BROADCAST_TARGET_SENT, 7),
+            (BROADCAST_TARGET_USER, BROADCAST_TARGET_FAILED, 3),
+            (BROADCAST_TARGET_USER, BROADCAST_TARGET_PENDING, 4),
+            (BROADCAST_TARGET_USER, BROADCAST_TARGET_CLAIMED, 1),
+        ];
+
+        let progress = BroadcastProgress::from_counts(&counts);
+
+        assert_eq!(
+            progress,
+            BroadcastProgress {
+                chats_sent: 5,
+                chats_failed: 1,
+                users_sent: 7,
+                users_failed: 3,
+                pending: 7,
+            }
+        );
+        assert_eq!(progress.total(), 23);
+    }
+
+    #[test]
+    fn claimed_targets_are_still_pending() {
+        let counts = [
+            (BROADCAST_TARGET_CHAT, BROADCAST_TARGET_CLAIMED, 2),
+            (BROADCAST_TARGET_USER, BROADCAST_TARGET_CLAIMED, 3),
+        ];
+
+        assert_eq!(
+            BroadcastProgress::from_counts(&counts),
+            BroadcastProgress { pending: 5, ..Default::default() }
+        );
+    }
+
+    #[test]
+    fn no_counts_is_an_empty_progress() {
+        assert_eq!(BroadcastProgress::from_counts(&[]).total(), 0);
+    }
+}
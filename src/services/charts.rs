@@ -13,6 +13,9 @@ use crate::{
     utils::date::get_datetime,
 };
 
+/// Chat titles run up to 128 characters; the bars are far narrower.
+const GROWTH_CHART_LABEL_LEN: usize = 14;
+
 pub async fn generate_charts(
     data: Vec<(Game, Vec<GrowLog>)>,
     chat_name: String,
@@ -46,6 +49,51 @@ pub async fn generate_my_chart(
     recv.await.ok()?
 }
 
+/// One bar per chat: mass gained over the window `/stats` reports.
+pub async fn generate_growth_chart(
+    top: Vec<(String, i64)>,
+    ltag: LocaleTag,
+) -> Option<Vec<u8>> {
+    let title = lng("StatsChartTitle", ltag);
+    let (send, recv) = tokio::sync::oneshot::channel();
+
+    rayon::spawn(move || {
+        let encoded = generate_growth_chart_inner(top, title);
+        let _ = send.send(encoded);
+    });
+
+    recv.await.ok()?
+}
+
+fn generate_growth_chart_inner(
+    top: Vec<(String, i64)>,
+    title: String,
+) -> Option<Vec<u8>> {
+    let (titles, gains): (Vec<_>, Vec<_>) = top
+        .into_iter()
+        .map(|(t, gain)| {
+            let short: String =
+                t.chars().take(GROWTH_CHART_LABEL_LEN).collect();
+            (blank_wide_chars(&short), gain as f32)
+        })
+        .unzip();
+
+    let mut series = Series::new(String::new(), gains);
+    series.label_show = true;
+
+    let mut bar_chart =
+        BarChart::new_with_theme(vec![series], titles, THEME_GRAFANA);
+
+    bar_chart.legend_show = Some(false);
+    bar_chart.margin = Box { top: 20.0, bottom: 10.0, left: 10.0, right: 10.0 };
+    bar_chart.title_text = title;
+    bar_chart.font_family = "Roboto".to_string();
+
+    let svg = bar_chart.svg().ok()?;
+
+    svg_to_png(&svg, CHARTS_PIXELS_WIDTH).ok()
+}
+
 fn generate_charts_inner(
     data: Vec<(Game, Vec<GrowLog>)>,
     title: String,
@@ -123,6 +171,17 @@ fn generate_charts_inner(
     svg_to_png(&svg, CHARTS_PIXELS_WIDTH).ok()
 }
 
+/// The chart font has no glyphs for wide characters (emoji, CJK); they would
+/// render as boxes.
+fn blank_wide_chars(text: &str) -> String {
+    text.chars()
+        .map(|c| match UnicodeWidthChar::width(c) {
+            Some(width) if width > 1 => ' ',
+            _ => c,
+        })
+        .collect()
+}
+
 fn normalize_data(
     mut data: Vec<(Game, Vec<GrowLog>)>,
     days: i64,
@@ -136,15 +195,7 @@ fn normalize_data(
         (0..days).map(|i| (start_date + Duration::days(i)).date()).collect();
 
     for (game, _) in data.iter_mut() {
-        let mut chars: Vec<_> = game.name.chars().collect();
-
-        for c in &mut chars {
-            if UnicodeWidthChar::width(*c).unwrap_or(1) > 1 {
-                *c = ' ';
-            }
-        }
-
-        game.name = chars.into_iter().collect();
+        game.name = blank_wide_chars(&game.name);
     }
 
     for (game, mut grow_logs) in data {
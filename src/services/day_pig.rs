@@ -4,6 +4,7 @@ use strum::EnumCount as _;
 use teloxide::{
     prelude::Requester,
     types::{ChatId, UserId},
+    utils::html::{bold, user_mention},
 };
 
 use crate::{
@@ -13,7 +14,10 @@ use crate::{
         models::{Game, InlineUsersGroup, NewInlineUser, User},
     },
     lang::{InnerLang, LocaleTag, lng},
-    services::achievements::{self, Ach},
+    services::{
+        achievements::{self, Ach},
+        notifications::{self, Notice},
+    },
     types::{MyBot, MyResult},
     utils::{
         date::get_date,
@@ -187,6 +191,22 @@ pub async fn select_and_record(
     })))
 }
 
+/// How the pig of the day is named in the announcement: a link that pings
+/// them, unless they turned that off in `/settings`.
+pub async fn mention(user: &User) -> MyResult<String> {
+    let settings = DB.other.get_user_settings(user.id).await?;
+
+    Ok(mention_as(user, settings.mention_me))
+}
+
+fn mention_as(user: &User, with_link: bool) -> String {
+    if with_link {
+        user_mention(UserId(user.user_id as u64), &user.first_name)
+    } else {
+        bold(&escape(&user.first_name))
+    }
+}
+
 pub async fn notify_achievements(
     bot: &MyBot,
     chat_id: ChatId,
@@ -213,10 +233,12 @@ pub async fn notify_achievements(
             ("achievement_name", &achievement_name),
             ("chat_count", &chat_count),
             ("chat_all_count", &all_count),
-            ("global_count", &global_count),
             ("global_all_count", &all_count),
+            ("global_count", &global_count),
         ]);
         bot.send_message(chat_id, text).await?;
+
+        notifications::push(uid, ltag, Notice::Achievement(*ach));
     }
 
     Ok(())
@@ -294,4 +316,12 @@ mod tests {
         assert_eq!(candidates.len(), 1);
         assert_eq!(candidates[&10].iug.as_ref().unwrap().id, 2);
     }
+    #[test]
+    fn an_opted_out_user_is_named_without_a_link() {
+        let mut pig = user(10, 1000);
+        pig.first_name = "<Pig>".to_owned();
+
+        assert!(mention_as(&pig, true).contains("tg://user?id=1000"));
+        assert_eq!(mention_as(&pig, false), "<b>&lt;Pig&gt;</b>");
+    }
 }
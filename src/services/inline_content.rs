@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use ahash::AHashMap;
+use teloxide::{
+    prelude::Requester,
+    types::{ChatId, UserId},
+    utils::html::user_mention,
+};
+
+use crate::{
+    config::{
+        consts::{DEFAULT_LANG_TAG, INLINE_TOP_VOICE_REWARD_KG},
+        env::BOT_CONFIG,
+    },
+    db::{DB, models::InlineVoiceAward},
+    lang::{InnerLang, lng, tag, tag_one_or},
+    metrics::{self, KgSource},
+    types::{MyBot, MyResult},
+    utils::{
+        date::{get_date, get_datetime, previous_month_start},
+        helpers::escape,
+    },
+};
+
+/// The award is keyed by month, so checking often only costs a query.
+const TOP_GRUNT_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// `items` must come newest first, as the browse queries return them. The
+/// first `newest` keep that order; the rest follow by lifetime uses, ties
+/// newest first.
+pub fn order_by_popularity<T>(
+    mut items: Vec<T>,
+    id_of: impl Fn(&T) -> i16,
+    uses: &AHashMap<i16, i64>,
+    newest: usize,
+) -> Vec<T> {
+    let split = newest.min(items.len());
+
+    // Stable, so equal counts keep the incoming newest-first order.
+    items[split..].sort_by_key(|item| {
+        std::cmp::Reverse(uses.get(&id_of(item)).copied().unwrap_or(0))
+    });
+
+    items
+}
+
+/// Runs for the whole process lifetime; spawned from `main`.
+pub async fn run_top_grunt_announcer(bot: MyBot) {
+    loop {
+        if let Err(err) = announce_top_grunt(&bot).await {
+            crate::myerr!("Top grunt announcement failed: {:?}", err);
+        }
+
+        tokio::time::sleep(TOP_GRUNT_CHECK_INTERVAL).await;
+    }
+}
+
+/// Rewards the submitter of last month's most used voice and announces it,
+/// once per month. `Ok(false)` when there was nothing (left) to announce.
+pub async fn announce_top_grunt(bot: &MyBot) -> MyResult<bool> {
+    let month = previous_month_start(get_date());
+
+    let Some((voice, uses)) = DB.other.get_top_voice_of_month(month).await?
+    else {
+        return Ok(false);
+    };
+
+    let award = InlineVoiceAward {
+        month,
+        voice_id: voice.id,
+        uid: voice.uid,
+        uses,
+        awarded_at: get_datetime(),
+    };
+
+    let Some(paid_kg) =
+        DB.other.add_voice_award(award, INLINE_TOP_VOICE_REWARD_KG).await?
+    else {
+        return Ok(false);
+    };
+    metrics::record_kg(KgSource::ContentReward, paid_kg);
+
+    let Some(user) = DB.other.get_user_by_id(voice.uid).await? else {
+        return Ok(true);
+    };
+
+    let number = voice.id.to_string();
+    let uses = uses.to_string();
+    let month = month.format("%m.%Y").to_string();
+    let user_id = UserId(user.user_id as u64);
+
+    let ltag = tag(DEFAULT_LANG_TAG);
+    let mention = user_mention(user_id, &escape(&user.first_name));
+    let text = lng("TopGruntChannelAnnounce", ltag).args(&[
+        ("mention", mention.as_str()),
+        ("number", &number),
+        ("uses", &uses),
+        ("month", &month),
+        ("amount", &INLINE_TOP_VOICE_REWARD_KG.to_string()),
+    ]);
+    bot.send_message(ChatId(BOT_CONFIG.channel_id), text).await?;
+
+    // The award is already recorded, so a blocked bot must not turn this
+    // into an error that gets retried every hour.
+    let ltag = tag_one_or(user.lang.as_deref(), DEFAULT_LANG_TAG);
+    let text = lng("TopGruntCongrats", ltag).args(&[
+        ("number", &number),
+        ("uses", &uses),
+        ("month", &month),
+        ("amount", &INLINE_TOP_VOICE_REWARD_KG.to_string()),
+    ]);
+    if let Err(err) = bot.send_message(user_id, text).await {
+        log::warn!("Top grunt DM to [{}] failed: {:?}", user_id, err);
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(pairs: &[(i16, i64)]) -> AHashMap<i16, i64> {
+        pairs.iter().copied().collect()
+    }
+
+    #[test]
+    fn the_newest_items_lead_regardless_of_uses() {
+        let ids = vec![9, 8, 7, 6, 5];
+        let uses = counts(&[(5, 100), (6, 50), (9, 0)]);
+
+        let ordered = order_by_popularity(ids, |id| *id, &uses, 2);
+
+        assert_eq!(ordered, [9, 8, 5, 6, 7]);
+    }
+
+    #[test]
+    fn unused_items_keep_newest_first_among_themselves() {
+        let ids = vec![6, 5, 4, 3, 2, 1];
+        let uses = counts(&[(2, 3)]);
+
+        let ordered = order_by_popularity(ids, |id| *id, &uses, 0);
+
+        assert_eq!(ordered, [2, 6, 5, 4, 3, 1]);
+    }
+
+    #[test]
+    fn a_new_section_longer_than_the_list_is_just_the_list() {
+        let ids = vec![3, 2, 1];
+        let uses = counts(&[(1, 10)]);
+
+        let ordered = order_by_popularity(ids, |id| *id, &uses, 50);
+
+        assert_eq!(ordered, [3, 2, 1]);
+    }
+}
@@ -1,6 +1,14 @@
 pub mod achievements;
+pub mod broadcast;
 pub mod charts;
 pub mod day_pig;
 pub mod duel;
+pub mod inline_content;
+pub mod moderation;
+pub mod name_filter;
+pub mod notifications;
+pub mod rate_limit;
 pub mod reset_vote;
 pub mod save_image;
+pub mod staff;
+pub mod user_moderation;
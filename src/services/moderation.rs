@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use teloxide::{
+    payloads::{SendAnimationSetters, SendVoiceSetters},
+    prelude::Requester,
+    types::{ChatId, FileId, InputFile, MessageId, UserId},
+};
+
+use crate::{
+    config::{consts::VOICE_FINGERPRINT_MAX_DISTANCE, env::BOT_CONFIG},
+    db::{
+        DB,
+        models::{InlineGif, InlineVoice},
+    },
+    keyboards,
+    types::{MyBot, MyResult},
+    utils::{
+        helpers::get_file_from_stream,
+        ogg::{fingerprint_distance, voice_fingerprint},
+    },
+};
+
+/// Pause between rows in [`backfill_voice_file_meta`], so the backfill never
+/// competes with real traffic for the Bot API limits.
+const BACKFILL_DELAY: Duration = Duration::from_millis(200);
+
+/// Posts the review card for a pending voice and remembers which message it
+/// is, so `/queue` can tell a lost card from one still waiting.
+pub async fn send_voice_review_card(
+    bot: &MyBot,
+    voice: &InlineVoice,
+    submitter: UserId,
+) -> MyResult<()> {
+    let caption = match find_similar_voice(voice).await? {
+        Some(similar_id) => format!("{submitter}\n🔁 ≈ #{similar_id}"),
+        None => submitter.to_string(),
+    };
+
+    let card = bot
+        .send_voice(
+            ChatId(BOT_CONFIG.content_check_channel_id),
+            InputFile::file_id(FileId(voice.file_id.clone())),
+        )
+        .caption(caption)
+        .reply_markup(keyboards::keyboard_voice_check(submitter, voice.id))
+        .await?;
+
+    DB.other.set_voice_review_message(voice.id, card.id.0).await
+}
+
+/// See [`send_voice_review_card`].
+pub async fn send_gif_review_card(
+    bot: &MyBot,
+    gif: &InlineGif,
+    submitter: UserId,
+) -> MyResult<()> {
+    let card = bot
+        .send_animation(
+            ChatId(BOT_CONFIG.content_check_channel_id),
+            InputFile::file_id(FileId(gif.file_id.clone())),
+        )
+        .caption(submitter.to_string())
+        .reply_markup(keyboards::keyboard_gif_check(submitter, gif.id))
+        .await?;
+
+    DB.other.set_gif_review_message(gif.id, card.id.0).await
+}
+
+/// The closest other voice whose fingerprint is within
+/// [`VOICE_FINGERPRINT_MAX_DISTANCE`], to flag a likely re-recording. Only a
+/// hint for the moderator; exact re-uploads are refused before this.
+async fn find_similar_voice(voice: &InlineVoice) -> MyResult<Option<i16>> {
+    let Some(fingerprint) = voice.fingerprint else { return Ok(None) };
+
+    let others = DB.other.get_voice_fingerprints(voice.id).await?;
+
+    let similar = others
+        .into_iter()
+        .map(|(id, other)| (fingerprint_distance(fingerprint, other), id))
+        .filter(|&(distance, _)| distance <= VOICE_FINGERPRINT_MAX_DISTANCE)
+        .min()
+        .map(|(_, id)| id);
+
+    Ok(similar)
+}
+
+/// `None` when the file can't be downloaded or decoded; the voice is then
+/// only checked by `file_unique_id`.
+pub async fn fingerprint_voice(bot: &MyBot, file_id: &FileId) -> Option<i64> {
+    let file = bot.get_file(file_id.clone()).await.ok()?;
+    let content = get_file_from_stream(bot, &file).await?;
+
+    voice_fingerprint(content).await
+}
+
+/// Runs once per process; spawned from `main`.
+pub async fn run_voice_backfill(bot: MyBot) {
+    match backfill_voice_file_meta(&bot).await {
+        Ok(0) => {},
+        Ok(count) => log::info!("Backfilled {count} voices"),
+        Err(err) => crate::myerr!("Voice backfill failed: {:?}", err),
+    }
+}
+
+/// Fills `file_unique_id` and `fingerprint` for voices stored before those
+/// columns existed, and `file_id` for ones approved before that was stored.
+/// A row whose file is gone is logged and left NULL.
+pub async fn backfill_voice_file_meta(bot: &MyBot) -> MyResult<usize> {
+    let voices = DB.other.get_voices_without_file_unique_id().await?;
+    let mut filled = 0;
+
+    for voice in voices {
+        tokio::time::sleep(BACKFILL_DELAY).await;
+
+        let file_id = if voice.file_id.is_empty() {
+            match recover_voice_file_id(bot, &voice.url).await {
+                Some(file_id) => file_id,
+                None => {
+                    log::warn!("Voice [{}] has no post to recover", voice.id);
+                    continue;
+                },
+            }
+        } else {
+            FileId(voice.file_id.clone())
+        };
+
+        let file = match bot.get_file(file_id.clone()).await {
+            Ok(file) => file,
+            Err(err) => {
+                log::warn!("Voice [{}] backfill skipped: {err}", voice.id);
+                continue;
+            },
+        };
+
+        let fingerprint = match get_file_from_stream(bot, &file).await {
+            Some(content) => voice_fingerprint(content).await,
+            None => None,
+        };
+
+        DB.other
+            .set_voice_file_meta(
+                voice.id,
+                &file_id.0,
+                &file.meta.unique_id.0,
+                fingerprint,
+            )
+            .await?;
+        filled += 1;
+    }
+
+    Ok(filled)
+}
+
+/// A voice approved before `file_id` was stored only has its `url`, the
+/// `{channel}/{post}` of its card in the content check channel. The card is
+/// forwarded within the channel to read the file, and the copy deleted.
+async fn recover_voice_file_id(bot: &MyBot, url: &str) -> Option<FileId> {
+    let post = channel_post_id(url)?;
+    let channel = ChatId(BOT_CONFIG.content_check_channel_id);
+
+    let copy = bot.forward_message(channel, channel, post).await.ok()?;
+    if let Err(err) = bot.delete_message(channel, copy.id).await {
+        log::warn!("Backfill copy [{}] not deleted: {err}", copy.id);
+    }
+
+    copy.voice().map(|voice| voice.file.id.clone())
+}
+
+pub fn channel_post_id(url: &str) -> Option<MessageId> {
+    let (_, post) = url.rsplit_once('/')?;
+
+    post.parse().ok().map(MessageId)
+}
@@ -0,0 +1,321 @@
+//! Blocklist for pig names shown in `/top` and the inline tops.
+//!
+//! `blocklists/<tag>.json` holds a JSON array of words per language, loaded
+//! like the locales. Every list applies to every name: a name set in one
+//! chat can surface in the global top for everyone else.
+//!
+//! A word ending in `*` also matches anything starting with it. Both the
+//! words and the names are folded the same way — lowercase, Cyrillic and
+//! Latin look-alikes and leetspeak to one letter, invisible characters
+//! dropped — and compared as runs of one letter, so `fuuuck` still matches
+//! `fuck` while `Niger` does not match a word spelled with a double `g`.
+//!
+//! In strict mode (`!epyc names +`) the whole name is searched with the
+//! separators removed, which also catches words hidden inside others.
+
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use walkdir::WalkDir;
+
+pub static BLOCKLIST: OnceLock<Blocklist> = OnceLock::new();
+
+type Runs = Vec<(char, u16)>;
+
+#[derive(Debug)]
+struct Entry {
+    runs: Runs,
+    prefix: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Blocklist {
+    entries: Vec<Entry>,
+}
+
+impl Blocklist {
+    pub fn new() -> Self {
+        Self::load_from("blocklists/")
+    }
+
+    /// Same as [`Blocklist::new`], but with an explicit directory.
+    pub fn load_from(dir: impl AsRef<Path>) -> Self {
+        let mut list = Self::default();
+        let mut info = String::from("Loaded blocklist:");
+
+        for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let Some((tag, _)) =
+                entry.file_name().to_str().and_then(|v| v.split_once(".json"))
+            else {
+                continue;
+            };
+
+            let Ok(file) = fs::File::open(entry.path()) else {
+                log::error!(
+                    "Blocklist::new() open error '{}'",
+                    entry.path().display()
+                );
+                continue;
+            };
+
+            let Ok(words) = serde_json::from_reader::<_, Vec<String>>(file)
+            else {
+                log::error!(
+                    "Blocklist::new() wrong json '{}'",
+                    entry.path().display()
+                );
+                continue;
+            };
+
+            info += &format!(" {} ({})", tag, words.len());
+            list.extend(words.iter().map(String::as_str));
+        }
+
+        log::info!("{}", info);
+
+        list
+    }
+
+    pub fn from_words<'a>(words: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut list = Self::default();
+        list.extend(words);
+        list
+    }
+
+    fn extend<'a>(&mut self, words: impl IntoIterator<Item = &'a str>) {
+        for word in words {
+            let (word, prefix) = match word.strip_suffix('*') {
+                Some(stem) => (stem, true),
+                None => (word, false),
+            };
+
+            let runs = tokens(word).into_iter().flatten().collect::<Vec<_>>();
+            let runs = merge_runs(runs);
+
+            if !runs.is_empty() {
+                self.entries.push(Entry { runs, prefix });
+            }
+        }
+    }
+
+    pub fn allows(&self, name: &str, strict: bool) -> bool {
+        let tokens = tokens(name);
+
+        if strict {
+            let joined = merge_runs(tokens.into_iter().flatten().collect());
+            return !self.entries.iter().any(|e| contains(&joined, &e.runs));
+        }
+
+        !tokens.iter().any(|token| {
+            self.entries.iter().any(|e| {
+                if e.prefix {
+                    starts_with(token, &e.runs)
+                } else {
+                    token.len() == e.runs.len() && starts_with(token, &e.runs)
+                }
+            })
+        })
+    }
+}
+
+/// Passes everything until [`BLOCKLIST`] is set, which keeps tests that
+/// never load it unaffected.
+pub fn is_allowed(name: &str, strict: bool) -> bool {
+    BLOCKLIST.get().is_none_or(|list| list.allows(name, strict))
+}
+
+/// Splits on anything that is neither a letter, a digit nor a leetspeak
+/// symbol. A run of single-letter words is glued back together, so spacing
+/// out `f u c k` doesn't help.
+fn tokens(text: &str) -> Vec<Runs> {
+    let mut result: Vec<Runs> = vec![];
+    let mut glued = false;
+
+    for word in text.split(|c| !is_ignorable(c) && fold(c).is_none()) {
+        // `!` is a letter only inside a word; at the end it is punctuation.
+        let word = word.trim_end_matches('!');
+        let runs = to_runs(word.chars().filter_map(fold));
+
+        let single = runs.len() == 1 && runs[0].1 == 1;
+
+        match result.last_mut() {
+            Some(last) if single && glued => {
+                last.extend(runs);
+                *last = merge_runs(std::mem::take(last));
+            },
+            _ if runs.is_empty() => continue,
+            _ => result.push(runs),
+        }
+
+        glued = single;
+    }
+
+    result
+}
+
+fn to_runs(chars: impl Iterator<Item = char>) -> Runs {
+    merge_runs(chars.map(|c| (c, 1)).collect())
+}
+
+fn merge_runs(runs: Runs) -> Runs {
+    let mut merged: Runs = Vec::with_capacity(runs.len());
+
+    for (c, n) in runs {
+        match merged.last_mut() {
+            Some(last) if last.0 == c => last.1 = last.1.saturating_add(n),
+            _ => merged.push((c, n)),
+        }
+    }
+
+    merged
+}
+
+/// The name may repeat a letter more often than the word, never less.
+fn starts_with(name: &[(char, u16)], word: &[(char, u16)]) -> bool {
+    name.len() >= word.len()
+        && name.iter().zip(word).all(|(n, w)| n.0 == w.0 && n.1 >= w.1)
+}
+
+fn contains(name: &[(char, u16)], word: &[(char, u16)]) -> bool {
+    (0..name.len()).any(|start| starts_with(&name[start..], word))
+}
+
+/// Zero-width characters, the soft hyphen and combining marks.
+fn is_ignorable(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00ad}'
+            | '\u{200b}'..='\u{200f}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{feff}'
+            | '\u{0300}'..='\u{036f}'
+    )
+}
+
+/// One letter per look-alike group, `None` for separators. Lossy on
+/// purpose: the blocklist goes through the same folding.
+fn fold(c: char) -> Option<char> {
+    if is_ignorable(c) {
+        return None;
+    }
+
+    let c = c.to_lowercase().next().unwrap_or(c);
+
+    let folded = match c {
+        'a' | 'а' | '@' | '4' | 'á' | 'à' | 'ä' | 'â' => 'a',
+        'b' | 'в' | '8' => 'b',
+        'c' | 'с' | 'ç' | '(' => 'c',
+        'e' | 'е' | 'ё' | 'є' | 'э' | 'ə' | 'é' | 'è' | '3' | 'з' => {
+            'e'
+        },
+        'g' | 'ğ' => 'g',
+        'h' | 'н' => 'h',
+        'i' | 'і' | 'ї' | 'ı' | 'í' | 'l' | '1' | '!' | '|' => 'i',
+        'k' | 'к' => 'k',
+        'm' | 'м' => 'm',
+        'o' | 'о' | 'ö' | 'ó' | '0' => 'o',
+        'p' | 'р' => 'p',
+        's' | 'ş' | '$' | '5' => 's',
+        't' | 'т' | '7' | '+' => 't',
+        'u' | 'ü' | 'ú' => 'u',
+        'x' | 'х' => 'x',
+        'y' | 'у' => 'y',
+        'б' | '6' => 'б',
+        'г' | 'ґ' => 'г',
+        c if c.is_alphanumeric() => c,
+        _ => return None,
+    };
+
+    Some(folded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list() -> Blocklist {
+        Blocklist::from_words(["fuck*", "nigger", "хуй*", "сука", "ass"])
+    }
+
+    #[test]
+    fn plain_words_are_rejected() {
+        assert!(!list().allows("fuck", false));
+        assert!(!list().allows("Свин сука", false));
+    }
+
+    #[test]
+    fn clean_names_pass() {
+        for name in ["Хрюша", "Pig 3000", "Sir Oinks-a-lot", "Bomba"] {
+            assert!(list().allows(name, false), "{name}");
+            assert!(list().allows(name, true), "{name}");
+        }
+    }
+
+    #[test]
+    fn a_star_also_matches_longer_words() {
+        assert!(!list().allows("fucking pig", false));
+        assert!(!list().allows("хуйло", false));
+    }
+
+    #[test]
+    fn without_a_star_only_the_whole_word_matches() {
+        assert!(list().allows("сукалий", false));
+        assert!(list().allows("bass", false));
+    }
+
+    #[test]
+    fn look_alikes_and_leetspeak_are_folded() {
+        // Latin `y` and `x` in a Cyrillic word, Cyrillic `с` and `а` in a
+        // Latin one.
+        assert!(!list().allows("xyй", false));
+        assert!(!list().allows("сука", false));
+        assert!(!list().allows("fu(k", false));
+        assert!(!list().allows("FUCK", false));
+        assert!(!list().allows("cyk4", false));
+    }
+
+    #[test]
+    fn repeated_letters_and_invisible_characters_do_not_help() {
+        assert!(!list().allows("fuuuuuck", false));
+        assert!(!list().allows("fu\u{200b}ck", false));
+        assert!(!list().allows("су\u{0301}ка", false));
+    }
+
+    #[test]
+    fn a_doubled_letter_in_the_word_is_required_in_the_name() {
+        assert!(list().allows("Niger", false));
+        assert!(!list().allows("Nigger", false));
+        assert!(list().allows("as", false));
+    }
+
+    #[test]
+    fn spaced_out_letters_are_glued_back() {
+        assert!(!list().allows("f u c k", false));
+        assert!(!list().allows("с.у.к.а", false));
+    }
+
+    #[test]
+    fn trailing_exclamation_marks_are_punctuation() {
+        assert!(!list().allows("сука!!!", false));
+    }
+
+    #[test]
+    fn strict_mode_finds_words_inside_others() {
+        assert!(list().allows("сукалий", false));
+        assert!(!list().allows("сукалий", true));
+        assert!(!list().allows("big-fu-ck", true));
+        assert!(list().allows("bass", false));
+        assert!(!list().allows("bass", true));
+    }
+
+    #[test]
+    fn an_unset_blocklist_allows_everything() {
+        // Nothing in the tests sets `BLOCKLIST`.
+        assert!(is_allowed("fuck", true));
+    }
+}
@@ -0,0 +1,285 @@
+//! Opt-in private messages about what happened to a player in a chat.
+//!
+//! Events are only queued where they happen. Every [`BATCH_WINDOW`] the
+//! queue is drained and each user gets at most one message with everything
+//! they turned on in `/settings`, so a streak of achievements is a single
+//! notification. Only users who started the bot, haven't blocked it and
+//! aren't banned by staff are written to.
+
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::Duration;
+
+use ahash::AHashMap;
+use strum::{IntoStaticStr, VariantArray};
+use teloxide::{
+    prelude::Requester,
+    types::{ChatId, UserId},
+};
+
+use crate::{
+    config::consts::BROADCAST_TARGET_USER,
+    db::{DB, models::UserSettings},
+    enums::UserSetting,
+    lang::{InnerLang, LocaleTag, lng, tag_opt},
+    services::{achievements::Ach, broadcast, user_moderation},
+    types::{MyBot, MyResult},
+    utils::helpers::escape,
+};
+
+const BATCH_WINDOW: Duration = Duration::from_secs(60);
+/// Lines per message; the rest of a batch is only counted.
+const BATCH_MAX_LINES: usize = 10;
+
+static PENDING: LazyLock<Mutex<AHashMap<i32, Pending>>> =
+    LazyLock::new(|| Mutex::new(AHashMap::new()));
+
+#[derive(Clone, Copy, PartialEq, Eq, IntoStaticStr, VariantArray)]
+#[cfg_attr(test, derive(Debug))]
+#[strum(const_into_str)]
+pub enum DuelOutcome {
+    Won,
+    Lost,
+    Draw,
+}
+
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Notice {
+    /// `chat` is the group's title as Telegram has it, `None` for an inline
+    /// chat that isn't linked to one.
+    DayPig {
+        chat: Option<String>,
+    },
+    /// `opponent` is the other hand pig's name, already escaped.
+    Duel {
+        opponent: String,
+        outcome: DuelOutcome,
+        diff: i32,
+    },
+    Achievement(Ach),
+}
+
+impl Notice {
+    /// The switch in `/settings` that lets this one through.
+    pub const fn setting(&self) -> UserSetting {
+        match self {
+            Self::DayPig { .. } => UserSetting::NotifyDayPig,
+            Self::Duel { .. } => UserSetting::NotifyDuels,
+            Self::Achievement(_) => UserSetting::NotifyAchievements,
+        }
+    }
+
+    fn text(&self, ltag: LocaleTag) -> String {
+        match self {
+            Self::DayPig { chat: Some(chat) } => {
+                lng("NotifyDayPig", ltag).args(&[("chat", escape(chat))])
+            },
+            Self::DayPig { chat: None } => lng("NotifyDayPigInline", ltag),
+            Self::Duel { opponent, outcome, diff } => {
+                let key = format!("NotifyDuel{}", outcome.into_str());
+                lng(&key, ltag)
+                    .plural(ltag, "diff", (*diff).into())
+                    .args(&[("opponent", opponent)])
+            },
+            Self::Achievement(ach) => {
+                let name = lng(&format!("Achievement_{}", *ach as i16), ltag);
+                lng("NotifyAchievement", ltag)
+                    .args(&[("achievement_name", &name)])
+            },
+        }
+    }
+}
+
+/// What is waiting for one user until the next flush.
+struct Pending {
+    /// Where the first event happened; used unless the user picked a
+    /// language of their own.
+    ltag: LocaleTag,
+    notices: Vec<Notice>,
+}
+
+/// Queues `notice` for the user with internal id `uid`. Cheap enough to
+/// call from any handler: whether it is ever sent is decided at the flush.
+pub fn push(uid: i32, ltag: LocaleTag, notice: Notice) {
+    let mut pending = PENDING.lock().unwrap_or_else(PoisonError::into_inner);
+
+    pending
+        .entry(uid)
+        .or_insert_with(|| Pending { ltag, notices: Vec::new() })
+        .notices
+        .push(notice);
+}
+
+/// Runs for the whole process lifetime; spawned from `main`.
+pub async fn run_notifier(bot: MyBot) {
+    let mut interval = tokio::time::interval(BATCH_WINDOW);
+    // The first tick is immediate and there is nothing queued yet.
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let pending = std::mem::take(
+            &mut *PENDING.lock().unwrap_or_else(PoisonError::into_inner),
+        );
+        if pending.is_empty() {
+            continue;
+        }
+
+        if let Err(err) = flush(&bot, pending).await {
+            crate::myerr!("Notifications failed: {:?}", err);
+        }
+    }
+}
+
+async fn flush(bot: &MyBot, pending: AHashMap<i32, Pending>) -> MyResult<()> {
+    let uids: Vec<i32> = pending.keys().copied().collect();
+    let recipients = DB.other.get_notification_recipients(&uids).await?;
+
+    for (user, settings) in recipients {
+        let Some(batch) = pending.get(&user.id) else { continue };
+        if user_moderation::is_banned(UserId(user.user_id as u64)) {
+            continue;
+        }
+
+        let ltag = tag_opt(user.lang.as_deref()).unwrap_or(batch.ltag);
+        let Some(text) = batch_text(ltag, &settings, &batch.notices) else {
+            continue;
+        };
+
+        if let Err(err) = bot.send_message(ChatId(user.user_id), text).await {
+            log::error!("Notification failed for [{}] {}", user.user_id, err);
+            let deactivated = broadcast::deactivate_target(
+                user.user_id,
+                BROADCAST_TARGET_USER,
+                &err,
+            )
+            .await;
+            if let Err(err) = deactivated {
+                log::error!("Failed to deactivate [{}] {}", user.user_id, err);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// One message out of what `settings` let through, in the order it
+/// happened; `None` when that is nothing.
+fn batch_text(
+    ltag: LocaleTag,
+    settings: &UserSettings,
+    notices: &[Notice],
+) -> Option<String> {
+    let wanted: Vec<&Notice> =
+        notices.iter().filter(|n| settings.get(n.setting())).collect();
+    if wanted.is_empty() {
+        return None;
+    }
+
+    let mut lines: Vec<String> = wanted
+        .iter()
+        .take(BATCH_MAX_LINES)
+        .map(|notice| notice.text(ltag))
+        .collect();
+
+    let more = wanted.len().saturating_sub(BATCH_MAX_LINES);
+    if more > 0 {
+        let count = more.to_string();
+        lines.push(lng("NotifyMore", ltag).args(&[("count", &count)]));
+    }
+
+    Some(lines.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::init_lang;
+
+    fn settings(
+        day_pig: bool,
+        duels: bool,
+        achievements: bool,
+    ) -> UserSettings {
+        UserSettings {
+            notify_day_pig: day_pig,
+            notify_duels: duels,
+            notify_achievements: achievements,
+            ..UserSettings::new(1)
+        }
+    }
+
+    fn duel() -> Notice {
+        Notice::Duel {
+            opponent: "Hryak".to_owned(),
+            outcome: DuelOutcome::Won,
+            diff: 5,
+        }
+    }
+
+    #[test]
+    fn nothing_is_sent_to_a_user_who_opted_out() {
+        let ltag = init_lang();
+        let notices = [Notice::DayPig { chat: None }, duel()];
+
+        assert_eq!(batch_text(ltag, &UserSettings::new(1), &notices), None);
+        assert_eq!(
+            batch_text(ltag, &settings(false, false, true), &notices),
+            None
+        );
+    }
+
+    #[test]
+    fn only_the_switched_on_kinds_make_it_into_the_message() {
+        let ltag = init_lang();
+        let notices =
+            [Notice::DayPig { chat: Some("Barn".to_owned()) }, duel()];
+
+        let text = batch_text(ltag, &settings(false, true, false), &notices)
+            .expect("duels are on");
+
+        assert!(text.contains("Hryak"));
+        assert!(!text.contains("Barn"));
+    }
+
+    #[test]
+    fn a_long_batch_is_one_message_with_the_rest_counted() {
+        let ltag = init_lang();
+        let notices = vec![duel(); BATCH_MAX_LINES + 3];
+
+        let text = batch_text(ltag, &settings(true, true, true), &notices)
+            .expect("duels are on");
+
+        assert_eq!(text.matches("Hryak").count(), BATCH_MAX_LINES);
+        assert!(
+            text.ends_with(&lng("NotifyMore", ltag).args(&[("count", "3")]))
+        );
+    }
+
+    #[test]
+    fn every_notice_has_a_text() {
+        let ltag = init_lang();
+        let notices = [
+            Notice::DayPig { chat: Some("Barn".to_owned()) },
+            Notice::DayPig { chat: None },
+            duel(),
+            Notice::Duel {
+                opponent: "Hryak".to_owned(),
+                outcome: DuelOutcome::Lost,
+                diff: 1,
+            },
+            Notice::Duel {
+                opponent: "Hryak".to_owned(),
+                outcome: DuelOutcome::Draw,
+                diff: 2,
+            },
+            Notice::Achievement(Ach::VARIANTS[0]),
+        ];
+
+        for notice in notices {
+            let text = notice.text(ltag);
+            assert!(!text.is_empty() && !text.contains('{'), "{notice:?}");
+        }
+    }
+}
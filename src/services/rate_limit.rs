@@ -0,0 +1,337 @@
+//! Token buckets in front of the command endpoints.
+//!
+//! A command takes its [`MyCommands::cost`] from the sender's bucket and
+//! from the chat's, and runs only if both can pay. Buckets refill one token
+//! per interval up to their capacity: a burst is fine, a steady stream of
+//! `/louder` is not. A throttled sender is told once per window; the rest
+//! is dropped silently.
+//!
+//! [`MyCommands::cost`]: crate::enums::MyCommands::cost
+
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use ahash::AHashMap;
+use teloxide::types::{ChatId, Message, UserId};
+
+use crate::config::consts::{
+    RATE_LIMIT_CHAT, RATE_LIMIT_USER, RATE_LIMIT_WARN_WINDOW,
+};
+
+/// Idle buckets are dropped this often, so one-off senders don't pile up.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+
+static LIMITER: LazyLock<RateLimiter> = LazyLock::new(|| {
+    RateLimiter::new(RATE_LIMIT_USER, RATE_LIMIT_CHAT, RATE_LIMIT_WARN_WINDOW)
+});
+
+#[derive(Clone, Copy)]
+pub struct Limits {
+    pub capacity: u32,
+    /// Time to regain one token.
+    pub refill: Duration,
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+pub enum Scope {
+    User,
+    Chat,
+}
+
+/// Passed on to the endpoint that answers a throttled command.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+pub struct Throttled {
+    pub scope: Scope,
+    /// First refusal in this window; later ones stay silent.
+    pub notify: bool,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    warned: Option<Instant>,
+}
+
+impl Bucket {
+    fn new(limits: Limits, now: Instant) -> Self {
+        Self { tokens: limits.capacity as f64, updated: now, warned: None }
+    }
+
+    fn refill(&mut self, limits: Limits, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated);
+        let gained = elapsed.as_secs_f64() / limits.refill.as_secs_f64();
+
+        self.tokens = (self.tokens + gained).min(limits.capacity as f64);
+        self.updated = now;
+    }
+
+    fn warned_within(&self, window: Duration, now: Instant) -> bool {
+        self.warned.is_some_and(|at| now.saturating_duration_since(at) < window)
+    }
+
+    fn warn_once(&mut self, window: Duration, now: Instant) -> bool {
+        if self.warned_within(window, now) {
+            return false;
+        }
+
+        self.warned = Some(now);
+        true
+    }
+
+    fn is_idle(&self, limits: Limits, window: Duration, now: Instant) -> bool {
+        self.tokens >= limits.capacity as f64
+            && !self.warned_within(window, now)
+    }
+}
+
+struct Buckets {
+    users: AHashMap<u64, Bucket>,
+    chats: AHashMap<i64, Bucket>,
+    pruned: Instant,
+}
+
+pub struct RateLimiter {
+    user: Limits,
+    chat: Limits,
+    warn_window: Duration,
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(user: Limits, chat: Limits, warn_window: Duration) -> Self {
+        let buckets = Buckets {
+            users: AHashMap::new(),
+            chats: AHashMap::new(),
+            pruned: Instant::now(),
+        };
+
+        Self { user, chat, warn_window, buckets: Mutex::new(buckets) }
+    }
+
+    /// Takes `cost` from both buckets, or from neither when either is short.
+    pub fn check(
+        &self,
+        user_id: Option<UserId>,
+        chat_id: ChatId,
+        cost: u32,
+        now: Instant,
+    ) -> Result<(), Throttled> {
+        let mut guard =
+            self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if now.saturating_duration_since(guard.pruned) >= PRUNE_INTERVAL {
+            self.prune(&mut guard, now);
+        }
+
+        let Buckets { users, chats, .. } = &mut *guard;
+        let cost = cost as f64;
+
+        let mut user = user_id.map(|id| {
+            let bucket = users
+                .entry(id.0)
+                .or_insert_with(|| Bucket::new(self.user, now));
+            bucket.refill(self.user, now);
+            bucket
+        });
+
+        let chat = chats
+            .entry(chat_id.0)
+            .or_insert_with(|| Bucket::new(self.chat, now));
+        chat.refill(self.chat, now);
+
+        if let Some(user) = user.as_mut()
+            && user.tokens < cost
+        {
+            let notify = user.warn_once(self.warn_window, now);
+            return Err(Throttled { scope: Scope::User, notify });
+        }
+
+        if chat.tokens < cost {
+            let notify = chat.warn_once(self.warn_window, now);
+            return Err(Throttled { scope: Scope::Chat, notify });
+        }
+
+        if let Some(user) = user {
+            user.tokens -= cost;
+        }
+        chat.tokens -= cost;
+
+        Ok(())
+    }
+
+    fn prune(&self, buckets: &mut Buckets, now: Instant) {
+        buckets.users.retain(|_, b| {
+            b.refill(self.user, now);
+            !b.is_idle(self.user, self.warn_window, now)
+        });
+        buckets.chats.retain(|_, b| {
+            b.refill(self.chat, now);
+            !b.is_idle(self.chat, self.warn_window, now)
+        });
+        buckets.pruned = now;
+    }
+}
+
+/// `Some` when the command must not run.
+pub fn throttle(m: &Message, cost: u32) -> Option<Throttled> {
+    let user_id = m.from.as_ref().map(|u| u.id);
+    let throttled = LIMITER.check(user_id, m.chat.id, cost, Instant::now());
+
+    let Err(throttled) = throttled else { return None };
+
+    match throttled.scope {
+        Scope::User => crate::metrics::THROTTLED_USER_COUNTER.inc(),
+        Scope::Chat => crate::metrics::THROTTLED_CHAT_COUNTER.inc(),
+    };
+
+    Some(throttled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: Limits =
+        Limits { capacity: 3, refill: Duration::from_secs(10) };
+    const CHAT: Limits = Limits { capacity: 5, refill: Duration::from_secs(2) };
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    const ALICE: Option<UserId> = Some(UserId(1));
+    const BOB: Option<UserId> = Some(UserId(2));
+    const CHAT_ID: ChatId = ChatId(-100);
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(USER, CHAT, WINDOW)
+    }
+
+    #[test]
+    fn a_burst_up_to_the_capacity_is_allowed() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            assert_eq!(limiter.check(ALICE, CHAT_ID, 1, now), Ok(()));
+        }
+
+        assert_eq!(
+            limiter.check(ALICE, CHAT_ID, 1, now),
+            Err(Throttled { scope: Scope::User, notify: true })
+        );
+    }
+
+    #[test]
+    fn tokens_come_back_over_time() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            limiter.check(ALICE, CHAT_ID, 1, now).unwrap();
+        }
+
+        assert!(limiter.check(ALICE, CHAT_ID, 1, now).is_err());
+        let later = now + Duration::from_secs(10);
+        assert_eq!(limiter.check(ALICE, CHAT_ID, 1, later), Ok(()));
+    }
+
+    #[test]
+    fn an_expensive_command_needs_its_whole_cost() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        limiter.check(ALICE, CHAT_ID, 2, now).unwrap();
+
+        assert!(limiter.check(ALICE, CHAT_ID, 2, now).is_err());
+        assert_eq!(limiter.check(ALICE, CHAT_ID, 1, now), Ok(()));
+    }
+
+    #[test]
+    fn the_slow_down_reply_is_sent_once_per_window() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        limiter.check(ALICE, CHAT_ID, 3, now).unwrap();
+
+        let notified =
+            |at| limiter.check(ALICE, CHAT_ID, 3, at).unwrap_err().notify;
+        assert!(notified(now));
+        assert!(!notified(now + Duration::from_secs(1)));
+
+        // Full again by now, so it takes one more command to be refused.
+        let later = now + WINDOW;
+        limiter.check(ALICE, CHAT_ID, 3, later).unwrap();
+        assert!(notified(later));
+    }
+
+    #[test]
+    fn the_chat_bucket_is_shared_between_users() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            limiter.check(ALICE, CHAT_ID, 1, now).unwrap();
+        }
+        for _ in 0..2 {
+            limiter.check(BOB, CHAT_ID, 1, now).unwrap();
+        }
+
+        assert_eq!(
+            limiter.check(BOB, CHAT_ID, 1, now),
+            Err(Throttled { scope: Scope::Chat, notify: true })
+        );
+        // Another chat has its own bucket.
+        assert_eq!(limiter.check(BOB, ChatId(-200), 1, now), Ok(()));
+    }
+
+    #[test]
+    fn a_refused_command_costs_nothing() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        for _ in 0..3 {
+            limiter.check(ALICE, CHAT_ID, 1, now).unwrap();
+        }
+        // Throttled by Alice's bucket, so the chat keeps its two tokens.
+        for _ in 0..5 {
+            assert!(limiter.check(ALICE, CHAT_ID, 1, now).is_err());
+        }
+
+        assert_eq!(limiter.check(BOB, CHAT_ID, 2, now), Ok(()));
+    }
+
+    #[test]
+    fn without_a_sender_only_the_chat_bucket_counts() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        for _ in 0..5 {
+            assert_eq!(limiter.check(None, CHAT_ID, 1, now), Ok(()));
+        }
+
+        assert_eq!(
+            limiter.check(None, CHAT_ID, 1, now).unwrap_err().scope,
+            Scope::Chat
+        );
+    }
+
+    #[test]
+    fn idle_buckets_are_pruned() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        limiter.check(ALICE, CHAT_ID, 1, now).unwrap();
+        limiter.check(BOB, CHAT_ID, 3, now).unwrap();
+        limiter.check(BOB, CHAT_ID, 1, now).unwrap_err();
+
+        // Everything has refilled by then, but Bob was warned too recently
+        // to forget.
+        let later = now + Duration::from_secs(30);
+        limiter.prune(&mut limiter.buckets.lock().unwrap(), later);
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert!(!buckets.users.contains_key(&1));
+        assert!(buckets.users.contains_key(&2));
+        assert!(buckets.chats.is_empty());
+    }
+}
@@ -0,0 +1,26 @@
+use teloxide::types::UserId;
+
+use crate::{
+    config::env::BOT_CONFIG, db::DB, enums::StaffRole, types::MyResult,
+};
+
+/// `creator_id` is always an owner, without a `staff` row, so a fresh
+/// database still has someone who can grant roles.
+pub async fn role_of(
+    creator_id: u64,
+    user_id: UserId,
+) -> MyResult<Option<StaffRole>> {
+    if user_id.0 == creator_id {
+        return Ok(Some(StaffRole::Owner));
+    }
+
+    let role = DB.other.get_staff_role(user_id.0 as i64).await?;
+
+    Ok(role.and_then(StaffRole::from_i16))
+}
+
+pub async fn has_role(user_id: UserId, required: StaffRole) -> MyResult<bool> {
+    let role = role_of(BOT_CONFIG.creator_id, user_id).await?;
+
+    Ok(role.is_some_and(|r| r >= required))
+}
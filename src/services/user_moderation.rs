@@ -0,0 +1,124 @@
+//! Staff bans and the user lookup the moderation commands share.
+//!
+//! Bans are checked on every update, so the banned Telegram ids are held in
+//! memory: loaded at startup and kept in step by [`set_banned`]. Another
+//! replica's `/ban` or `/unban` only reaches this one with the next
+//! [`run_ban_refresher`] reload, up to [`BAN_REFRESH_INTERVAL`] later.
+
+use std::sync::{LazyLock, PoisonError, RwLock};
+use std::time::Duration;
+
+use ahash::AHashSet;
+use teloxide::types::UserId;
+
+use crate::{
+    db::{DB, models::User},
+    types::MyResult,
+};
+
+const BAN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+static BANNED: LazyLock<RwLock<AHashSet<u64>>> =
+    LazyLock::new(|| RwLock::new(AHashSet::new()));
+
+/// Replaces the cache with the `bot_bans` table; the number of bans loaded.
+pub async fn load_bans() -> MyResult<usize> {
+    let ids = DB.other.get_banned_user_ids().await?;
+    let mut banned = BANNED.write().unwrap_or_else(PoisonError::into_inner);
+
+    banned.clear();
+    banned.extend(ids.into_iter().map(|id| id as u64));
+
+    Ok(banned.len())
+}
+
+/// Runs for the whole process lifetime; spawned from `main`.
+pub async fn run_ban_refresher() {
+    let mut interval = tokio::time::interval(BAN_REFRESH_INTERVAL);
+    // The first tick is immediate and `setup_bans` has just loaded them.
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = load_bans().await {
+            log::error!("Failed to reload bot bans: {:?}", err);
+        }
+    }
+}
+
+pub fn is_banned(user_id: UserId) -> bool {
+    BANNED.read().unwrap_or_else(PoisonError::into_inner).contains(&user_id.0)
+}
+
+/// Only the cache; the `bot_bans` row is the caller's.
+pub fn set_banned(user_id: UserId, banned: bool) {
+    let mut set = BANNED.write().unwrap_or_else(PoisonError::into_inner);
+
+    if banned {
+        set.insert(user_id.0);
+    } else {
+        set.remove(&user_id.0);
+    }
+}
+
+/// How a moderation command names its target: a plain number is `users.id`,
+/// the one `/id` shows and `/grant` takes; `tg:<id>` is a Telegram id.
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq, Eq))]
+pub enum UserTarget {
+    Internal(i32),
+    Telegram(i64),
+}
+
+impl UserTarget {
+    pub fn parse(word: &str) -> Option<Self> {
+        match word.strip_prefix("tg:") {
+            Some(id) => id.parse().ok().map(Self::Telegram),
+            None => word.parse().ok().map(Self::Internal),
+        }
+    }
+
+    pub async fn resolve(self) -> MyResult<Option<User>> {
+        match self {
+            Self::Internal(id) => DB.other.get_user_by_id(id).await,
+            Self::Telegram(id) => DB.other.get_user(id).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_plain_number_is_the_internal_id() {
+        assert_eq!(UserTarget::parse("42"), Some(UserTarget::Internal(42)));
+    }
+
+    #[test]
+    fn the_tg_prefix_selects_the_telegram_id() {
+        assert_eq!(
+            UserTarget::parse("tg:5000000001"),
+            Some(UserTarget::Telegram(5_000_000_001))
+        );
+    }
+
+    #[test]
+    fn anything_else_is_rejected() {
+        for word in ["", "tg:", "tg:abc", "@name", "5000000001"] {
+            assert_eq!(UserTarget::parse(word), None, "{word}");
+        }
+    }
+
+    #[test]
+    fn the_cache_follows_set_banned() {
+        let id = UserId(9_000_000_001);
+
+        set_banned(id, true);
+        assert!(is_banned(id));
+
+        set_banned(id, false);
+        assert!(!is_banned(id));
+    }
+}
@@ -1,6 +1,10 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
 use axum::Router;
 
 use teloxide::{
+    adaptors::{Throttle, throttle},
     prelude::*,
     update_listeners::{UpdateListener, webhooks},
     utils::command::BotCommands,
@@ -9,13 +13,15 @@ use tokio::net::TcpListener;
 
 use crate::{
     config::{
-        consts::{DEFAULT_LANG_TAG, IGNORED_COMMANDS},
-        env::{BOT_CONFIG, BOT_ME, BOT_STATIC},
+        consts::{BOT_PARSE_MODE, DEFAULT_LANG_TAG, IGNORED_COMMANDS},
+        env::{BOT_CONFIG, BOT_ME, BOT_STATIC, Config, init_config},
     },
     db::init::Database,
     enums::MyCommands,
+    health,
     lang::{self, get_langs, lng},
     metrics,
+    services::{name_filter, user_moderation},
     types::{MyBot, MyResult},
 };
 
@@ -43,6 +49,7 @@ pub async fn setup_listener(
             Router::new()
                 .merge(bot_router)
                 .merge(metrics::init())
+                .merge(health::init())
                 .fallback(fallback_404)
                 .into_make_service(),
         )
@@ -55,9 +62,78 @@ pub async fn setup_listener(
     Ok(update_listener)
 }
 
+/// The adaptor reports its queue only while it is full, every few seconds;
+/// a report this old means it has drained since.
+const QUEUE_FULL_REPORT_TTL: Duration = Duration::from_secs(10);
+
+static QUEUE_FULL_REPORTS: AtomicU64 = AtomicU64::new(0);
+
+pub fn setup_bot(bot: Bot) -> MyBot {
+    let settings =
+        throttle::Settings::default().on_queue_full(|pending| async move {
+            let report = QUEUE_FULL_REPORTS.fetch_add(1, Ordering::Relaxed) + 1;
+            log::warn!("Telegram request queue is full: {pending} pending");
+            metrics::TG_QUEUE_DEPTH.set(pending as i64);
+
+            tokio::time::sleep(QUEUE_FULL_REPORT_TTL).await;
+            if QUEUE_FULL_REPORTS.load(Ordering::Relaxed) == report {
+                metrics::TG_QUEUE_DEPTH.set(0);
+            }
+        });
+
+    Throttle::spawn_with_settings(bot, settings).parse_mode(BOT_PARSE_MODE)
+}
+
+/// Exits with every problem listed instead of panicking on the first use.
+pub fn setup_config() {
+    let config = Config::load().unwrap_or_else(|err| {
+        log::error!("{err}");
+        std::process::exit(1);
+    });
+
+    if init_config(config).is_err() {
+        log::warn!("BOT_CONFIG was read before the config was checked");
+    }
+}
+
+/// For `--check-config`: the exit code, and the problems on stderr.
+pub fn check_config() -> i32 {
+    match Config::load() {
+        Ok(_) => {
+            println!("Configuration is valid");
+            0
+        },
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        },
+    }
+}
+
 pub fn setup_lang() {
-    let loc = lang::Locale::new(DEFAULT_LANG_TAG);
-    lang::LANG.set(loc).expect("Locale set error!");
+    let loc = lang::Locale::new(DEFAULT_LANG_TAG)
+        .unwrap_or_else(|err| panic!("{err}"));
+
+    // Caught again by `/reloadlocales`; not worth refusing to start over.
+    // A missing key is served from a fallback language meanwhile.
+    for (tag, keys) in loc.missing_keys() {
+        log::warn!(
+            "Locale: {tag} lacks {} keys: {}",
+            keys.len(),
+            keys.join(", ")
+        );
+    }
+
+    for problem in loc.problems() {
+        log::error!("Locale: {problem}");
+    }
+
+    lang::set_locale(loc);
+}
+
+pub fn setup_blocklist() {
+    let list = name_filter::Blocklist::new();
+    name_filter::BLOCKLIST.set(list).expect("Blocklist set error!");
 }
 
 pub async fn setup_me(bot: &MyBot) {
@@ -71,6 +147,12 @@ pub async fn setup_db() {
     let _ = Database::get_or_init_pool();
 }
 
+pub async fn setup_bans() {
+    let count =
+        user_moderation::load_bans().await.expect("Couldn't load bot bans!");
+    log::info!("Loaded {} banned users", count);
+}
+
 pub async fn setup_commands(bot: &MyBot) {
     let langs = get_langs();
     for (ltag, lang) in langs.iter().enumerate() {
@@ -1,25 +1,30 @@
 //! Fixtures and one-time global init shared by the test modules.
 
 use std::path::Path;
+use std::sync::Once;
 
 use chrono::{NaiveDate, NaiveDateTime};
 use teloxide::types::Me;
 
 use crate::{
     config::env::BOT_ME,
-    db::models::{Game, GrowLog, InlineUser, User},
-    lang::{LANG, Locale, LocaleTag},
+    db::models::{Game, Groups, GrowLog, InlineUser, User},
+    lang::{Locale, LocaleTag, set_locale},
 };
 
 
-/// `get_or_init`, not `setup_lang`'s `set().expect()` — tests share one
-/// process.
+/// Loaded once, not per call like `setup_lang` — tests share one process.
 pub fn init_lang() -> LocaleTag {
-    LANG.get_or_init(|| {
-        Locale::load_from(
+    static INIT: Once = Once::new();
+
+    INIT.call_once(|| {
+        let locale = Locale::load_from(
             Path::new(env!("CARGO_MANIFEST_DIR")).join("locales"),
             crate::config::consts::DEFAULT_LANG_TAG,
         )
+        .expect("locales do not load");
+
+        set_locale(locale);
     });
 
     crate::lang::tag(crate::config::consts::DEFAULT_LANG_TAG)
@@ -133,6 +138,23 @@ pub fn daily_grow_log(
         .collect()
 }
 
+pub fn group(id: i32, chat_id: i64) -> Groups {
+    Groups {
+        id,
+        chat_id,
+        date: datetime(2026, 1, 1, 0, 0),
+        settings: 0,
+        top10_setting: 0,
+        lang: None,
+        active: true,
+        ig_id: None,
+        username: None,
+        title: "Test chat".to_owned(),
+        reset_at: None,
+        strict_names: false,
+    }
+}
+
 pub fn user(id: i32, user_id: i64) -> User {
     User {
         id,
@@ -146,6 +168,7 @@ pub fn user(id: i32, user_id: i64) -> User {
         username: None,
         first_name: "Tester".to_owned(),
         last_name: None,
+        started_at: Some(datetime(2026, 1, 1, 0, 0)),
     }
 }
 
@@ -3,7 +3,9 @@
 
 use std::str::FromStr;
 
-use crate::enums::{InlineResults, Top10Variant};
+use crate::enums::{
+    InlineResults, SettingsPage, StaffRole, Top10Variant, UserSetting,
+};
 
 
 fn all_variants() -> Vec<InlineResults> {
@@ -14,6 +16,7 @@ fn all_variants() -> Vec<InlineResults> {
         InlineResults::GetMoreInfo,
         InlineResults::NameHryakInfo,
         InlineResults::RenameHryakInfo,
+        InlineResults::NameRejectedInfo,
         InlineResults::DayPigInfo,
         InlineResults::FlagInfo,
         InlineResults::FlagEmptyInfo,
@@ -192,3 +195,88 @@ fn top10_variants_round_trip_through_their_wire_name() {
         assert_eq!(parsed.into_str(), name);
     }
 }
+
+#[test]
+fn staff_roles_round_trip_through_their_stored_value() {
+    use strum::VariantArray;
+
+    for &role in StaffRole::VARIANTS {
+        assert_eq!(StaffRole::from_i16(role as i16), Some(role));
+        assert_eq!(StaffRole::from_str(role.into_str()), Ok(role));
+    }
+
+    assert_eq!(StaffRole::from_i16(0), None);
+    assert_eq!(StaffRole::from_i16(4), None);
+}
+
+#[test]
+fn a_higher_staff_role_includes_the_lower_ones() {
+    // The permission checks are plain comparisons.
+    assert!(StaffRole::Owner > StaffRole::Admin);
+    assert!(StaffRole::Admin > StaffRole::Moderator);
+}
+
+#[test]
+fn settings_pages_round_trip_through_their_payload() {
+    let pages = [
+        SettingsPage::Main,
+        SettingsPage::Lang,
+        SettingsPage::Flags(0),
+        SettingsPage::Flags(11),
+        SettingsPage::Notifications,
+        SettingsPage::Privacy,
+    ];
+
+    for page in pages {
+        let payload = page.to_payload();
+        assert_eq!(
+            SettingsPage::from_payload(&payload),
+            Some(page),
+            "{payload:?}"
+        );
+    }
+
+    for bad in ["flags", "flags-1", "flagsx", "nope"] {
+        assert_eq!(SettingsPage::from_payload(bad), None, "{bad:?}");
+    }
+}
+
+#[test]
+fn the_user_setting_wire_names_are_stable() {
+    // Sent in settings buttons, and part of the `UserSetting_*` locale keys.
+    use strum::VariantArray;
+
+    let names: Vec<&str> =
+        UserSetting::VARIANTS.iter().map(|s| s.into_str()).collect();
+
+    assert_eq!(
+        names,
+        ["notify_day_pig", "notify_duels", "notify_achievements", "mention_me"]
+    );
+
+    for &setting in UserSetting::VARIANTS {
+        assert_eq!(UserSetting::from_str(setting.into_str()), Ok(setting));
+    }
+}
+
+#[test]
+fn every_user_setting_is_on_exactly_one_settings_page() {
+    use strum::VariantArray;
+
+    for &setting in UserSetting::VARIANTS {
+        let listed = UserSetting::NOTIFICATIONS
+            .iter()
+            .chain(&UserSetting::PRIVACY)
+            .filter(|&&s| s == setting)
+            .count();
+        assert_eq!(listed, 1, "{setting:?}");
+
+        let page = setting.page();
+        let on_its_page = match page {
+            SettingsPage::Notifications => &UserSetting::NOTIFICATIONS[..],
+            SettingsPage::Privacy => &UserSetting::PRIVACY[..],
+            other => panic!("{setting:?} returns to {other:?}"),
+        };
+        assert!(on_its_page.contains(&setting), "{setting:?}");
+    }
+}
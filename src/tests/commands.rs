@@ -1,11 +1,10 @@
 //! A typo in a Cyrillic or Azerbaijani alias is invisible in review and
 //! silently breaks that language's whole command.
 
-use std::str::FromStr;
-
 use crate::enums::{
-    AdminCommands, EpycCommands, InlineCommands, InlineKeywords, MyCommands,
+    AdminCommands, EpycCommand, InlineCommands, InlineKeywords, MyCommands,
 };
+use crate::test_support::init_lang;
 use teloxide::utils::command::BotCommands;
 
 const BOT: &str = "fr0staman_bot";
@@ -28,6 +27,8 @@ fn every_slash_command_parses() {
         "/louder",
         "/achievements",
         "/resetpigs",
+        "/mysubmissions",
+        "/settings",
     ];
 
     for command in commands {
@@ -110,30 +111,48 @@ fn the_ignored_commands_list_only_names_real_commands() {
 
 #[test]
 fn epyc_parses_in_all_three_languages() {
-    let cases = [("!епік", "епік"), ("!эпик", "эпик"), ("!epyc", "epyc")];
+    init_lang();
 
-    for (prefix, _) in cases {
-        let parsed = EpycCommands::parse(&format!("{prefix} top 10"), BOT)
-            .unwrap_or_else(|e| panic!("{prefix}: {e:?}"));
+    for name in ["епік", "эпик", "epyc"] {
+        let parsed = EpycCommand::parse(&format!("!{name} top 10"), BOT)
+            .unwrap_or_else(|| panic!("!{name} did not parse"));
 
-        let arg = match parsed {
-            EpycCommands::EpycUA(a)
-            | EpycCommands::EpycRU(a)
-            | EpycCommands::EpycEN(a) => a,
-        };
-        assert_eq!(arg, "top 10", "{prefix}");
+        assert_eq!(parsed.name, name);
+        assert_eq!(parsed.args, "top 10", "{name}");
     }
 }
 
 #[test]
 fn epyc_requires_the_bang_prefix() {
-    assert!(EpycCommands::parse("/epyc top 10", BOT).is_err());
-    assert!(EpycCommands::parse("epyc top 10", BOT).is_err());
+    init_lang();
+
+    assert!(EpycCommand::parse("/epyc top 10", BOT).is_none());
+    assert!(EpycCommand::parse("epyc top 10", BOT).is_none());
 }
 
 #[test]
 fn epyc_accepts_no_argument() {
-    assert!(EpycCommands::parse("!epyc", BOT).is_ok());
+    init_lang();
+
+    assert_eq!(EpycCommand::parse("!epyc", BOT).unwrap().args, "");
+}
+
+#[test]
+fn epyc_addressed_to_another_bot_is_not_ours() {
+    init_lang();
+
+    let ours = EpycCommand::parse(&format!("!epyc@{BOT} top 10"), BOT);
+    assert_eq!(ours.unwrap().args, "top 10");
+    assert!(EpycCommand::parse("!epyc@other_bot top 10", BOT).is_none());
+}
+
+#[test]
+fn epyc_matching_is_exact() {
+    init_lang();
+
+    for text in ["!EPYC top", "! epyc top", "!epycs top", "!"] {
+        assert!(EpycCommand::parse(text, BOT).is_none(), "{text:?}");
+    }
 }
 
 
@@ -148,6 +167,46 @@ fn admin_commands_parse_with_their_arguments() {
         AdminCommands::Repost(arg) => assert_eq!(arg, "+dm +chats"),
         other => panic!("{other:?}"),
     }
+
+    match AdminCommands::parse("/repost +dm --lang=uk --dry-run", BOT).unwrap()
+    {
+        AdminCommands::Repost(arg) => {
+            assert_eq!(arg, "+dm --lang=uk --dry-run")
+        },
+        other => panic!("{other:?}"),
+    }
+
+    match AdminCommands::parse("/broadcast pause 3", BOT).unwrap() {
+        AdminCommands::Broadcast(arg) => assert_eq!(arg, "pause 3"),
+        other => panic!("{other:?}"),
+    }
+
+    match AdminCommands::parse("/queue resend all", BOT).unwrap() {
+        AdminCommands::Queue(arg) => assert_eq!(arg, "resend all"),
+        other => panic!("{other:?}"),
+    }
+
+    match AdminCommands::parse("/mass tg:42 -100 -5 duel exploit", BOT).unwrap()
+    {
+        AdminCommands::Mass(arg) => {
+            assert_eq!(arg, "tg:42 -100 -5 duel exploit")
+        },
+        other => panic!("{other:?}"),
+    }
+
+    match AdminCommands::parse("/grant 42 moderator", BOT).unwrap() {
+        AdminCommands::Grant(arg) => assert_eq!(arg, "42 moderator"),
+        other => panic!("{other:?}"),
+    }
+
+    match AdminCommands::parse("/content voice 12 caption Hello world", BOT)
+        .unwrap()
+    {
+        AdminCommands::Content(arg) => {
+            assert_eq!(arg, "voice 12 caption Hello world")
+        },
+        other => panic!("{other:?}"),
+    }
 }
 
 
@@ -161,16 +220,17 @@ fn every_inline_command_alias_parses() {
         &["гіф", "гиф", "gif"],
     ];
 
+    init_lang();
+
     for group in groups {
-        let first = InlineCommands::from_str(group[0])
-            .unwrap_or_else(|_| panic!("{} did not parse", group[0]));
+        let first = InlineCommands::from_alias(group[0])
+            .unwrap_or_else(|| panic!("{} did not parse", group[0]));
 
         for alias in group {
-            let parsed = InlineCommands::from_str(alias)
-                .unwrap_or_else(|_| panic!("{alias} did not parse"));
+            let parsed = InlineCommands::from_alias(alias)
+                .unwrap_or_else(|| panic!("{alias} did not parse"));
             assert_eq!(
-                std::mem::discriminant(&parsed),
-                std::mem::discriminant(&first),
+                parsed, first,
                 "{alias} resolved to a different variant than {}",
                 group[0]
             );
@@ -190,16 +250,17 @@ fn every_inline_keyword_alias_parses() {
         &["гіф", "гиф", "gif"],
     ];
 
+    init_lang();
+
     for group in groups {
-        let first = InlineKeywords::from_str(group[0])
-            .unwrap_or_else(|_| panic!("{} did not parse", group[0]));
+        let first = InlineKeywords::from_alias(group[0])
+            .unwrap_or_else(|| panic!("{} did not parse", group[0]));
 
         for alias in group {
-            let parsed = InlineKeywords::from_str(alias)
-                .unwrap_or_else(|_| panic!("{alias} did not parse"));
+            let parsed = InlineKeywords::from_alias(alias)
+                .unwrap_or_else(|| panic!("{alias} did not parse"));
             assert_eq!(
-                std::mem::discriminant(&parsed),
-                std::mem::discriminant(&first),
+                parsed, first,
                 "{alias} resolved to a different variant than {}",
                 group[0]
             );
@@ -214,15 +275,19 @@ fn the_latin_oc_and_the_cyrillic_oc_both_parse() {
     let cyrillic = "\u{043e}\u{0441}";
     let latin = "oc";
 
+    init_lang();
+
     assert_ne!(cyrillic, latin);
-    assert!(InlineKeywords::from_str(cyrillic).is_ok());
-    assert!(InlineKeywords::from_str(latin).is_ok());
+    assert_eq!(InlineKeywords::from_alias(cyrillic), Some(InlineKeywords::OC));
+    assert_eq!(InlineKeywords::from_alias(latin), Some(InlineKeywords::OC));
 }
 
 #[test]
 fn inline_keyword_matching_is_exact() {
-    for text in ["", "flags", " flag", "FLAG", "flag "] {
-        assert!(InlineKeywords::from_str(text).is_err(), "{text:?}");
+    init_lang();
+
+    for text in ["", "flags", " flag", "FLAG", "flag ", "flag,pig"] {
+        assert!(InlineKeywords::from_alias(text).is_none(), "{text:?}");
     }
 }
 
@@ -230,13 +295,69 @@ fn inline_keyword_matching_is_exact() {
 fn every_inline_command_alias_is_also_an_inline_keyword() {
     // `InlineCommands` is the subset of keywords that take a payload; each
     // must still be routable as a keyword.
+    init_lang();
+
     for alias in [
         "ім'я", "імя", "имя", "name", "ad", "хрю", "hru", "grunt", "xort",
         "прапор", "флаг", "flag", "bayraq", "гіф", "гиф", "gif",
     ] {
         assert!(
-            InlineKeywords::from_str(alias).is_ok(),
+            InlineKeywords::from_alias(alias).is_some(),
             "{alias} is an InlineCommand but not an InlineKeyword"
         );
     }
 }
+
+#[test]
+fn staff_management_is_owner_only() {
+    use crate::enums::StaffRole;
+
+    for command in ["/grant 42 admin", "/revoke 42"] {
+        let parsed = AdminCommands::parse(command, BOT).unwrap();
+        assert_eq!(parsed.required_role(), StaffRole::Owner, "{command}");
+    }
+
+    let mass = AdminCommands::parse("/mass 12 -100 -5 exploit", BOT).unwrap();
+    assert_eq!(mass.required_role(), StaffRole::Admin);
+
+    let reload = AdminCommands::parse("/reloadlocales", BOT).unwrap();
+    assert_eq!(reload.required_role(), StaffRole::Admin);
+
+    for command in ["/queue", "/stats", "/ban 12", "/rename 12 hand Pig"] {
+        let parsed = AdminCommands::parse(command, BOT).unwrap();
+        assert_eq!(parsed.required_role(), StaffRole::Moderator, "{command}");
+    }
+}
+
+#[test]
+fn heavy_commands_cost_more_rate_limit_tokens() {
+    let cost = |command| MyCommands::parse(command, BOT).unwrap().cost();
+
+    assert_eq!(cost("/grow"), 1);
+    assert!(cost("/top") > cost("/grow"));
+    assert!(cost("/louder") > cost("/top"));
+}
+
+#[test]
+fn the_metrics_label_of_a_command_is_its_name() {
+    for command in ["/start", "/daypigs", "/resetpigs", "/mysubmissions"] {
+        let parsed = MyCommands::parse(command, BOT).unwrap();
+        assert_eq!(<&str>::from(&parsed), &command[1..]);
+    }
+
+    let print = MyCommands::parse("/p hello", BOT).unwrap();
+    assert_eq!(<&str>::from(&print), "p");
+}
+
+#[test]
+fn every_inline_alias_has_the_label_of_its_group() {
+    init_lang();
+
+    for (alias, label) in [("имя", "name"), ("xort", "hru"), ("bayraq", "flag")]
+    {
+        assert_eq!(InlineCommands::from_alias(alias).unwrap().label(), label);
+        assert_eq!(InlineKeywords::from_alias(alias).unwrap().label(), label);
+    }
+
+    assert_eq!(InlineKeywords::from_alias("donuz").unwrap().label(), "day_pig");
+}
@@ -28,15 +28,25 @@ use chrono::{NaiveDate, NaiveDateTime};
 /// One statement so `CASCADE` sorts out the foreign keys.
 const TRUNCATE_ALL: &str = "TRUNCATE \
     achievements_users, \
+    bot_bans, \
+    broadcast_targets, \
+    broadcasts, \
+    duel_log, \
     game, \
     groups, \
     grow_log, \
     hryak_day, \
+    inline_gif_uses, \
     inline_gifs, \
     inline_groups, \
     inline_users, \
     inline_users_groups, \
+    inline_voice_awards, \
+    inline_voice_uses, \
     inline_voices, \
+    moderation_log, \
+    staff, \
+    user_settings, \
     users \
     RESTART IDENTITY CASCADE";
 
@@ -147,6 +157,7 @@ impl TestDb {
                 supported: false,
                 subscribed: false,
                 created_at: datetime(2026, 1, 1, 0, 0),
+                started_at: Some(datetime(2026, 1, 1, 0, 0)),
                 lang: None,
                 username: None,
                 first_name: "Tester",
@@ -207,6 +207,11 @@ async fn counting_pigs_respects_the_same_minimum_as_the_top() {
     assert_eq!(t.db.chat_pig.count_chat_pig(-100_001, 0).await.unwrap(), 3);
     assert_eq!(t.db.chat_pig.count_chat_pig(-100_001, 10).await.unwrap(), 1);
     assert_eq!(t.db.chat_pig.count_active_pigs(group.id).await.unwrap(), 3);
+
+    let empty = t.seed_group(-100_002).await;
+    let per_group = t.db.chat_pig.count_pigs_per_group().await.unwrap();
+    assert_eq!(per_group.get(&group.id), Some(&3));
+    assert_eq!(per_group.get(&empty.id), None);
 }
 
 
@@ -467,3 +472,75 @@ async fn the_growth_chart_query_caps_at_ten_pigs() {
 
     assert_eq!(data.len(), 10);
 }
+
+#[tokio::test]
+async fn feeds_and_growth_are_counted_from_the_window_start() {
+    use crate::db::models::UpdateGroups;
+
+    let t = db!();
+
+    let now = datetime(2026, 7, 28, 12, 0);
+    let week_ago = now - chrono::Duration::days(7);
+
+    let mut chats = Vec::new();
+    for (i, title) in [(1, "Slow"), (2, "Fast"), (3, "Idle")] {
+        let group = t.seed_group(-100_000 - i).await;
+        let update =
+            UpdateGroups { title: title.to_owned(), ..group.to_update() };
+        t.db.other.update_chat(group.chat_id, update).await.unwrap();
+        chats.push(group);
+    }
+
+    let user = t.seed_user(1_001).await;
+    let slow = t.seed_chat_pig(&user, &chats[0], 10, date(2026, 7, 28)).await;
+    let fast = t.seed_chat_pig(&user, &chats[1], 10, date(2026, 7, 28)).await;
+    let idle = t.seed_chat_pig(&user, &chats[2], 10, date(2026, 7, 28)).await;
+
+    t.seed_grow_log(slow.id, now, 5, 10).await;
+    t.seed_grow_log(fast.id, now - chrono::Duration::days(2), 8, 8).await;
+    t.seed_grow_log(fast.id, now, -2, 6).await;
+    // Before the window: counts for neither feeds nor growth.
+    t.seed_grow_log(idle.id, now - chrono::Duration::days(8), 50, 50).await;
+
+    assert_eq!(t.db.chat_pig.count_feeds_since(week_ago).await.unwrap(), 3);
+    assert_eq!(t.db.chat_pig.count_chat_pigs().await.unwrap(), 3);
+
+    let top = t.db.chat_pig.get_top_growing_chats(week_ago, 5).await.unwrap();
+    assert_eq!(top, [("Fast".to_owned(), 6), ("Slow".to_owned(), 5)]);
+
+    let top = t.db.chat_pig.get_top_growing_chats(week_ago, 1).await.unwrap();
+    assert_eq!(top.len(), 1);
+}
+
+#[tokio::test]
+async fn the_mass_sum_covers_every_chat_pig() {
+    let t = db!();
+
+    assert_eq!(t.db.chat_pig.sum_chat_pig_mass().await.unwrap(), 0);
+
+    let user = t.seed_user(1_001).await;
+    for (chat_id, mass) in [(-100_001, 10), (-100_002, 250)] {
+        let group = t.seed_group(chat_id).await;
+        t.seed_chat_pig(&user, &group, mass, date(2026, 7, 28)).await;
+    }
+
+    assert_eq!(t.db.chat_pig.sum_chat_pig_mass().await.unwrap(), 260);
+}
+
+#[tokio::test]
+async fn a_mass_adjustment_floors_at_one_kilogram() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    let group = t.seed_group(-100_001).await;
+    let pig = t.seed_chat_pig(&user, &group, 50, date(2026, 7, 28)).await;
+
+    for (delta, expected) in [(25, 75), (-74, 1), (-500, 1)] {
+        let mass =
+            t.db.chat_pig.adjust_chat_pig_mass(pig.id, delta).await.unwrap();
+        assert_eq!(mass, expected, "{delta:+}");
+    }
+
+    let stored = t.db.chat_pig.get_chat_pig(1_001, -100_001).await.unwrap();
+    assert_eq!(stored.unwrap().mass, 1);
+}
@@ -154,6 +154,24 @@ async fn a_duel_only_touches_the_pig_it_names() {
     assert_eq!(untouched.win, 0);
 }
 
+#[tokio::test]
+async fn duels_are_counted_from_the_window_start() {
+    let t = db!();
+
+    let a = t.seed_user(1_001).await;
+    let b = t.seed_user(1_002).await;
+
+    let now = datetime(2026, 7, 28, 12, 0);
+    for at in [now, now - chrono::Duration::hours(30)] {
+        t.db.hand_pig.add_duel_log(a.id, b.id, 10, at).await.unwrap();
+    }
+
+    let day_ago = now - chrono::Duration::days(1);
+    assert_eq!(t.db.hand_pig.count_duels_since(day_ago).await.unwrap(), 1);
+    let week_ago = now - chrono::Duration::days(7);
+    assert_eq!(t.db.hand_pig.count_duels_since(week_ago).await.unwrap(), 2);
+}
+
 
 #[tokio::test]
 async fn an_inline_group_is_found_by_its_chat_instance() {
@@ -2,11 +2,18 @@
 //!
 //! Requires `TEST_DATABASE_URL`; see `src/tests/common.rs`.
 
-use crate::tests::common::datetime;
-use crate::config::consts::INLINE_CONTENT_APPROVED;
+use crate::tests::common::{date, datetime};
+use crate::config::consts::{
+    BROADCAST_CANCELLED, BROADCAST_PAUSED, BROADCAST_RUNNING,
+    BROADCAST_TARGET_CHAT, BROADCAST_TARGET_CLAIMED, BROADCAST_TARGET_FAILED,
+    BROADCAST_TARGET_SENT, BROADCAST_TARGET_USER, INLINE_CONTENT_APPROVED,
+    INLINE_CONTENT_HIDDEN, INLINE_CONTENT_PENDING, INLINE_CONTENT_REJECTED,
+};
 use crate::db::models::{
-    AchievementUserAdd, UpdateGroups, UpdateUser, UserStatus,
+    AchievementUserAdd, InlineVoiceAward, NewBroadcast, NewModerationLog,
+    Staff, UpdateGroups, UpdateUser, UserStatus,
 };
+use crate::enums::UserSetting;
 
 macro_rules! db {
     () => {
@@ -57,6 +64,21 @@ async fn a_user_status_change_keeps_the_other_flags() {
     assert!(!user.subscribed);
 }
 
+#[tokio::test]
+async fn the_start_time_is_kept_apart_from_the_first_sighting() {
+    let t = db!();
+
+    t.seed_user(1_001).await;
+    t.db.other
+        .set_user_started_at(1_001, datetime(2026, 6, 1, 12, 0))
+        .await
+        .unwrap();
+
+    let user = t.db.other.get_user(1_001).await.unwrap().unwrap();
+    assert_eq!(user.created_at, datetime(2026, 1, 1, 0, 0));
+    assert_eq!(user.started_at, Some(datetime(2026, 6, 1, 12, 0)));
+}
+
 #[tokio::test]
 async fn a_language_override_can_be_set_and_cleared() {
     let t = db!();
@@ -74,6 +96,35 @@ async fn a_language_override_can_be_set_and_cleared() {
     assert!(t.db.other.get_user(1_001).await.unwrap().unwrap().lang.is_none());
 }
 
+#[tokio::test]
+async fn a_user_without_settings_gets_the_defaults_until_one_is_saved() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    let settings = t.db.other.get_user_settings(user.id).await.unwrap();
+    assert!(!settings.notify_day_pig && !settings.notify_duels);
+    assert!(!settings.notify_achievements);
+    assert!(settings.mention_me);
+
+    let mut changed = settings;
+    changed.toggle(UserSetting::NotifyDuels);
+    changed.toggle(UserSetting::MentionMe);
+    t.db.other.set_user_settings(changed).await.unwrap();
+
+    // A second save updates the row rather than failing on the key.
+    changed.toggle(UserSetting::NotifyAchievements);
+    t.db.other.set_user_settings(changed).await.unwrap();
+
+    let stored = t.db.other.get_user_settings(user.id).await.unwrap();
+    assert!(stored.notify_duels && stored.notify_achievements);
+    assert!(!stored.notify_day_pig);
+    assert!(!stored.mention_me);
+
+    let other = t.seed_user(1_002).await;
+    let untouched = t.db.other.get_user_settings(other.id).await.unwrap();
+    assert!(untouched.mention_me && !untouched.notify_duels);
+}
+
 #[tokio::test]
 async fn updating_a_user_rewrites_the_profile_fields() {
     let t = db!();
@@ -208,6 +259,27 @@ async fn the_reset_timestamp_is_stamped_on_the_group() {
     );
 }
 
+#[tokio::test]
+async fn strict_names_start_off_and_survive_other_updates() {
+    let t = db!();
+
+    let group = t.seed_group(-100_001).await;
+    assert!(!group.strict_names);
+
+    t.db.other.set_strict_names(-100_001, true).await.unwrap();
+    let strict = t.db.other.get_chat(-100_001).await.unwrap().unwrap();
+    assert!(strict.strict_names);
+
+    t.db.other
+        .update_chat(
+            -100_001,
+            UpdateGroups { active: false, ..strict.to_update() },
+        )
+        .await
+        .unwrap();
+    assert!(t.db.other.get_chat(-100_001).await.unwrap().unwrap().strict_names);
+}
+
 #[tokio::test]
 async fn listing_chats_and_users_returns_everything_seeded() {
     let t = db!();
@@ -221,6 +293,35 @@ async fn listing_chats_and_users_returns_everything_seeded() {
     assert_eq!(t.db.other.get_chats().await.unwrap().len(), 3);
 }
 
+#[tokio::test]
+async fn the_dashboard_totals_split_users_and_chats_by_status() {
+    let t = db!();
+
+    for i in 0..3i64 {
+        t.seed_user(1_001 + i).await;
+    }
+    let banned = UserStatus {
+        started: false,
+        banned: true,
+        supported: false,
+        subscribed: false,
+    };
+    t.db.other.change_user_status(1_003, banned).await.unwrap();
+
+    let inactive = t.seed_group(-100_001).await;
+    t.seed_group(-100_002).await;
+    t.db.other
+        .update_chat(
+            -100_001,
+            UpdateGroups { active: false, ..inactive.to_update() },
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(t.db.other.count_users().await.unwrap(), (3, 2, 1));
+    assert_eq!(t.db.other.count_active_chats().await.unwrap(), 1);
+}
+
 
 #[tokio::test]
 async fn achievements_are_stored_per_pig() {
@@ -478,6 +579,757 @@ async fn a_gif_is_deduplicated_by_its_file_unique_id() {
 }
 
 
+#[tokio::test]
+async fn chosen_uses_accumulate_per_month_and_sum_over_the_lifetime() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    t.db.other.add_voice(user.id, "a.ogg".to_owned()).await.unwrap();
+    t.db.other.add_voice(user.id, "b.ogg".to_owned()).await.unwrap();
+    let voices = t.db.other.get_voices_by_user(user.id).await.unwrap();
+    let (a, b) = (voices[0].id, voices[1].id);
+
+    for _ in 0..3 {
+        t.db.other.add_voice_use(a, date(2026, 6, 1)).await.unwrap();
+    }
+    t.db.other.add_voice_use(a, date(2026, 7, 1)).await.unwrap();
+    t.db.other.add_voice_use(b, date(2026, 7, 1)).await.unwrap();
+
+    let mut counts = t.db.other.get_voice_use_counts().await.unwrap();
+    counts.sort();
+    assert_eq!(counts, [(a, 4), (b, 1)]);
+
+    let (top, uses) = t
+        .db
+        .other
+        .get_top_voice_of_month(date(2026, 6, 1))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((top.id, uses), (a, 3));
+    assert!(
+        t.db.other.get_top_voice_of_month(date(2026, 5, 1)).await.unwrap().is_none()
+    );
+}
+
+#[tokio::test]
+async fn a_tied_month_goes_to_the_older_voice() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    t.db.other.add_voice(user.id, "a.ogg".to_owned()).await.unwrap();
+    t.db.other.add_voice(user.id, "b.ogg".to_owned()).await.unwrap();
+    let voices = t.db.other.get_voices_by_user(user.id).await.unwrap();
+
+    t.db.other.add_voice_use(voices[1].id, date(2026, 6, 1)).await.unwrap();
+    t.db.other.add_voice_use(voices[0].id, date(2026, 6, 1)).await.unwrap();
+
+    let (top, _) = t
+        .db
+        .other
+        .get_top_voice_of_month(date(2026, 6, 1))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(top.id, voices[0].id);
+}
+
+#[tokio::test]
+async fn gif_uses_are_counted_separately_from_voices() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    t.db.other
+        .add_gif(user.id, "file".to_owned(), "unique".to_owned())
+        .await
+        .unwrap();
+    let gifs = t.db.other.get_gifs_by_user(user.id).await.unwrap();
+    let gif = &gifs[0];
+
+    t.db.other.add_gif_use(gif.id, date(2026, 6, 1)).await.unwrap();
+    t.db.other.add_gif_use(gif.id, date(2026, 6, 1)).await.unwrap();
+
+    assert_eq!(t.db.other.get_gif_use_counts().await.unwrap(), [(gif.id, 2)]);
+    assert!(t.db.other.get_voice_use_counts().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn a_month_is_awarded_only_once() {
+    // The announcer re-checks every hour and on every restart; only the
+    // first insert may pay out.
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    t.seed_hand_pig(&user, 50, date(2026, 6, 1)).await;
+    t.db.other.add_voice(user.id, "a.ogg".to_owned()).await.unwrap();
+    let voices = t.db.other.get_voices_by_user(user.id).await.unwrap();
+    let voice = &voices[0];
+
+    let award = || InlineVoiceAward {
+        month: date(2026, 6, 1),
+        voice_id: voice.id,
+        uid: user.id,
+        uses: 3,
+        awarded_at: datetime(2026, 7, 1, 0, 0),
+    };
+
+    assert_eq!(
+        t.db.other.add_voice_award(award(), 1000).await.unwrap(),
+        Some(1000)
+    );
+    assert_eq!(t.db.other.add_voice_award(award(), 1000).await.unwrap(), None);
+
+    let (hand_pig, _) =
+        t.db.hand_pig.get_hrundel(1_001).await.unwrap().unwrap();
+    assert_eq!(hand_pig.weight, 1050, "paid once, with the award");
+    assert_eq!(hand_pig.date, date(2026, 7, 1));
+}
+
+#[tokio::test]
+async fn a_pending_voice_is_hidden_until_approved() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    let pending =
+        t.db.other
+            .add_pending_voice(
+                user.id,
+                "file-id",
+                "unique",
+                datetime(2026, 6, 1, 12, 0),
+            )
+            .await
+            .unwrap();
+
+    assert_eq!(pending.status, INLINE_CONTENT_PENDING);
+    assert!(t.db.other.get_inline_voices().await.unwrap().is_empty());
+
+    let approved =
+        t.db.other
+            .approve_voice(pending.id, "channel/42".to_owned())
+            .await
+            .unwrap()
+            .unwrap();
+
+    assert_eq!(approved.id, pending.id);
+    assert_eq!(approved.url, "channel/42");
+    assert_eq!(t.db.other.get_inline_voices().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn a_submission_is_decided_only_once() {
+    // Re-sent review cards mean two buttons can point at the same row.
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    let submitted = datetime(2026, 6, 1, 12, 0);
+    let voice =
+        t.db.other
+            .add_pending_voice(user.id, "file-id", "unique", submitted)
+            .await
+            .unwrap();
+    let gif =
+        t.db.other
+            .add_pending_gif(user.id, "file-id", "unique", submitted)
+            .await
+            .unwrap();
+
+    assert!(t.db.other.reject_voice(voice.id, None).await.unwrap().is_some());
+    assert!(
+        t.db.other
+            .approve_voice(voice.id, "channel/1".to_owned())
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    assert!(t.db.other.approve_gif(gif.id).await.unwrap().is_some());
+    assert!(t.db.other.reject_gif(gif.id, None).await.unwrap().is_none());
+    assert_eq!(t.db.other.get_inline_gifs().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn a_rejection_keeps_its_reason_for_the_submitters_history() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    let voice =
+        t.db.other
+            .add_pending_voice(
+                user.id,
+                "file-id",
+                "unique",
+                datetime(2026, 6, 1, 12, 0),
+            )
+            .await
+            .unwrap();
+
+    let rejected =
+        t.db.other.reject_voice(voice.id, Some(3)).await.unwrap().unwrap();
+    assert_eq!(rejected.reject_reason, Some(3));
+
+    let mine = t.db.other.get_voices_by_user(user.id).await.unwrap();
+    assert_eq!(mine[0].status, INLINE_CONTENT_REJECTED);
+    assert_eq!(mine[0].reject_reason, Some(3));
+}
+
+#[tokio::test]
+async fn the_queue_lists_pending_submissions_oldest_first() {
+    let t = db!();
+
+    let alice = t.seed_user(1_001).await;
+    let bob = t.seed_user(1_002).await;
+    let submitted = datetime(2026, 6, 1, 12, 0);
+
+    let first =
+        t.db.other
+            .add_pending_voice(alice.id, "a", "a", submitted)
+            .await
+            .unwrap();
+    let second =
+        t.db.other
+            .add_pending_voice(bob.id, "b", "b", submitted)
+            .await
+            .unwrap();
+    t.db.other.set_voice_review_message(second.id, 77).await.unwrap();
+
+    let queue = t.db.other.get_pending_voices().await.unwrap();
+    assert_eq!(queue.len(), 2);
+    assert_eq!((queue[0].0.id, queue[0].1.user_id), (first.id, 1_001));
+    assert_eq!(queue[0].0.review_message_id, None);
+    assert_eq!(queue[1].0.review_message_id, Some(77));
+
+    t.db.other.reject_voice(first.id, None).await.unwrap();
+    assert_eq!(t.db.other.get_pending_voices().await.unwrap().len(), 1);
+    assert!(t.db.other.get_pending_gifs().await.unwrap().is_empty());
+    assert_eq!(t.db.other.count_pending_submissions().await.unwrap(), (1, 0));
+}
+
+#[tokio::test]
+async fn hiding_and_restoring_a_voice_keeps_its_id() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    let voice =
+        t.db.other
+            .add_pending_voice(
+                user.id,
+                "file-id",
+                "unique",
+                datetime(2026, 6, 1, 12, 0),
+            )
+            .await
+            .unwrap();
+    t.db.other.approve_voice(voice.id, "channel/1".to_owned()).await.unwrap();
+
+    let hidden =
+        t.db.other
+            .change_voice_status(
+                voice.id,
+                INLINE_CONTENT_APPROVED,
+                INLINE_CONTENT_HIDDEN,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+    assert_eq!(hidden.id, voice.id);
+    assert!(t.db.other.get_inline_voices().await.unwrap().is_empty());
+
+    // Hiding twice is a no-op, not a second transition.
+    assert!(
+        t.db.other
+            .change_voice_status(
+                voice.id,
+                INLINE_CONTENT_APPROVED,
+                INLINE_CONTENT_HIDDEN,
+            )
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    t.db.other
+        .change_voice_status(
+            voice.id,
+            INLINE_CONTENT_HIDDEN,
+            INLINE_CONTENT_APPROVED,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    let voices = t.db.other.get_inline_voices().await.unwrap();
+    assert_eq!(voices.len(), 1);
+    assert_eq!(voices[0].id, voice.id);
+}
+
+#[tokio::test]
+async fn captions_and_files_are_only_edited_on_published_rows() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    let submitted = datetime(2026, 6, 1, 12, 0);
+    let pending =
+        t.db.other
+            .add_pending_voice(user.id, "a", "a", submitted)
+            .await
+            .unwrap();
+    let published =
+        t.db.other
+            .add_pending_voice(user.id, "b", "b", submitted)
+            .await
+            .unwrap();
+    t.db.other
+        .approve_voice(published.id, "channel/1".to_owned())
+        .await
+        .unwrap();
+
+    assert!(!t.db.other.set_voice_caption(pending.id, "Oink").await.unwrap());
+    assert!(
+        !t.db
+            .other
+            .replace_voice_file(pending.id, "c", "c", None, "channel/2")
+            .await
+            .unwrap()
+    );
+
+    assert!(t.db.other.set_voice_caption(published.id, "Oink").await.unwrap());
+    assert!(
+        t.db.other
+            .replace_voice_file(published.id, "c", "c", Some(7), "channel/2")
+            .await
+            .unwrap()
+    );
+
+    let voices = t.db.other.get_inline_voices().await.unwrap();
+    assert_eq!(voices[0].id, published.id);
+    assert_eq!(voices[0].caption, "Oink");
+    assert_eq!(voices[0].file_id, "c");
+    assert_eq!(voices[0].url, "channel/2");
+    assert_eq!(voices[0].file_unique_id.as_deref(), Some("c"));
+    assert_eq!(voices[0].fingerprint, Some(7));
+}
+
+#[tokio::test]
+async fn a_voice_is_found_by_its_file_unique_id_whatever_its_status() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    let voice = t
+        .db
+        .other
+        .add_pending_voice(user.id, "a", "unique", datetime(2026, 6, 1, 12, 0))
+        .await
+        .unwrap();
+    t.db.other.reject_voice(voice.id, None).await.unwrap();
+
+    let found =
+        t.db.other
+            .get_voice_by_file_unique_id("unique")
+            .await
+            .unwrap()
+            .unwrap();
+    assert_eq!(found.id, voice.id);
+    assert!(
+        t.db.other
+            .get_voice_by_file_unique_id("other")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn fingerprints_skip_the_voice_itself_and_rejected_ones() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    let submitted = datetime(2026, 6, 1, 12, 0);
+    let mut ids = Vec::new();
+    for (n, fingerprint) in [1, 2, 3].into_iter().enumerate() {
+        let unique = n.to_string();
+        let voice =
+            t.db.other
+                .add_pending_voice(user.id, "a", &unique, submitted)
+                .await
+                .unwrap();
+        t.db.other.set_voice_fingerprint(voice.id, fingerprint).await.unwrap();
+        ids.push(voice.id);
+    }
+    t.db.other.add_pending_voice(user.id, "a", "3", submitted).await.unwrap();
+    t.db.other.reject_voice(ids[1], None).await.unwrap();
+
+    let fingerprints = t.db.other.get_voice_fingerprints(ids[0]).await.unwrap();
+    assert_eq!(fingerprints, vec![(ids[2], 3)]);
+}
+
+#[tokio::test]
+async fn the_backfill_only_sees_rows_with_a_file_to_look_up() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    let voice =
+        t.db.other
+            .add_pending_voice(user.id, "a", "a", datetime(2026, 6, 1, 12, 0))
+            .await
+            .unwrap();
+    assert!(
+        t.db.other
+            .get_voices_without_file_unique_id()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // Stored before the migration, with a file but no unique id yet.
+    use diesel_async::RunQueryDsl as _;
+
+    diesel::sql_query("UPDATE inline_voices SET file_unique_id = NULL")
+        .execute(&mut t.conn().await)
+        .await
+        .unwrap();
+
+    let missing = t.db.other.get_voices_without_file_unique_id().await.unwrap();
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].id, voice.id);
+
+    t.db.other.set_voice_file_meta(voice.id, "a", "a", None).await.unwrap();
+    assert!(
+        t.db.other
+            .get_voices_without_file_unique_id()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn a_voice_approved_before_file_ids_is_backfilled_from_its_post() {
+    use crate::services::moderation::channel_post_id;
+    use teloxide::types::MessageId;
+
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    // Approved before `file_id` was stored: only the channel post is known.
+    t.db.other.add_voice(user.id, "channel/42".to_owned()).await.unwrap();
+
+    let missing = t.db.other.get_voices_without_file_unique_id().await.unwrap();
+    assert_eq!(missing.len(), 1);
+    let legacy = &missing[0];
+    assert_eq!(legacy.file_id, "");
+    assert_eq!(legacy.file_unique_id, None);
+    assert_eq!(channel_post_id(&legacy.url), Some(MessageId(42)));
+
+    t.db.other
+        .set_voice_file_meta(legacy.id, "recovered", "u", Some(7))
+        .await
+        .unwrap();
+
+    let voice =
+        t.db.other.get_inline_voice_by_id(legacy.id).await.unwrap().unwrap();
+    assert_eq!(voice.file_id, "recovered");
+    assert_eq!(voice.file_unique_id.as_deref(), Some("u"));
+    assert_eq!(voice.fingerprint, Some(7));
+    assert!(
+        t.db.other
+            .get_voices_without_file_unique_id()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn a_staff_role_is_found_by_telegram_id_and_can_be_replaced() {
+    let t = db!();
+
+    let owner = t.seed_user(1_001).await;
+    let user = t.seed_user(1_002).await;
+    assert!(t.db.other.get_staff_role(1_002).await.unwrap().is_none());
+
+    let grant = |role| Staff {
+        uid: user.id,
+        role,
+        granted_by: Some(owner.id),
+        granted_at: datetime(2026, 6, 1, 12, 0),
+    };
+
+    t.db.other.set_staff(grant(1)).await.unwrap();
+    t.db.other.set_staff(grant(2)).await.unwrap();
+    assert_eq!(t.db.other.get_staff_role(1_002).await.unwrap(), Some(2));
+
+    let staff = t.db.other.get_staff().await.unwrap();
+    assert_eq!(staff.len(), 1);
+    assert_eq!(staff[0].1.user_id, 1_002);
+}
+
+#[tokio::test]
+async fn revoking_a_role_reports_whether_there_was_one() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    t.db.other
+        .set_staff(Staff {
+            uid: user.id,
+            role: 1,
+            granted_by: None,
+            granted_at: datetime(2026, 6, 1, 12, 0),
+        })
+        .await
+        .unwrap();
+
+    assert!(t.db.other.remove_staff(user.id).await.unwrap());
+    assert!(!t.db.other.remove_staff(user.id).await.unwrap());
+    assert!(t.db.other.get_staff_role(1_001).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn a_ban_is_separate_from_blocking_the_bot() {
+    let t = db!();
+
+    let user = t.seed_user(1_001).await;
+    t.seed_user(1_002).await;
+    let at = datetime(2026, 6, 1, 12, 0);
+
+    assert!(t.db.other.ban_user(user.id, at).await.unwrap());
+    assert!(!t.db.other.ban_user(user.id, at).await.unwrap());
+    assert_eq!(t.db.other.get_banned_user_ids().await.unwrap(), [1_001]);
+
+    // Unblocking the bot rewrites `users.banned`, not the ban.
+    let status = UserStatus {
+        started: true,
+        banned: false,
+        supported: false,
+        subscribed: false,
+    };
+    t.db.other.change_user_status(1_001, status).await.unwrap();
+    assert_eq!(t.db.other.get_banned_user_ids().await.unwrap(), [1_001]);
+
+    assert!(t.db.other.unban_user(user.id).await.unwrap());
+    assert!(!t.db.other.unban_user(user.id).await.unwrap());
+    assert!(t.db.other.get_banned_user_ids().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn the_moderation_log_is_per_target_and_newest_first() {
+    let t = db!();
+
+    let moderator = t.seed_user(1_001).await;
+    let target = t.seed_user(1_002).await;
+    let other = t.seed_user(1_003).await;
+
+    for (uid, action, note) in
+        [(target.id, 1, "spam"), (other.id, 1, "flood"), (target.id, 2, "")]
+    {
+        t.db.other
+            .add_moderation_log(NewModerationLog {
+                staff_uid: moderator.id,
+                target_uid: uid,
+                action,
+                note,
+                created_at: datetime(2026, 6, 1, 12, 0),
+            })
+            .await
+            .unwrap();
+    }
+
+    let log = t.db.other.get_moderation_log(target.id, 10).await.unwrap();
+    let entries: Vec<_> =
+        log.iter().map(|e| (e.action, e.note.as_str())).collect();
+    assert_eq!(entries, [(2, ""), (1, "spam")]);
+
+    let log = t.db.other.get_moderation_log(target.id, 1).await.unwrap();
+    assert_eq!(log.len(), 1);
+}
+
+fn new_broadcast(created_by: i32) -> NewBroadcast {
+    NewBroadcast {
+        from_chat_id: -100_500,
+        message_id: 7,
+        created_by,
+        report_chat_id: 1_001,
+        created_at: datetime(2026, 6, 1, 12, 0),
+    }
+}
+
+#[tokio::test]
+async fn a_broadcast_hands_out_chats_before_users_until_none_are_pending() {
+    let t = db!();
+
+    let admin = t.seed_user(1_001).await;
+    let targets: [(i64, i16); 4] = [
+        (1_002, BROADCAST_TARGET_USER),
+        (-100_002, BROADCAST_TARGET_CHAT),
+        (-100_001, BROADCAST_TARGET_CHAT),
+        // A duplicate target is stored once.
+        (1_002, BROADCAST_TARGET_USER),
+    ];
+    let job =
+        t.db.other
+            .add_broadcast(new_broadcast(admin.id), &targets)
+            .await
+            .unwrap();
+    assert_eq!(job.status, BROADCAST_RUNNING);
+
+    let next = t.db.other.get_next_running_broadcast().await.unwrap().unwrap();
+    assert_eq!(next.id, job.id);
+
+    let claimed_at = datetime(2026, 6, 1, 12, 5);
+    let stale_before = datetime(2026, 6, 1, 11, 55);
+    let claim = |limit| {
+        t.db.other.claim_broadcast_targets(
+            job.id,
+            limit,
+            claimed_at,
+            stale_before,
+        )
+    };
+
+    let batch = claim(2).await.unwrap();
+    assert_eq!(
+        batch,
+        [(-100_002, BROADCAST_TARGET_CHAT), (-100_001, BROADCAST_TARGET_CHAT)]
+    );
+    assert!(t.db.other.has_claimed_broadcast_targets(job.id).await.unwrap());
+
+    let sent = datetime(2026, 6, 1, 12, 6);
+    t.db.other
+        .set_broadcast_target_status(
+            job.id,
+            -100_002,
+            BROADCAST_TARGET_SENT,
+            sent,
+        )
+        .await
+        .unwrap();
+    t.db.other
+        .set_broadcast_target_status(
+            job.id,
+            -100_001,
+            BROADCAST_TARGET_FAILED,
+            sent,
+        )
+        .await
+        .unwrap();
+
+    assert!(!t.db.other.has_claimed_broadcast_targets(job.id).await.unwrap());
+
+    // What a restarted worker would resume from.
+    let rest = claim(20).await.unwrap();
+    assert_eq!(rest, [(1_002, BROADCAST_TARGET_USER)]);
+
+    let mut progress = t.db.other.get_broadcast_progress(job.id).await.unwrap();
+    progress.sort();
+    assert_eq!(
+        progress,
+        [
+            (BROADCAST_TARGET_CHAT, BROADCAST_TARGET_SENT, 1),
+            (BROADCAST_TARGET_CHAT, BROADCAST_TARGET_FAILED, 1),
+            (BROADCAST_TARGET_USER, BROADCAST_TARGET_CLAIMED, 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn a_claimed_target_goes_to_one_worker_until_the_claim_is_stale() {
+    let t = db!();
+
+    let admin = t.seed_user(1_001).await;
+    let targets = [(1_002, BROADCAST_TARGET_USER)];
+    let job =
+        t.db.other
+            .add_broadcast(new_broadcast(admin.id), &targets)
+            .await
+            .unwrap();
+
+    let claimed_at = datetime(2026, 6, 1, 12, 0);
+    let first =
+        t.db.other
+            .claim_broadcast_targets(job.id, 20, claimed_at, claimed_at)
+            .await
+            .unwrap();
+    assert_eq!(first, targets);
+
+    // Another replica, a minute later, within the timeout.
+    let second =
+        t.db.other
+            .claim_broadcast_targets(
+                job.id,
+                20,
+                datetime(2026, 6, 1, 12, 1),
+                datetime(2026, 6, 1, 11, 51),
+            )
+            .await
+            .unwrap();
+    assert!(second.is_empty());
+
+    // The first worker died; its claim is taken over once stale.
+    let taken_over =
+        t.db.other
+            .claim_broadcast_targets(
+                job.id,
+                20,
+                datetime(2026, 6, 1, 12, 20),
+                datetime(2026, 6, 1, 12, 10),
+            )
+            .await
+            .unwrap();
+    assert_eq!(taken_over, targets);
+}
+
+#[tokio::test]
+async fn only_running_broadcasts_are_picked_up_and_finished_ones_stay_finished()
+{
+    let t = db!();
+
+    let admin = t.seed_user(1_001).await;
+    let job =
+        t.db.other.add_broadcast(new_broadcast(admin.id), &[]).await.unwrap();
+
+    assert!(
+        t.db.other
+            .change_broadcast_status(
+                job.id,
+                &[BROADCAST_RUNNING],
+                BROADCAST_PAUSED,
+                None
+            )
+            .await
+            .unwrap()
+    );
+    assert!(t.db.other.get_next_running_broadcast().await.unwrap().is_none());
+
+    let finished = Some(datetime(2026, 6, 1, 13, 0));
+    assert!(
+        t.db.other
+            .change_broadcast_status(
+                job.id,
+                &[BROADCAST_RUNNING, BROADCAST_PAUSED],
+                BROADCAST_CANCELLED,
+                finished,
+            )
+            .await
+            .unwrap()
+    );
+    assert!(
+        !t.db
+            .other
+            .change_broadcast_status(
+                job.id,
+                &[BROADCAST_PAUSED],
+                BROADCAST_RUNNING,
+                None
+            )
+            .await
+            .unwrap()
+    );
+
+    let stored = t.db.other.get_broadcast(job.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, BROADCAST_CANCELLED);
+    assert_eq!(stored.finished_at, finished);
+    assert_eq!(t.db.other.get_broadcasts(10).await.unwrap().len(), 1);
+}
+
 mod shortcuts {
     use super::*;
     use crate::db::shortcuts;
@@ -489,9 +1341,8 @@ mod shortcuts {
         last: Option<&str>,
         username: Option<&str>,
     ) -> TelegramUser {
-        let last = last
-            .map(|l| format!(r#""last_name": "{l}","#))
-            .unwrap_or_default();
+        let last =
+            last.map(|l| format!(r#""last_name": "{l}","#)).unwrap_or_default();
         let username = username
             .map(|u| format!(r#""username": "{u}","#))
             .unwrap_or_default();
@@ -227,10 +227,7 @@ mod day_pig {
     /// Only used for the membership check, skipped via
     /// `telegram_chat_id: None`.
     fn bot() -> crate::types::MyBot {
-        use teloxide::prelude::*;
-        Bot::new("0000000000:TEST").parse_mode(
-            crate::config::consts::BOT_PARSE_MODE,
-        )
+        crate::setup::setup_bot(teloxide::Bot::new("0000000000:TEST"))
     }
 
     #[tokio::test]
@@ -4,12 +4,14 @@
 use std::str::FromStr;
 
 use crate::{
-    enums::{CbActions, Top10Variant},
+    db::models::UserSettings,
+    enums::{CbActions, RejectReason, SettingsPage, Top10Variant, UserSetting},
     keyboards,
-    lang::LocaleTag,
+    lang::{LocaleTag, get_langs},
     test_support::init_all,
-    utils::helpers::decode_callback_data,
+    utils::{flag::Flags, helpers::decode_callback_data},
 };
+use strum::VariantArray;
 use teloxide::types::{
     InlineKeyboardButton, InlineKeyboardButtonKind, InlineKeyboardMarkup,
     UserId,
@@ -108,20 +110,49 @@ fn the_top10_button_encodes_the_variant_it_switches_to() {
 fn the_moderation_keyboards_offer_accept_and_reject() {
     setup();
 
-    let voice = keyboards::keyboard_voice_check(USER);
+    let voice = keyboards::keyboard_voice_check(USER, 42);
     let voice_rows = rows(&voice);
     assert_eq!(voice_rows.len(), 1);
     assert_eq!(voice_rows[0].len(), 2);
     assert_eq!(voice_rows[0][0].text, "✅");
     assert_eq!(voice_rows[0][1].text, "❌");
-    assert_action(&voice_rows[0][0], CbActions::AllowVoice);
-    assert_action(&voice_rows[0][1], CbActions::DisallowVoice);
+    assert_eq!(assert_action(&voice_rows[0][0], CbActions::AllowVoice), "42");
+    assert_eq!(
+        assert_action(&voice_rows[0][1], CbActions::DisallowVoice),
+        "42"
+    );
 
-    let gif = keyboards::keyboard_gif_check(USER);
+    let gif = keyboards::keyboard_gif_check(USER, 7);
     let gif_rows = rows(&gif);
     assert_eq!(gif_rows[0].len(), 2);
-    assert_eq!(assert_action(&gif_rows[0][0], CbActions::GifDecision), "+");
-    assert_eq!(assert_action(&gif_rows[0][1], CbActions::GifDecision), "-");
+    assert_eq!(assert_action(&gif_rows[0][0], CbActions::GifDecision), "+7");
+    assert_eq!(assert_action(&gif_rows[0][1], CbActions::GifDecision), "-7");
+}
+
+#[test]
+fn the_reject_keyboards_offer_every_reason_and_a_way_back() {
+    let ltag = setup();
+
+    let voice = keyboards::keyboard_voice_reject(ltag, USER, 42);
+    let buttons: Vec<_> = rows(&voice).iter().flatten().collect();
+    let (back, reasons) = buttons.split_last().unwrap();
+
+    assert_eq!(reasons.len(), RejectReason::VARIANTS.len());
+    for (button, reason) in reasons.iter().zip(RejectReason::VARIANTS) {
+        assert_eq!(
+            assert_action(button, CbActions::DisallowVoice),
+            format!("42:{}", *reason as i16)
+        );
+    }
+    assert_eq!(assert_action(back, CbActions::ReviewBack), "v42");
+
+    let gif = keyboards::keyboard_gif_reject(ltag, USER, 7);
+    let buttons: Vec<_> = rows(&gif).iter().flatten().collect();
+    assert_eq!(assert_action(buttons[0], CbActions::GifDecision), "-7:1");
+    assert_eq!(
+        assert_action(buttons.last().unwrap(), CbActions::ReviewBack),
+        "g7"
+    );
 }
 
 #[test]
@@ -240,6 +271,17 @@ fn no_button_label_is_an_unresolved_locale_key() {
         keyboards::keyboard_link_to_chat(ltag),
         keyboards::keyboard_new_name(ltag, USER, "Pig".to_owned()),
         keyboards::keyboard_in_top10(ltag, USER, Top10Variant::Global),
+        keyboards::keyboard_voice_reject(ltag, USER, 1),
+        keyboards::keyboard_gif_reject(ltag, USER, 1),
+        keyboards::keyboard_settings(ltag, USER),
+        keyboards::keyboard_settings_lang(ltag, USER, None),
+        keyboards::keyboard_settings_flags(ltag, USER, 0, "ua"),
+        keyboards::keyboard_settings_toggles(
+            ltag,
+            USER,
+            &UserSettings::new(1),
+            UserSetting::VARIANTS,
+        ),
     ];
 
     for markup in &all {
@@ -294,9 +336,20 @@ fn every_keyboard_payload_stays_within_telegrams_limit() {
         keyboards::keyboard_change_flag(ltag, USER, "ua"),
         keyboards::keyboard_change_lang(ltag, USER, "uk"),
         keyboards::keyboard_reset_vote(ltag, USER),
-        keyboards::keyboard_voice_check(USER),
-        keyboards::keyboard_gif_check(USER),
+        keyboards::keyboard_voice_check(USER, i16::MAX),
+        keyboards::keyboard_gif_check(USER, i16::MAX),
+        keyboards::keyboard_voice_reject(ltag, USER, i16::MAX),
+        keyboards::keyboard_gif_reject(ltag, USER, i16::MAX),
         keyboards::keyboard_in_top10(ltag, USER, Top10Variant::PGlobal),
+        keyboards::keyboard_settings(ltag, USER),
+        keyboards::keyboard_settings_lang(ltag, USER, Some("uk")),
+        keyboards::keyboard_settings_flags(ltag, USER, usize::MAX, ""),
+        keyboards::keyboard_settings_toggles(
+            ltag,
+            USER,
+            &UserSettings::new(1),
+            UserSetting::VARIANTS,
+        ),
     ];
 
     for markup in &all {
@@ -315,3 +368,135 @@ fn every_keyboard_payload_stays_within_telegrams_limit() {
         }
     }
 }
+
+fn settings_page(button: &InlineKeyboardButton) -> SettingsPage {
+    let payload = assert_action(button, CbActions::Settings);
+    SettingsPage::from_payload(&payload)
+        .unwrap_or_else(|| panic!("{payload:?} is not a settings page"))
+}
+
+#[test]
+fn the_settings_menu_opens_every_page() {
+    let ltag = setup();
+
+    let markup = keyboards::keyboard_settings(ltag, USER);
+    let pages: Vec<_> =
+        rows(&markup).iter().flatten().map(settings_page).collect();
+
+    assert_eq!(
+        pages,
+        [
+            SettingsPage::Lang,
+            SettingsPage::Flags(0),
+            SettingsPage::Notifications,
+            SettingsPage::Privacy,
+        ]
+    );
+}
+
+#[test]
+fn the_flag_picker_pages_through_every_flag_once() {
+    let ltag = setup();
+
+    let mut seen = vec![];
+    let mut page = 0;
+
+    loop {
+        let markup = keyboards::keyboard_settings_flags(ltag, USER, page, "");
+        let (nav, flags) = rows(&markup).split_last().unwrap();
+
+        for button in flags.iter().flatten() {
+            seen.push(assert_action(button, CbActions::SettingsFlag));
+        }
+
+        let nav: Vec<_> = nav.iter().map(settings_page).collect();
+        assert!(
+            nav.contains(&SettingsPage::Main),
+            "page {page} has no way back"
+        );
+        assert_eq!(
+            nav.contains(&SettingsPage::Flags(page.wrapping_sub(1))),
+            page > 0
+        );
+
+        if !nav.contains(&SettingsPage::Flags(page + 1)) {
+            break;
+        }
+        page += 1;
+    }
+
+    assert_eq!(seen, Flags::CODES);
+
+    // A stale button past the end shows the last page rather than nothing.
+    let past_the_end =
+        keyboards::keyboard_settings_flags(ltag, USER, page + 5, "");
+    let last = keyboards::keyboard_settings_flags(ltag, USER, page, "");
+    assert_eq!(rows(&past_the_end), rows(&last));
+}
+
+#[test]
+fn the_flag_picker_marks_the_current_flag() {
+    let ltag = setup();
+
+    let markup = keyboards::keyboard_settings_flags(ltag, USER, 0, "ad");
+    let first = &rows(&markup)[0][0];
+
+    assert_eq!(first.text, "✅");
+    assert_eq!(assert_action(first, CbActions::SettingsFlag), "ad");
+}
+
+#[test]
+fn the_lang_picker_offers_every_language_and_automatic() {
+    let ltag = setup();
+
+    let markup = keyboards::keyboard_settings_lang(ltag, USER, Some("en"));
+    let (back, langs) = rows(&markup).split_last().unwrap();
+
+    let buttons: Vec<_> = langs.iter().flatten().collect();
+    let codes: Vec<_> = buttons
+        .iter()
+        .map(|b| assert_action(b, CbActions::SettingsLang))
+        .collect();
+
+    let mut expected = get_langs();
+    expected.push("-".to_owned());
+    assert_eq!(codes, expected);
+
+    let marked: Vec<_> =
+        buttons.iter().filter(|b| b.text.starts_with("✅")).collect();
+    assert_eq!(marked.len(), 1);
+    assert_eq!(assert_action(marked[0], CbActions::SettingsLang), "en");
+
+    assert_eq!(settings_page(&back[0]), SettingsPage::Main);
+}
+
+#[test]
+fn the_toggles_show_the_stored_state() {
+    let ltag = setup();
+
+    let mut settings = UserSettings::new(1);
+    settings.toggle(UserSetting::NotifyDuels);
+
+    let switches = [UserSetting::NotifyDayPig, UserSetting::NotifyDuels];
+    let markup =
+        keyboards::keyboard_settings_toggles(ltag, USER, &settings, &switches);
+    let (back, toggles) = rows(&markup).split_last().unwrap();
+
+    let states: Vec<_> = toggles
+        .iter()
+        .map(|row| {
+            let button = &row[0];
+            let name = assert_action(button, CbActions::SettingsToggle);
+            (name, button.text.starts_with("✅"))
+        })
+        .collect();
+
+    assert_eq!(
+        states,
+        [
+            ("notify_day_pig".to_owned(), false),
+            ("notify_duels".to_owned(), true)
+        ]
+    );
+    assert_eq!(settings_page(&back[0]), SettingsPage::Main);
+}
@@ -6,7 +6,9 @@ use std::path::PathBuf;
 
 use crate::{
     config::consts::DEFAULT_LANG_TAG,
-    lang::{get_langs, lng},
+    lang::{
+        InnerLang, LOCALE_LOCAL_KEYS, Locale, get_langs, has_key, lng, tag,
+    },
     test_support::init_lang,
 };
 
@@ -47,9 +49,6 @@ fn the_expected_locales_are_present() {
     assert!(all.contains_key(DEFAULT_LANG_TAG));
 }
 
-/// Keys a locale may carry without the default locale having them.
-const LOCALE_LOCAL_KEYS: &[&str] = &["AUTHOR_OF_THIS_TRANSLATION"];
-
 #[test]
 fn every_locale_has_the_same_keys_as_the_default_one() {
     let all = load_all();
@@ -79,9 +78,17 @@ fn every_locale_has_the_same_keys_as_the_default_one() {
     assert!(problems.is_empty(), "locale drift:\n{}", problems.join("\n"));
 }
 
+#[test]
+fn the_shipped_locales_pass_the_reload_check() {
+    let locale = Locale::load_from(locales_dir(), DEFAULT_LANG_TAG)
+        .unwrap_or_else(|err| panic!("{err}"));
+
+    assert_eq!(locale.check(), Vec::<String>::new());
+}
+
 #[test]
 fn every_locale_value_is_a_string() {
-    // `Locale::new` panics on a non-string value, taking the bot down at
+    // `Locale::new` refuses a non-string value, taking the bot down at
     // startup.
     for entry in std::fs::read_dir(locales_dir()).unwrap() {
         let path = entry.unwrap().path();
@@ -112,11 +119,8 @@ fn every_key_resolves_in_every_locale_at_runtime() {
 
     for (ltag, _) in langs.iter().enumerate() {
         for key in reference {
-            let text = lng(key, ltag);
-            assert!(
-                !text.starts_with("lang: key"),
-                "tag {ltag} could not resolve {key}"
-            );
+            // `lng` would fall back to the default language.
+            assert!(has_key(key, ltag), "tag {ltag} could not resolve {key}");
         }
     }
 }
@@ -133,11 +137,22 @@ fn the_achievement_names_cover_every_code() {
     for (ltag, tag) in langs.iter().enumerate() {
         for ach in Ach::VARIANTS {
             let key = format!("Achievement_{}", *ach as i16);
-            let text = lng(&key, ltag);
-            assert!(
-                !text.starts_with("lang:"),
-                "{tag} is missing {key} ({ach:?})"
-            );
+            assert!(has_key(&key, ltag), "{tag} is missing {key} ({ach:?})");
+        }
+    }
+}
+
+#[test]
+fn the_user_setting_labels_cover_every_setting() {
+    use crate::enums::UserSetting;
+    use strum::VariantArray;
+
+    init_lang();
+
+    for (ltag, tag) in get_langs().iter().enumerate() {
+        for setting in UserSetting::VARIANTS {
+            let key = format!("UserSetting_{}", setting.into_str());
+            assert!(has_key(&key, ltag), "{tag}: {key}");
         }
     }
 }
@@ -155,7 +170,7 @@ fn the_growth_status_messages_cover_every_status() {
             PigGrowthStatus::Gained,
         ] {
             let key = format!("GamePigGrowMessage_{}", status.into_str());
-            assert!(!lng(&key, ltag).starts_with("lang:"), "{tag}: {key}");
+            assert!(has_key(&key, ltag), "{tag}: {key}");
         }
     }
 }
@@ -174,7 +189,60 @@ fn the_duel_result_messages_cover_every_outcome() {
             DuelResult::Knockout,
         ] {
             let key = format!("InlineDuelMessage_{}", status.into_str());
-            assert!(!lng(&key, ltag).starts_with("lang:"), "{tag}: {key}");
+            assert!(has_key(&key, ltag), "{tag}: {key}");
+        }
+    }
+}
+
+#[test]
+fn the_duel_notifications_cover_every_outcome() {
+    use strum::VariantArray;
+
+    use crate::services::notifications::DuelOutcome;
+
+    init_lang();
+
+    for (ltag, tag) in get_langs().iter().enumerate() {
+        for outcome in DuelOutcome::VARIANTS {
+            let key = format!("NotifyDuel{}", outcome.into_str());
+            assert!(has_key(&key, ltag), "{tag}: {key}");
+        }
+    }
+}
+
+#[test]
+fn the_broadcast_status_names_cover_every_status() {
+    use crate::config::consts::{
+        BROADCAST_CANCELLED, BROADCAST_DONE, BROADCAST_PAUSED,
+        BROADCAST_RUNNING,
+    };
+
+    init_lang();
+
+    for (ltag, tag) in get_langs().iter().enumerate() {
+        for status in [
+            BROADCAST_RUNNING,
+            BROADCAST_PAUSED,
+            BROADCAST_CANCELLED,
+            BROADCAST_DONE,
+        ] {
+            let key = format!("BroadcastStatus_{}", status);
+            assert!(has_key(&key, ltag), "{tag}: {key}");
+        }
+    }
+}
+
+#[test]
+fn the_moderation_action_names_cover_every_action() {
+    use crate::enums::ModerationAction;
+    use strum::VariantArray;
+
+    init_lang();
+
+    for (ltag, tag) in get_langs().iter().enumerate() {
+        for &action in ModerationAction::VARIANTS {
+            let key = format!("ModerationAction_{}", action as i16);
+            assert!(has_key(&key, ltag), "{tag}: {key}");
         }
     }
 }
@@ -193,7 +261,7 @@ fn every_advertised_command_has_a_description_in_every_locale() {
         for command in &commands {
             let key = format!("{}_desc", command.command);
             let text = lng(&key, ltag);
-            assert!(!text.starts_with("lang:"), "{tag} is missing {key}");
+            assert!(has_key(&key, ltag), "{tag} is missing {key}");
             // Telegram rejects command descriptions over 256 chars.
             assert!(text.len() <= 256, "{tag}/{key} is {} bytes", text.len());
         }
@@ -201,15 +269,46 @@ fn every_advertised_command_has_a_description_in_every_locale() {
 }
 
 #[test]
-fn the_plural_forms_exist_for_every_unit() {
+fn the_grow_cooldown_agrees_with_its_numbers() {
     init_lang();
 
-    for (ltag, tag) in get_langs().iter().enumerate() {
-        for unit in ["hour", "minute", "second"] {
-            for rule in 0..3 {
-                let key = format!("{unit}_{rule}");
-                assert!(!lng(&key, ltag).starts_with("lang:"), "{tag}: {key}");
-            }
+    let cooldown = |lang, hours, minutes| {
+        let ltag = tag(lang);
+
+        lng("GameNextFeedingToHoursMinutes", ltag)
+            .plural(ltag, "hours", hours)
+            .plural(ltag, "minutes", minutes)
+    };
+
+    assert_eq!(
+        cooldown("uk", 1, 22),
+        "Наступна годівля через 1 годину 22 хвилини."
+    );
+    assert_eq!(
+        cooldown("ru", 5, 11),
+        "Следующая кормёжка через 5 часов 11 минут."
+    );
+    assert_eq!(cooldown("en", 1, 2), "Next feeding in 1 hour and 2 minutes.");
+}
+
+#[test]
+fn every_locale_has_a_blocklist_that_rejects_its_own_words() {
+    use crate::services::name_filter::Blocklist;
+
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("blocklists");
+    let list = Blocklist::load_from(&dir);
+
+    for tag in load_all().keys() {
+        let raw = std::fs::read_to_string(dir.join(format!("{tag}.json")))
+            .unwrap_or_else(|_| panic!("no blocklists/{tag}.json"));
+        let words: Vec<String> =
+            serde_json::from_str(&raw).expect("blocklist is not a string list");
+
+        assert!(!words.is_empty(), "blocklists/{tag}.json is empty");
+
+        for word in &words {
+            let word = word.trim_end_matches('*');
+            assert!(!list.allows(word, false), "{tag}: {word}");
         }
     }
 }
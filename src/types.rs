@@ -1,5 +1,8 @@
 use diesel_async::pooled_connection::deadpool::{Pool, PoolError};
-use teloxide::{adaptors::DefaultParseMode, prelude::*};
+use teloxide::{
+    adaptors::{DefaultParseMode, Throttle},
+    prelude::*,
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum MyError {
@@ -23,7 +26,12 @@ pub enum MyError {
     Unknown(String),
 }
 
-pub type MyBot = DefaultParseMode<Bot>;
+/// Sending requests (`send_*`, `forward_message`, `copy_message`...) wait for
+/// Telegram's per-chat and global limits, and one answered with `RetryAfter`
+/// is sent again once it passes. Everything else, e.g. `get_chat_member`,
+/// goes straight through and can still fail with `RetryAfter`. See
+/// [`crate::setup::setup_bot`].
+pub type MyBot = DefaultParseMode<Throttle<Bot>>;
 pub type MyResult<T> = Result<T, MyError>;
 
 pub type ParsedCallbackData<'a> = (&'a str, UserId, &'a str);
@@ -49,6 +49,16 @@ pub fn get_datetime_from_message_date(
     FIXED_OFFSET.from_local_datetime(&datetime.naive_utc()).unwrap().naive_utc()
 }
 
+/// First day of the month `date` falls in — the bucket key for the
+/// `inline_*_uses` tables.
+pub fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap()
+}
+
+pub fn previous_month_start(date: NaiveDate) -> NaiveDate {
+    month_start(month_start(date) - Duration::days(1))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,4 +154,22 @@ mod tests {
         // A leap day, for good measure.
         assert_eq!(get_timediff(dt(2024, 2, 28, 22, 0, 0)), (2, 0, 0));
     }
+
+    #[test]
+    fn month_start_is_the_first_of_the_same_month() {
+        let d = |m, day| NaiveDate::from_ymd_opt(2026, m, day).unwrap();
+
+        assert_eq!(month_start(d(7, 28)), d(7, 1));
+        assert_eq!(month_start(d(7, 1)), d(7, 1));
+        assert_eq!(month_start(d(2, 28)), d(2, 1));
+    }
+
+    #[test]
+    fn previous_month_start_crosses_the_year_boundary() {
+        let d = |y, m, day| NaiveDate::from_ymd_opt(y, m, day).unwrap();
+
+        assert_eq!(previous_month_start(d(2026, 7, 28)), d(2026, 6, 1));
+        assert_eq!(previous_month_start(d(2026, 3, 1)), d(2026, 2, 1));
+        assert_eq!(previous_month_start(d(2026, 1, 15)), d(2025, 12, 1));
+    }
 }
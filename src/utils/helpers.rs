@@ -169,23 +169,6 @@ pub fn escape_links(s: &str) -> String {
     }
 }
 
-// Plural rule for languages, may some inaccurate - but it works!
-pub fn plural(n: i64) -> i64 {
-    // `%` keeps the sign of the dividend, so negatives used to fall through
-    // to "many". `unsigned_abs` over `abs` so `i64::MIN` cannot overflow.
-    let magnitude = n.unsigned_abs();
-    let last_digit = magnitude % 10;
-    let last_two = magnitude % 100;
-
-    if last_digit == 1 && last_two != 11 {
-        0
-    } else if (2..=4).contains(&last_digit) && !(10..20).contains(&last_two) {
-        1
-    } else {
-        2
-    }
-}
-
 #[allow(unused)]
 pub fn db_debug<T>(query: &T) -> DebugQuery<'_, T, Pg> {
     debug_query::<Pg, _>(query)
@@ -517,75 +500,6 @@ mod tests {
     }
 
 
-    #[test]
-    fn plural_follows_the_slavic_rule() {
-        let ones = [1, 21, 31, 101, 121];
-        let few = [2, 3, 4, 22, 23, 24, 102];
-        let many = [0, 5, 6, 9, 10, 11, 12, 13, 14, 15, 19, 20, 25, 100, 111];
-
-        for n in ones {
-            assert_eq!(plural(n), 0, "n = {n}");
-        }
-        for n in few {
-            assert_eq!(plural(n), 1, "n = {n}");
-        }
-        for n in many {
-            assert_eq!(plural(n), 2, "n = {n}");
-        }
-    }
-
-    #[test]
-    fn plural_only_ever_returns_zero_one_or_two() {
-        for n in 0..1_000i64 {
-            assert!((0..=2).contains(&plural(n)), "n = {n}");
-        }
-    }
-
-    #[test]
-    fn plural_treats_negatives_by_magnitude() {
-        // Rust's `%` keeps the sign of the dividend, so every negative input
-        // used to fall through to the "many" bucket.
-        for n in 1..500i64 {
-            assert_eq!(plural(-n), plural(n), "n = {n}");
-        }
-
-        assert_eq!(plural(-1), 0);
-        assert_eq!(plural(-2), 1);
-        assert_eq!(plural(-11), 2);
-        assert_eq!(plural(-21), 0);
-    }
-
-    #[test]
-    fn plural_handles_the_extremes_without_overflowing() {
-        // `unsigned_abs`, not `abs`: `i64::MIN.abs()` would panic.
-        for n in [i64::MIN, i64::MIN + 1, i64::MAX, 0] {
-            assert!((0..=2).contains(&plural(n)), "n = {n}");
-        }
-    }
-
-    #[test]
-    fn plural_is_unchanged_for_the_values_callers_actually_pass() {
-        // `get_timediff` yields 0..=24 hours and 0..60 minutes/seconds.
-        let expected: Vec<i64> = (0..=60)
-            .map(|n: i64| {
-                if n % 10 == 1 && n % 100 != 11 {
-                    0
-                } else if (2..=4).contains(&(n % 10))
-                    && (n % 100 < 10 || n % 100 >= 20)
-                {
-                    1
-                } else {
-                    2
-                }
-            })
-            .collect();
-
-        for (n, want) in expected.iter().enumerate() {
-            assert_eq!(plural(n as i64), *want, "n = {n}");
-        }
-    }
-
-
     #[test]
     fn hashing_is_stable_within_a_run() {
         assert_eq!(get_hash("abc"), get_hash("abc"));
@@ -179,6 +179,20 @@ pub fn rename_hryak_info(
     .thumbnail_url(get_photostock(Image::NameSuccess))
 }
 
+pub fn name_rejected_info(ltag: LocaleTag) -> InlineQueryResultArticle {
+    let caption = lng("PigNameRejected", ltag);
+
+    InlineQueryResultArticle::new(
+        InlineResults::NameRejectedInfo.to_string_with_args(),
+        &caption,
+        InputMessageContent::Text(
+            InputMessageContentText::new(&caption)
+                .link_preview_options(LinkPreviewOptions::disable(true)),
+        ),
+    )
+    .thumbnail_url(get_photostock(Image::Error))
+}
+
 pub fn day_pig_info(
     ltag: LocaleTag,
     id_user: UserId,
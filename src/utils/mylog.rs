@@ -1,14 +1,35 @@
+//! Errors for the log group.
+//!
+//! Each error is fingerprinted by its kind and where it was raised. The
+//! first of a fingerprint is sent right away; repeats are only counted and
+//! go out together in a digest every [`DIGEST_WINDOW`], so an outage is a
+//! few lines instead of hundreds of identical messages. A fingerprint with
+//! no repeats for a whole window is forgotten, and its next error is sent
+//! right away again.
+
+use std::collections::hash_map::Entry;
 use std::fmt::Debug;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock, Mutex, PoisonError};
+use std::time::Duration;
 
-use futures::future::BoxFuture;
+use ahash::AHashMap;
 use futures::FutureExt;
+use futures::future::BoxFuture;
 use teloxide::{
     error_handlers::ErrorHandler, requests::Requester, types::ChatId,
 };
 
 use crate::config::env::{BOT_CONFIG, bot_static};
 
+const DIGEST_WINDOW: Duration = Duration::from_secs(300);
+/// Keeps a digest well inside one message.
+const DIGEST_MAX_LINES: usize = 30;
+/// For messages with no `{:?}` of an error to take the kind from.
+const KIND_MAX_LEN: usize = 60;
+
+static DIGEST: LazyLock<Mutex<ErrorDigest>> =
+    LazyLock::new(|| Mutex::new(ErrorDigest::default()));
+
 pub struct MyErrorHandler {
     text: String,
 }
@@ -34,7 +55,8 @@ where
         let error_text = format!("{text}: {:?}", error, text = self.text);
         log::error!("{}", &error_text);
 
-        log_error(error_text).boxed()
+        report_error(error_text, "dispatcher");
+        futures::future::ready(()).boxed()
     }
 }
 
@@ -48,13 +70,272 @@ pub fn fire_log_error(text: String) {
     tokio::spawn(log_error(text));
 }
 
+/// Sends `text` now if it is the first of its fingerprint, otherwise only
+/// counts it for the next digest.
+pub fn report_error(text: String, location: &str) {
+    let fingerprint = fingerprint(&text, location);
+    let first = DIGEST
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .record(fingerprint);
+
+    if first {
+        fire_log_error(text);
+    }
+}
+
+pub async fn run_error_digest() {
+    let mut interval = tokio::time::interval(DIGEST_WINDOW);
+    // The first tick is immediate and there is nothing to report yet.
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let repeats =
+            DIGEST.lock().unwrap_or_else(PoisonError::into_inner).take();
+
+        if let Some(text) = digest_text(&repeats, DIGEST_WINDOW) {
+            log::warn!("{}", text);
+            log_error(text).await;
+        }
+    }
+}
+
+#[derive(Default)]
+struct ErrorDigest {
+    /// Repeats since the last digest, per fingerprint.
+    repeats: AHashMap<String, u64>,
+}
+
+impl ErrorDigest {
+    /// `true` for the first error of its fingerprint.
+    fn record(&mut self, fingerprint: String) -> bool {
+        match self.repeats.entry(fingerprint) {
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() += 1;
+                false
+            },
+            Entry::Vacant(entry) => {
+                entry.insert(0);
+                true
+            },
+        }
+    }
+
+    /// Repeats since the last call, most frequent first.
+    fn take(&mut self) -> Vec<(String, u64)> {
+        self.repeats.retain(|_, count| *count > 0);
+
+        let mut taken: Vec<(String, u64)> = self
+            .repeats
+            .iter_mut()
+            .map(|(fingerprint, count)| {
+                (fingerprint.clone(), std::mem::take(count))
+            })
+            .collect();
+
+        taken.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        taken
+    }
+}
+
+fn digest_text(repeats: &[(String, u64)], window: Duration) -> Option<String> {
+    if repeats.is_empty() {
+        return None;
+    }
+
+    let minutes = window.as_secs() / 60;
+    let mut text = format!("Repeated errors, last {minutes} min:");
+
+    for (fingerprint, count) in repeats.iter().take(DIGEST_MAX_LINES) {
+        text += &format!("\n{fingerprint} ×{count}");
+    }
+
+    if repeats.len() > DIGEST_MAX_LINES {
+        let rest = repeats.len() - DIGEST_MAX_LINES;
+        text += &format!("\n…and {rest} more");
+    }
+
+    Some(text)
+}
+
+fn fingerprint(text: &str, location: &str) -> String {
+    format!("{} at {}", error_kind(text), location)
+}
+
+/// `RequestError::Api::BotBlocked` from the first `{:?}` of an error in
+/// `text`: at most three nested variant names, ignoring their payload.
+/// Without one, the text up to its first variable-looking part.
+fn error_kind(text: &str) -> String {
+    let Some(start) = first_variant(text) else {
+        let end = text
+            .find(|c: char| "[\"'{(:".contains(c) || c.is_ascii_digit())
+            .unwrap_or(text.len());
+        return text[..end].trim().chars().take(KIND_MAX_LEN).collect();
+    };
+
+    let mut path = vec![];
+    let mut rest = &text[start..];
+
+    while path.len() < 3 {
+        let len = ident_len(rest);
+        let ident = &rest[..len];
+
+        if !ident.starts_with(|c: char| c.is_ascii_uppercase()) {
+            break;
+        }
+
+        path.push(ident);
+        rest = &rest[len..];
+
+        let Some(inner) = rest.strip_prefix('(') else { break };
+        rest = inner;
+    }
+
+    path.join("::")
+}
+
+/// Byte offset of the first `Name(` that starts a word.
+fn first_variant(text: &str) -> Option<usize> {
+    let mut prev = ' ';
+
+    for (i, c) in text.char_indices() {
+        let starts_word =
+            !(prev.is_alphanumeric() || prev == '_' || prev == ':');
+
+        if starts_word && c.is_ascii_uppercase() {
+            let len = ident_len(&text[i..]);
+            if text[i + len..].starts_with('(') {
+                return Some(i);
+            }
+        }
+
+        prev = c;
+    }
+
+    None
+}
+
+fn ident_len(text: &str) -> usize {
+    text.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(text.len())
+}
+
 #[macro_export]
 macro_rules! myerr {
     ($($arg:tt)+) => {
         {
             let error_text = format!($($arg)+);
             log::error!("{}", &error_text);
-            $crate::utils::mylog::fire_log_error(error_text);
+            $crate::utils::mylog::report_error(
+                error_text,
+                concat!(file!(), ":", line!()),
+            );
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_kind_is_the_nested_variant_path() {
+        let cases = [
+            (
+                "Error RequestError(Network(reqwest::Error { kind: Request })) \
+                 in command: !top",
+                "RequestError::Network",
+            ),
+            (
+                "Error: RequestError(Api(BotBlocked))",
+                "RequestError::Api::BotBlocked",
+            ),
+            ("Error Database(NotFound) in callback", "Database::NotFound"),
+            ("Error: PoolError(Timeout(Wait))", "PoolError::Timeout::Wait"),
+        ];
+
+        for (text, kind) in cases {
+            assert_eq!(error_kind(text), kind, "{text}");
+        }
+    }
+
+    #[test]
+    fn without_a_variant_the_variable_tail_is_dropped() {
+        assert_eq!(
+            error_kind("Undefined chosen inline: [abc] user: [42]"),
+            "Undefined chosen inline"
+        );
+        assert_eq!(
+            error_kind("No chat member in new chat members, wtf?"),
+            "No chat member in new chat members, wtf?"
+        );
+    }
+
+    #[test]
+    fn the_same_error_elsewhere_is_another_fingerprint() {
+        let text = "Error RequestError(Network(timeout))";
+
+        assert_eq!(
+            fingerprint(text, "src/a.rs:1"),
+            "RequestError::Network at src/a.rs:1"
+        );
+        assert_ne!(
+            fingerprint(text, "src/a.rs:1"),
+            fingerprint(text, "src/b.rs:2")
+        );
+    }
+
+    #[test]
+    fn only_the_first_of_a_fingerprint_is_sent_right_away() {
+        let mut digest = ErrorDigest::default();
+
+        assert!(digest.record("a".to_owned()));
+        assert!(!digest.record("a".to_owned()));
+        assert!(!digest.record("a".to_owned()));
+        assert!(digest.record("b".to_owned()));
+
+        assert_eq!(digest.take(), vec![("a".to_owned(), 2)]);
+    }
+
+    #[test]
+    fn a_fingerprint_quiet_for_a_window_is_sent_right_away_again() {
+        let mut digest = ErrorDigest::default();
+
+        digest.record("a".to_owned());
+        digest.record("a".to_owned());
+
+        // Still repeating: stays in the digest.
+        digest.take();
+        assert!(!digest.record("a".to_owned()));
+
+        // A window with one repeat, then a quiet one.
+        digest.take();
+        assert!(digest.take().is_empty());
+        assert!(digest.record("a".to_owned()));
+    }
+
+    #[test]
+    fn the_digest_lists_the_most_frequent_first() {
+        let repeats =
+            vec![("x at y".to_owned(), 312), ("z at w".to_owned(), 2)];
+
+        assert_eq!(
+            digest_text(&repeats, Duration::from_secs(300)).unwrap(),
+            "Repeated errors, last 5 min:\nx at y ×312\nz at w ×2"
+        );
+        assert_eq!(digest_text(&[], Duration::from_secs(300)), None);
+    }
+
+    #[test]
+    fn a_long_digest_is_cut() {
+        let repeats: Vec<_> =
+            (0..DIGEST_MAX_LINES + 5).map(|i| (i.to_string(), 1)).collect();
+
+        let text = digest_text(&repeats, Duration::from_secs(300)).unwrap();
+
+        assert_eq!(text.lines().count(), DIGEST_MAX_LINES + 2);
+        assert!(text.ends_with("…and 5 more"));
+    }
+}
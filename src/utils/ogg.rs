@@ -25,3 +25,101 @@ fn _transcode(input_data: bytes::Bytes, volume_factor: f32) -> Option<Vec<u8>> {
 
     ogg_opus::encode::<48000, 1>(&raw).ok()
 }
+
+/// Bits in a fingerprint, one per step between adjacent frames.
+const FINGERPRINT_BITS: usize = 64;
+/// Samples at or below this amplitude don't count as the start or the end of
+/// a recording.
+const SILENCE_THRESHOLD: u16 = 500;
+
+/// Decodes and fingerprints on the rayon pool, like [`increase_sound`].
+pub async fn voice_fingerprint(input_data: bytes::Bytes) -> Option<i64> {
+    let (send, recv) = tokio::sync::oneshot::channel();
+
+    rayon::spawn(move || {
+        let fingerprint = ogg_opus::decode::<_, 48000>(Cursor::new(input_data))
+            .ok()
+            .and_then(|(raw, _)| fingerprint(&raw));
+        let _ = send.send(fingerprint);
+    });
+
+    recv.await.ok()?
+}
+
+/// Splits the non-silent part into equal frames and sets bit `i` when frame
+/// `i + 1` is louder than frame `i`. Volume and padding don't move it, so a
+/// re-upload or a re-recording of the same grunt lands a few bits away.
+/// `None` for silence or anything too short to split.
+pub fn fingerprint(pcm: &[i16]) -> Option<i64> {
+    let loud = |s: &i16| s.unsigned_abs() > SILENCE_THRESHOLD;
+    let start = pcm.iter().position(loud)?;
+    let end = pcm.iter().rposition(loud)? + 1;
+    let voiced = &pcm[start..end];
+
+    let frame = voiced.len() / (FINGERPRINT_BITS + 1);
+    if frame == 0 {
+        return None;
+    }
+
+    let energy: Vec<u64> = voiced
+        .chunks_exact(frame)
+        .take(FINGERPRINT_BITS + 1)
+        .map(|chunk| chunk.iter().map(|&s| (s as i64 * s as i64) as u64).sum())
+        .collect();
+
+    let bits = energy
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair[1] > pair[0])
+        .fold(0u64, |acc, (i, _)| acc | (1 << i));
+
+    Some(bits as i64)
+}
+
+pub fn fingerprint_distance(a: i64, b: i64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::*;
+
+    /// A tone whose loudness wobbles `wobble` times a second.
+    fn recording(len: usize, gain: f32, wobble: f32) -> Vec<i16> {
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / 48_000.0;
+                let envelope = 0.5 + 0.5 * (t * wobble * TAU).sin();
+                (gain * envelope * (t * 220.0 * TAU).sin()) as i16
+            })
+            .collect()
+    }
+
+    #[test]
+    fn volume_and_silence_padding_do_not_change_the_fingerprint() {
+        let original = recording(48_000, 8_000.0, 3.0);
+        let louder = recording(48_000, 16_000.0, 3.0);
+        let padded =
+            [vec![0; 4_800], original.clone(), vec![0; 9_600]].concat();
+
+        let fp = fingerprint(&original).unwrap();
+        assert!(fingerprint_distance(fp, fingerprint(&louder).unwrap()) <= 2);
+        assert!(fingerprint_distance(fp, fingerprint(&padded).unwrap()) <= 2);
+    }
+
+    #[test]
+    fn a_different_recording_is_far_away() {
+        let a = fingerprint(&recording(48_000, 8_000.0, 3.0)).unwrap();
+        let b = fingerprint(&recording(48_000, 8_000.0, 5.0)).unwrap();
+
+        assert!(fingerprint_distance(a, b) > 16);
+    }
+
+    #[test]
+    fn silence_and_tiny_clips_have_no_fingerprint() {
+        assert_eq!(fingerprint(&[0; 48_000]), None);
+        assert_eq!(fingerprint(&[1_000; 10]), None);
+    }
+}